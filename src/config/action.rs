@@ -0,0 +1,657 @@
+//! Bindable actions and the key binding table
+//!
+//! Every keybind resolves to an `Action`. The legacy named fields in
+//! `[keybinds]` (copy, paste, split_right, ...) and user-defined
+//! `[[keybinds.bind]]` entries are merged into a single `KeyBindings` table.
+//!
+//! Example:
+//! ```toml
+//! [[keybinds.bind]]
+//! key = "alt+1"
+//! action = "goto_tab"
+//! n = 1
+//!
+//! [[keybinds.bind]]
+//! key = "ctrl+shift+w"
+//! action = "unbind"
+//! ```
+//!
+//! Key tables (tmux-style prefix) are entered through a leader key and
+//! have their own bindings:
+//! ```toml
+//! [[keybinds.table]]
+//! name = "prefix"
+//! key = "ctrl+a"
+//!
+//! [[keybinds.table.bind]]
+//! key = "|"
+//! action = "split"
+//! direction = "right"
+//! ```
+//!
+//! Mouse buttons are bound the same way through `[[mouse.bind]]`:
+//! ```toml
+//! [[mouse.bind]]
+//! button = "right"
+//! action = "paste"
+//! ```
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{deserialize_keybind, KeybindConfig, MouseConfig, ParsedKeybind};
+use crate::pane;
+
+/// Action triggered by a keybind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Copy selection to clipboard
+    Copy,
+    /// Paste from clipboard
+    Paste,
+    /// Paste the primary selection
+    PastePrimary,
+    /// Open the clipboard history picker
+    ClipboardHistory,
+    /// Save screenshot
+    Screenshot,
+    /// Write the scrollback and screen to a file in paths.screenshot_dir
+    ExportScrollback {
+        #[serde(default)]
+        format: ExportFormat,
+    },
+    /// Open the scrollback in a pager pane ([terminal] scrollback_pager)
+    ShowScrollback {
+        /// Keep colors and links as escape sequences (for `less -R`)
+        #[serde(default)]
+        ansi: bool,
+    },
+    /// Start scrollback search
+    Search,
+    /// Enter copy mode
+    CopyMode,
+    /// Label on-screen matches (URLs, paths, hashes, ...) for keyboard selection
+    Hints {
+        #[serde(default)]
+        mode: HintMode,
+    },
+    /// Increase font size
+    FontIncrease,
+    /// Decrease font size
+    FontDecrease,
+    /// Reset font size
+    FontReset,
+    /// Scroll back half a page
+    ScrollUp,
+    /// Scroll forward half a page
+    ScrollDown,
+    /// Scroll by a number of lines (negative = toward older output)
+    Scroll { lines: i32 },
+    /// Reset enhanced input modes
+    ResetTerminal,
+    /// Toggle notification panel
+    NotificationPanel,
+    /// Toggle notification mute
+    NotificationMute,
+    /// Activate the newest toast notification (focus its pane and tab)
+    NotificationActivate,
+    /// Split the active pane
+    Split {
+        #[serde(default)]
+        direction: SplitDirection,
+        /// Fraction of the space given to the new pane (0.1-0.9)
+        #[serde(default = "default_split_ratio")]
+        ratio: f32,
+    },
+    /// Close the active pane
+    ClosePane,
+    /// Move focus to an adjacent pane
+    FocusPane { direction: PaneDirection },
+    /// Grow/shrink the active pane
+    ResizePane {
+        direction: PaneDirection,
+        #[serde(default = "default_resize_amount")]
+        amount: f32,
+    },
+    /// Toggle zoom for the active pane
+    ZoomPane,
+    /// Open a new tab
+    NewTab,
+    /// Close the active tab
+    CloseTab,
+    /// Switch to the next tab
+    NextTab,
+    /// Switch to the previous tab
+    PrevTab,
+    /// Switch to tab number `n` (1-based)
+    GotoTab { n: usize },
+    /// Write text to the PTY (TOML escapes like "\u001b" can be used)
+    SendText { text: String },
+    /// Enter a key table ([[keybinds.table]])
+    ActivateTable { name: String },
+    /// Leave the active key table
+    DeactivateTable,
+    /// Forward the key to the application, bypassing all bcon handling
+    Passthrough,
+    /// Start a selection at the pointer, extended by dragging (mouse only)
+    StartSelection,
+    /// Start a rectangular selection at the pointer (mouse only)
+    StartBlockSelection,
+    /// Move the selection end to the pointer (mouse only)
+    ExtendSelection,
+    /// Select the word under the pointer (mouse only)
+    SelectWord,
+    /// Select the line under the pointer (mouse only)
+    SelectLine,
+    /// Open the link under the pointer (mouse only)
+    OpenLink,
+    /// Copy the link under the pointer to the clipboard (mouse only)
+    CopyLink,
+    /// Remove earlier bindings for this key
+    Unbind,
+}
+
+/// What to do with a match picked in hints mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HintMode {
+    /// Copy to clipboard
+    #[default]
+    Copy,
+    /// Paste into the terminal
+    Paste,
+    /// Open as a link
+    Open,
+}
+
+/// File format of a scrollback export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Plain text, soft-wrapped lines joined
+    #[default]
+    Text,
+    /// Text with SGR colors and OSC 8 links (view with `less -R`)
+    Ansi,
+    /// Self-contained HTML page with the current palette
+    Html,
+}
+
+impl ExportFormat {
+    /// File extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Text => "txt",
+            ExportFormat::Ansi => "ansi",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Direction of a new split
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDirection {
+    /// New pane to the right (left | right)
+    #[default]
+    Right,
+    /// New pane below (top / bottom)
+    Down,
+}
+
+impl SplitDirection {
+    pub fn to_pane_direction(self) -> pane::Direction {
+        match self {
+            SplitDirection::Right => pane::Direction::Horizontal,
+            SplitDirection::Down => pane::Direction::Vertical,
+        }
+    }
+}
+
+/// Direction for pane focus/resize actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaneDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl PaneDirection {
+    pub fn to_nav_direction(self) -> pane::NavDirection {
+        match self {
+            PaneDirection::Left => pane::NavDirection::Left,
+            PaneDirection::Right => pane::NavDirection::Right,
+            PaneDirection::Up => pane::NavDirection::Up,
+            PaneDirection::Down => pane::NavDirection::Down,
+        }
+    }
+
+    /// Split axis and ratio delta sign for resizing (left/up shrink, right/down grow)
+    pub fn resize_axis(self) -> (pane::Direction, f32) {
+        match self {
+            PaneDirection::Left => (pane::Direction::Horizontal, -1.0),
+            PaneDirection::Right => (pane::Direction::Horizontal, 1.0),
+            PaneDirection::Up => (pane::Direction::Vertical, -1.0),
+            PaneDirection::Down => (pane::Direction::Vertical, 1.0),
+        }
+    }
+}
+
+fn default_split_ratio() -> f32 {
+    0.5
+}
+
+fn default_resize_amount() -> f32 {
+    0.05
+}
+
+/// User-defined keybind entry (`[[keybinds.bind]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeybindEntry {
+    /// Key or keys ("alt+1" or ["alt+1", "ctrl+1"])
+    #[serde(deserialize_with = "deserialize_keybind")]
+    pub key: Vec<String>,
+    /// Action and its parameters
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+fn default_table_timeout() -> u64 {
+    1000
+}
+
+/// Key table definition (`[[keybinds.table]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyTableConfig {
+    /// Table name (shown in the tab bar while active)
+    pub name: String,
+    /// Leader key(s) that enter this table (optional; tables can also be
+    /// entered with the `activate_table` action)
+    #[serde(default, deserialize_with = "deserialize_keybind")]
+    pub key: Vec<String>,
+    /// Leave the table after this many milliseconds without input (0 = never)
+    #[serde(default = "default_table_timeout")]
+    pub timeout: u64,
+    /// Stay in the table after an action (leave with Escape or timeout).
+    /// When false the table is one-shot, like a tmux prefix.
+    #[serde(default)]
+    pub sticky: bool,
+    /// Bindings active while in this table
+    #[serde(default)]
+    pub bind: Vec<KeybindEntry>,
+}
+
+/// Resolved key table
+#[derive(Debug, Clone)]
+pub struct KeyTable {
+    pub name: String,
+    pub timeout: Option<Duration>,
+    pub sticky: bool,
+    pub bindings: KeyBindings,
+}
+
+/// Runtime state of the currently active key table
+#[derive(Debug, Clone)]
+pub struct ActiveKeyTable {
+    pub name: String,
+    pub sticky: bool,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl ActiveKeyTable {
+    pub fn new(table: &KeyTable) -> Self {
+        Self {
+            name: table.name.clone(),
+            sticky: table.sticky,
+            timeout: table.timeout,
+            deadline: table.timeout.map(|t| Instant::now() + t),
+        }
+    }
+
+    /// Restart the timeout (sticky tables stay alive while in use)
+    pub fn touch(&mut self) {
+        self.deadline = self.timeout.map(|t| Instant::now() + t);
+    }
+
+    /// Check if the table timed out
+    pub fn expired(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|d| now >= d)
+    }
+}
+
+/// Resolved key binding table
+///
+/// Built from the legacy named keybinds followed by `[[keybinds.bind]]`
+/// entries. A later entry for the same key replaces earlier ones.
+#[derive(Debug, Clone, Default)]
+pub struct KeyBindings {
+    bindings: Vec<(ParsedKeybind, Action)>,
+    tables: Vec<KeyTable>,
+}
+
+impl KeyBindings {
+    /// Build the table from keybind config
+    pub fn from_config(kb: &KeybindConfig) -> Self {
+        let mut table = Self::default();
+
+        // Legacy named keybinds, in the order they were historically checked
+        // (first match wins when two actions share a key)
+        let named: [(&Vec<String>, Action); 33] = [
+            (&kb.scroll_up, Action::ScrollUp),
+            (&kb.scroll_down, Action::ScrollDown),
+            (&kb.copy, Action::Copy),
+            (&kb.paste, Action::Paste),
+            (&kb.paste_primary, Action::PastePrimary),
+            (&kb.clipboard_history, Action::ClipboardHistory),
+            (&kb.screenshot, Action::Screenshot),
+            (&kb.reset_terminal, Action::ResetTerminal),
+            (&kb.notification_mute, Action::NotificationMute),
+            (&kb.notification_panel, Action::NotificationPanel),
+            (&kb.notification_activate, Action::NotificationActivate),
+            (
+                &kb.split_right,
+                Action::Split {
+                    direction: SplitDirection::Right,
+                    ratio: default_split_ratio(),
+                },
+            ),
+            (
+                &kb.split_down,
+                Action::Split {
+                    direction: SplitDirection::Down,
+                    ratio: default_split_ratio(),
+                },
+            ),
+            (&kb.close_pane, Action::ClosePane),
+            (&kb.pane_left, Self::focus(PaneDirection::Left)),
+            (&kb.pane_right, Self::focus(PaneDirection::Right)),
+            (&kb.pane_up, Self::focus(PaneDirection::Up)),
+            (&kb.pane_down, Self::focus(PaneDirection::Down)),
+            (&kb.resize_left, Self::resize(PaneDirection::Left)),
+            (&kb.resize_right, Self::resize(PaneDirection::Right)),
+            (&kb.resize_up, Self::resize(PaneDirection::Up)),
+            (&kb.resize_down, Self::resize(PaneDirection::Down)),
+            (&kb.zoom_pane, Action::ZoomPane),
+            (&kb.new_tab, Action::NewTab),
+            (&kb.close_tab, Action::CloseTab),
+            (&kb.next_tab, Action::NextTab),
+            (&kb.prev_tab, Action::PrevTab),
+            (&kb.copy_mode, Action::CopyMode),
+            (
+                &kb.hints,
+                Action::Hints {
+                    mode: HintMode::Copy,
+                },
+            ),
+            (&kb.search, Action::Search),
+            (&kb.font_increase, Action::FontIncrease),
+            (&kb.font_decrease, Action::FontDecrease),
+            (&kb.font_reset, Action::FontReset),
+        ];
+        for (keys, action) in named {
+            for key in keys {
+                table.bindings.push((ParsedKeybind::parse(key), action.clone()));
+            }
+        }
+
+        // Leader keys first, so explicit [[keybinds.bind]] entries can override them
+        for kt in &kb.table {
+            for key in &kt.key {
+                table.bind(
+                    key,
+                    Action::ActivateTable {
+                        name: kt.name.clone(),
+                    },
+                );
+            }
+        }
+
+        table.bind_entries(&kb.bind);
+
+        for kt in &kb.table {
+            let mut bindings = KeyBindings::default();
+            bindings.bind_entries(&kt.bind);
+            table.tables.push(KeyTable {
+                name: kt.name.clone(),
+                timeout: (kt.timeout > 0).then(|| Duration::from_millis(kt.timeout)),
+                sticky: kt.sticky,
+                bindings,
+            });
+        }
+
+        table
+    }
+
+    fn bind_entries(&mut self, entries: &[KeybindEntry]) {
+        for entry in entries {
+            for key in &entry.key {
+                self.bind(key, entry.action.clone());
+            }
+        }
+    }
+
+    /// Find a key table by name
+    pub fn table(&self, name: &str) -> Option<&KeyTable> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    fn focus(direction: PaneDirection) -> Action {
+        Action::FocusPane { direction }
+    }
+
+    fn resize(direction: PaneDirection) -> Action {
+        Action::ResizePane {
+            direction,
+            amount: default_resize_amount(),
+        }
+    }
+
+    /// Bind a key, replacing any earlier binding for it (`unbind` only removes).
+    /// New bindings take precedence over everything bound before them.
+    pub fn bind(&mut self, key: &str, action: Action) {
+        let parsed = ParsedKeybind::parse(key);
+        self.bindings.retain(|(kb, _)| *kb != parsed);
+        if action != Action::Unbind {
+            self.bindings.insert(0, (parsed, action));
+        }
+    }
+
+    /// Find the action bound to a key press
+    pub fn lookup(
+        &self,
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+        keycode: u32,
+        keysym: u32,
+    ) -> Option<&Action> {
+        self.bindings
+            .iter()
+            .find(|(kb, _)| kb.matches(ctrl, shift, alt, keycode, keysym))
+            .map(|(_, action)| action)
+    }
+}
+
+/// Mouse button (or wheel direction) for `[[mouse.bind]]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+}
+
+impl MouseButton {
+    /// Map an evdev button code (BTN_LEFT etc.)
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            crate::input::BTN_LEFT => Some(MouseButton::Left),
+            crate::input::BTN_MIDDLE => Some(MouseButton::Middle),
+            crate::input::BTN_RIGHT => Some(MouseButton::Right),
+            _ => None,
+        }
+    }
+}
+
+fn default_clicks() -> u8 {
+    1
+}
+
+/// User-defined mouse binding (`[[mouse.bind]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MouseBindEntry {
+    /// "left", "middle", "right", "wheel_up" or "wheel_down"
+    pub button: MouseButton,
+    /// Click count: 1 (single), 2 (double) or 3 (triple)
+    #[serde(default = "default_clicks")]
+    pub clicks: u8,
+    /// Modifiers ("ctrl", "shift+alt", ...; empty = none)
+    #[serde(default)]
+    pub mods: String,
+    /// Match while the application has enabled mouse reporting.
+    /// Unbound clicks are then forwarded to the application.
+    #[serde(default)]
+    pub grabbed: bool,
+    /// Action and its parameters
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// Button, click count and modifier state a mouse binding matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseTrigger {
+    pub button: MouseButton,
+    pub clicks: u8,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub grabbed: bool,
+}
+
+impl MouseTrigger {
+    fn new(button: MouseButton, clicks: u8, mods: &str, grabbed: bool) -> Self {
+        let parsed = ParsedKeybind::parse(mods);
+        Self {
+            button,
+            clicks: clicks.clamp(1, 3),
+            ctrl: parsed.ctrl,
+            shift: parsed.shift,
+            alt: parsed.alt,
+            grabbed,
+        }
+    }
+}
+
+/// Resolved mouse binding table
+///
+/// Starts from the built-in defaults; `[[mouse.bind]]` entries replace the
+/// default for the same button, click count, modifiers and grab state.
+#[derive(Debug, Clone, Default)]
+pub struct MouseBindings {
+    bindings: Vec<(MouseTrigger, Action)>,
+}
+
+impl MouseBindings {
+    /// Build the table from mouse config
+    pub fn from_config(mc: &MouseConfig) -> Self {
+        use MouseButton::*;
+
+        let mut table = Self::default();
+        let defaults = [
+            (Left, 1, "", false, Action::StartSelection),
+            (Left, 2, "", false, Action::SelectWord),
+            (Left, 3, "", false, Action::SelectLine),
+            (Left, 1, "shift", false, Action::ExtendSelection),
+            (Left, 1, "alt", false, Action::StartBlockSelection),
+            (Left, 1, "ctrl", false, Action::OpenLink),
+            (Left, 1, "ctrl", true, Action::OpenLink),
+            (Middle, 1, "", false, Action::PastePrimary),
+            (Right, 1, "", false, Action::ExtendSelection),
+            (WheelUp, 1, "", false, Action::Scroll { lines: -1 }),
+            (WheelDown, 1, "", false, Action::Scroll { lines: 1 }),
+        ];
+        for (button, clicks, mods, grabbed, action) in defaults {
+            table.bind(MouseTrigger::new(button, clicks, mods, grabbed), action);
+        }
+
+        for entry in &mc.bind {
+            if !Self::supported(&entry.action) {
+                log::warn!(
+                    "[[mouse.bind]]: action {:?} is not available for mouse bindings",
+                    entry.action
+                );
+                continue;
+            }
+            table.bind(
+                MouseTrigger::new(entry.button, entry.clicks, &entry.mods, entry.grabbed),
+                entry.action.clone(),
+            );
+        }
+
+        table
+    }
+
+    /// Actions that can be triggered from the mouse
+    fn supported(action: &Action) -> bool {
+        matches!(
+            action,
+            Action::StartSelection
+                | Action::StartBlockSelection
+                | Action::ExtendSelection
+                | Action::SelectWord
+                | Action::SelectLine
+                | Action::OpenLink
+                | Action::CopyLink
+                | Action::Copy
+                | Action::Paste
+                | Action::PastePrimary
+                | Action::ScrollUp
+                | Action::ScrollDown
+                | Action::Scroll { .. }
+                | Action::SendText { .. }
+                | Action::Unbind
+        )
+    }
+
+    /// Bind a trigger, replacing any earlier binding for it (`unbind` only removes)
+    pub fn bind(&mut self, trigger: MouseTrigger, action: Action) {
+        self.bindings.retain(|(t, _)| *t != trigger);
+        if action != Action::Unbind {
+            self.bindings.push((trigger, action));
+        }
+    }
+
+    /// Find the action for a click. Falls back to lower click counts, so a
+    /// fast second middle click still pastes.
+    pub fn lookup(
+        &self,
+        button: MouseButton,
+        clicks: u8,
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+        grabbed: bool,
+    ) -> Option<&Action> {
+        (1..=clicks.clamp(1, 3)).rev().find_map(|clicks| {
+            let trigger = MouseTrigger {
+                button,
+                clicks,
+                ctrl,
+                shift,
+                alt,
+                grabbed,
+            };
+            self.bindings
+                .iter()
+                .find(|(t, _)| *t == trigger)
+                .map(|(_, action)| action)
+        })
+    }
+}
@@ -5,6 +5,8 @@
 
 #![allow(dead_code)]
 
+pub mod action;
+
 use anyhow::{Context, Result};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,11 @@ use std::path::PathBuf;
 
 use crate::constants::{LCD_DEFAULT_CONTRAST, LCD_DEFAULT_GAMMA};
 
+pub use action::{
+    Action, ActiveKeyTable, ExportFormat, HintMode, KeyBindings, KeyTableConfig, KeybindEntry,
+    MouseBindEntry, MouseBindings, MouseButton,
+};
+
 #[cfg(target_os = "linux")]
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 #[cfg(target_os = "linux")]
@@ -47,6 +54,18 @@ pub struct Config {
     pub notifications: NotificationConfig,
     /// Security settings
     pub security: SecurityConfig,
+    /// Hints (quick-select) settings
+    pub hints: HintsConfig,
+    /// Link opening settings
+    pub links: LinksConfig,
+    /// Text selection settings
+    pub selection: SelectionConfig,
+    /// Clipboard settings
+    pub clipboard: ClipboardConfig,
+    /// Remote control settings
+    pub remote: RemoteConfig,
+    /// Commands run on terminal events
+    pub hooks: HooksConfig,
 }
 
 /// Font settings
@@ -184,6 +203,10 @@ pub struct TerminalConfig {
     /// List of apps that auto-disable IME
     /// When foreground process name is in this list, IME is automatically disabled
     pub ime_disabled_apps: Vec<String>,
+    /// Command for show_scrollback, run as the logged-in user in a new pane.
+    /// `{path}` is replaced by the dump file, otherwise it is appended
+    /// (empty = `$PAGER`, falling back to `less +G`)
+    pub scrollback_pager: String,
 }
 
 /// Keyboard input settings
@@ -231,6 +254,9 @@ pub struct MouseConfig {
     pub natural_scroll: bool,
     /// Disable touchpad while typing (default: true)
     pub disable_while_typing: bool,
+    /// Mouse bindings (`[[mouse.bind]]`), applied on top of the defaults
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bind: Vec<MouseBindEntry>,
 }
 
 impl Default for MouseConfig {
@@ -240,6 +266,7 @@ impl Default for MouseConfig {
             tap_to_click: true,
             natural_scroll: false,
             disable_while_typing: true,
+            bind: Vec::new(),
         }
     }
 }
@@ -294,6 +321,12 @@ pub struct KeybindConfig {
     /// Paste (default: "ctrl+shift+v")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub paste: Vec<String>,
+    /// Paste the primary selection (default: "shift+insert")
+    #[serde(deserialize_with = "deserialize_keybind")]
+    pub paste_primary: Vec<String>,
+    /// Clipboard history picker (default: "ctrl+shift+b")
+    #[serde(deserialize_with = "deserialize_keybind")]
+    pub clipboard_history: Vec<String>,
     /// Screenshot (default: "ctrl+shift+s")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub screenshot: Vec<String>,
@@ -303,6 +336,9 @@ pub struct KeybindConfig {
     /// Copy mode (default: "ctrl+shift+space")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub copy_mode: Vec<String>,
+    /// Hints mode: label on-screen URLs/paths/hashes and copy one (default: "ctrl+shift+e")
+    #[serde(deserialize_with = "deserialize_keybind")]
+    pub hints: Vec<String>,
     /// Font increase (default: "ctrl+plus")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub font_increase: Vec<String>,
@@ -330,6 +366,9 @@ pub struct KeybindConfig {
     /// Notification mute toggle (default: "ctrl+shift+m")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub notification_mute: Vec<String>,
+    /// Activate the newest toast notification (default: "ctrl+shift+a")
+    #[serde(deserialize_with = "deserialize_keybind")]
+    pub notification_activate: Vec<String>,
 
     // === Pane management ===
 
@@ -384,6 +423,16 @@ pub struct KeybindConfig {
     /// Previous tab (default: "ctrl+pageup")
     #[serde(deserialize_with = "deserialize_keybind")]
     pub prev_tab: Vec<String>,
+
+    // === Custom bindings ===
+
+    /// Arbitrary key-to-action bindings ([[keybinds.bind]])
+    /// Applied after the named keybinds above; later entries win.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bind: Vec<KeybindEntry>,
+    /// Key tables entered through a leader key ([[keybinds.table]])
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub table: Vec<KeyTableConfig>,
 }
 
 /// Keybind deserializer: accepts string or array
@@ -439,6 +488,12 @@ impl Default for Config {
             display: DisplayOutputConfig::default(),
             notifications: NotificationConfig::default(),
             security: SecurityConfig::default(),
+            hints: HintsConfig::default(),
+            links: LinksConfig::default(),
+            selection: SelectionConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            remote: RemoteConfig::default(),
+            hooks: HooksConfig::default(),
         }
     }
 }
@@ -610,6 +665,7 @@ impl Default for TerminalConfig {
             ime: false,
             // Empty by default - uncomment in config for CJK/IME users
             ime_disabled_apps: vec![],
+            scrollback_pager: String::new(),
         }
     }
 }
@@ -620,11 +676,17 @@ impl Default for TerminalConfig {
 pub struct NotificationConfig {
     /// Enable notification system (OSC 9/99). Default: true
     pub enabled: bool,
+    /// Also send notifications to org.freedesktop.Notifications on the
+    /// session bus. Default: false
+    pub desktop: bool,
 }
 
 impl Default for NotificationConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            desktop: false,
+        }
     }
 }
 
@@ -651,6 +713,233 @@ impl Default for SecurityConfig {
     }
 }
 
+/// Text selection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionConfig {
+    /// Also copy every selection to the clipboard, not only to the
+    /// primary selection (default: false)
+    pub copy_on_select: bool,
+    /// Characters that belong to a word on double click, besides letters
+    /// and digits
+    pub word_chars: String,
+    /// Enable built-in smart selection rules (URL, email, path, quoted string)
+    pub builtin: bool,
+    /// Extra smart selection rules ([[selection.rule]]), checked before the
+    /// built-ins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule: Vec<SelectionRuleConfig>,
+}
+
+/// User-defined smart selection rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionRuleConfig {
+    /// Rule name (for logs)
+    pub name: String,
+    /// Regular expression (Rust regex syntax). With a capture group, only
+    /// the first group is selected.
+    pub regex: String,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            copy_on_select: false,
+            word_chars: "@-./_~?&=%+#".to_string(),
+            builtin: true,
+            rule: Vec::new(),
+        }
+    }
+}
+
+impl SelectionConfig {
+    /// User rules as (name, regex) pairs
+    pub fn user_rules(&self) -> Vec<(String, String)> {
+        self.rule
+            .iter()
+            .map(|r| (r.name.clone(), r.regex.clone()))
+            .collect()
+    }
+}
+
+/// Clipboard settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    /// Number of clipboard history entries (0 = history disabled)
+    pub history_size: usize,
+    /// Keep the history in $XDG_RUNTIME_DIR across restarts (memory only
+    /// without it)
+    pub history_persist: bool,
+    /// Do not record copies made while the pane has echo disabled
+    /// (password prompts)
+    pub history_skip_no_echo: bool,
+    /// Share the clipboard with the user's other bcon instances (other VTs)
+    /// through a broker socket in $XDG_RUNTIME_DIR (read at startup)
+    pub share: bool,
+    /// Command that receives every clipboard change on stdin (e.g. "wl-copy").
+    /// Runs as the logged-in user. Empty = disabled.
+    pub copy_command: String,
+    /// Command whose stdout is pasted instead of bcon's clipboard
+    /// (e.g. "wl-paste -n"). Runs as the logged-in user. Empty = disabled.
+    pub paste_command: String,
+    /// Time limit for copy_command / paste_command (milliseconds)
+    pub command_timeout_ms: u64,
+    /// Strip ESC and C1 control characters from pasted text
+    pub paste_sanitize: bool,
+    /// Ask before pasting newlines or control characters into an
+    /// application without bracketed paste
+    pub paste_confirm: bool,
+    /// Ask before pasting more than this many bytes (0 = never)
+    pub paste_warn_size: usize,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            history_size: 50,
+            history_persist: false,
+            history_skip_no_echo: true,
+            share: true,
+            copy_command: String::new(),
+            paste_command: String::new(),
+            command_timeout_ms: 2000,
+            paste_sanitize: true,
+            paste_confirm: true,
+            paste_warn_size: 64 * 1024,
+        }
+    }
+}
+
+/// Remote control settings (read at startup)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    /// Listen for remote control requests from the logged-in user
+    pub enabled: bool,
+    /// Socket path (empty = $XDG_RUNTIME_DIR/bcon-<pid>.sock)
+    pub socket: String,
+}
+
+/// Hook commands, run through `sh -c` as the logged-in user with BCON_*
+/// variables describing the event (empty = no hook)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// BEL in any pane
+    pub bell: String,
+    /// OSC 9 / OSC 99 notification
+    pub notification: String,
+    /// OSC 133 command finished with a non-zero exit code, or ran for at
+    /// least `command_seconds`
+    pub command_finished: String,
+    /// Minimum command duration for command_finished (0 = failures only)
+    pub command_seconds: u64,
+    /// A pane's shell or program exited
+    pub pane_exited: String,
+    /// Switched to bcon's VT
+    pub focus_gained: String,
+    /// Switched away from bcon's VT
+    pub focus_lost: String,
+}
+
+/// Hints (quick-select) settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HintsConfig {
+    /// Characters used for labels, most convenient first
+    pub alphabet: String,
+    /// Enable built-in patterns (URL, path[:line[:col]], git hash, IP, UUID)
+    pub builtin: bool,
+    /// Extra patterns ([[hints.pattern]]), checked before the built-ins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pattern: Vec<HintPatternConfig>,
+}
+
+/// User-defined hint pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HintPatternConfig {
+    /// Pattern name (for logs)
+    pub name: String,
+    /// Regular expression (Rust regex syntax)
+    pub regex: String,
+}
+
+impl Default for HintsConfig {
+    fn default() -> Self {
+        Self {
+            alphabet: "asdfghjklqwertyuiopzxcvbnm".to_string(),
+            builtin: true,
+            pattern: Vec::new(),
+        }
+    }
+}
+
+impl HintsConfig {
+    /// User patterns as (name, regex) pairs
+    pub fn user_patterns(&self) -> Vec<(String, String)> {
+        self.pattern
+            .iter()
+            .map(|p| (p.name.clone(), p.regex.clone()))
+            .collect()
+    }
+}
+
+/// Link opening settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinksConfig {
+    /// Command that opens links without a scheme handler (e.g. "xdg-open").
+    /// Runs as the logged-in user. Empty = copy the link to the clipboard.
+    pub open_command: String,
+    /// Highlight the whole OSC 8 link under the mouse pointer
+    pub hover: bool,
+    /// Per-scheme handlers ([[links.handler]])
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub handler: Vec<LinkHandlerConfig>,
+}
+
+/// Handler for one URL scheme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkHandlerConfig {
+    /// URL scheme ("file", "https", ...)
+    pub scheme: String,
+    /// Shell command; {url}, {path}, {line} and {col} are replaced with
+    /// quoted values (the URL is appended if none is used)
+    pub command: String,
+    /// Run in a new pane instead of in the background
+    #[serde(default)]
+    pub pane: bool,
+}
+
+impl Default for LinksConfig {
+    fn default() -> Self {
+        Self {
+            open_command: String::new(),
+            hover: true,
+            handler: Vec::new(),
+        }
+    }
+}
+
+impl LinksConfig {
+    /// Find the handler for a scheme. `file` links open `$EDITOR` in a new
+    /// pane unless configured otherwise.
+    pub fn handler_for(&self, scheme: &str) -> Option<LinkHandlerConfig> {
+        self.handler
+            .iter()
+            .find(|h| h.scheme.eq_ignore_ascii_case(scheme))
+            .cloned()
+            .or_else(|| {
+                (scheme == "file").then(|| LinkHandlerConfig {
+                    scheme: "file".to_string(),
+                    command: "${EDITOR:-vi} +{line} {path}".to_string(),
+                    pane: true,
+                })
+            })
+    }
+}
+
 impl Default for KeybindConfig {
     fn default() -> Self {
         Self::default_preset()
@@ -702,9 +991,12 @@ impl KeybindConfig {
         Self {
             copy: vec!["ctrl+shift+c".to_string()],
             paste: vec!["ctrl+shift+v".to_string()],
+            paste_primary: vec!["shift+insert".to_string()],
+            clipboard_history: vec!["ctrl+shift+b".to_string()],
             screenshot: vec!["ctrl+shift+s".to_string(), "printscreen".to_string()],
             search: vec!["ctrl+shift+f".to_string()],
             copy_mode: vec!["ctrl+shift+space".to_string()],
+            hints: vec!["ctrl+shift+e".to_string()],
             font_increase: vec!["ctrl+=".to_string(), "ctrl+shift+=".to_string()],
             font_decrease: vec!["ctrl+minus".to_string(), "ctrl+shift+minus".to_string()],
             font_reset: vec!["ctrl+0".to_string()],
@@ -714,6 +1006,7 @@ impl KeybindConfig {
             reset_terminal: vec!["ctrl+shift+escape".to_string()],
             notification_panel: vec!["ctrl+shift+n".to_string()],
             notification_mute: vec!["ctrl+shift+m".to_string()],
+            notification_activate: vec!["ctrl+shift+a".to_string()],
             split_right: pane.split_right,
             split_down: pane.split_down,
             close_pane: pane.close_pane,
@@ -730,6 +1023,8 @@ impl KeybindConfig {
             close_tab: pane.close_tab,
             next_tab: pane.next_tab,
             prev_tab: pane.prev_tab,
+            bind: Vec::new(),
+            table: Vec::new(),
         }
     }
 
@@ -741,9 +1036,12 @@ impl KeybindConfig {
         Self {
             copy: vec!["ctrl+shift+w".to_string()],
             paste: vec!["ctrl+shift+y".to_string()],
+            paste_primary: vec!["shift+insert".to_string()],
+            clipboard_history: vec!["ctrl+shift+b".to_string()],
             screenshot: vec!["printscreen".to_string()],
             search: vec!["ctrl+shift+s".to_string()],
             copy_mode: vec!["ctrl+shift+m".to_string()],
+            hints: vec!["ctrl+shift+e".to_string()],
             font_increase: vec!["ctrl+=".to_string(), "ctrl+shift+=".to_string()],
             font_decrease: vec!["ctrl+minus".to_string(), "ctrl+shift+minus".to_string()],
             font_reset: vec!["ctrl+0".to_string()],
@@ -753,6 +1051,7 @@ impl KeybindConfig {
             reset_terminal: vec!["ctrl+shift+escape".to_string()],
             notification_panel: vec!["ctrl+shift+n".to_string()],
             notification_mute: vec!["alt+shift+m".to_string()],
+            notification_activate: vec!["ctrl+shift+a".to_string()],
             split_right: pane.split_right,
             split_down: pane.split_down,
             close_pane: pane.close_pane,
@@ -769,6 +1068,8 @@ impl KeybindConfig {
             close_tab: pane.close_tab,
             next_tab: pane.next_tab,
             prev_tab: pane.prev_tab,
+            bind: Vec::new(),
+            table: Vec::new(),
         }
     }
 
@@ -778,9 +1079,12 @@ impl KeybindConfig {
         Self {
             copy: vec!["ctrl+shift+c".to_string()],
             paste: vec!["ctrl+shift+v".to_string()],
+            paste_primary: vec!["shift+insert".to_string()],
+            clipboard_history: vec!["ctrl+shift+b".to_string()],
             screenshot: vec!["ctrl+shift+s".to_string(), "printscreen".to_string()],
             search: vec!["ctrl+shift+f".to_string()],
             copy_mode: vec!["ctrl+shift+space".to_string()],
+            hints: vec!["ctrl+shift+e".to_string()],
             font_increase: vec!["ctrl+=".to_string(), "ctrl+shift+=".to_string()],
             font_decrease: vec!["ctrl+minus".to_string(), "ctrl+shift+minus".to_string()],
             font_reset: vec!["ctrl+0".to_string()],
@@ -790,6 +1094,7 @@ impl KeybindConfig {
             reset_terminal: vec!["ctrl+shift+escape".to_string()],
             notification_panel: vec!["ctrl+shift+n".to_string()],
             notification_mute: vec!["ctrl+shift+m".to_string()],
+            notification_activate: vec!["ctrl+shift+a".to_string()],
             split_right: pane.split_right,
             split_down: pane.split_down,
             close_pane: pane.close_pane,
@@ -806,6 +1111,8 @@ impl KeybindConfig {
             close_tab: pane.close_tab,
             next_tab: pane.next_tab,
             prev_tab: pane.prev_tab,
+            bind: Vec::new(),
+            table: Vec::new(),
         }
     }
 }
@@ -1024,6 +1331,38 @@ ime_disabled_apps = ["vim", "nvim", "vi", "vimdiff", "emacs", "nano", "less", "m
 [keybinds]
 {keybinds_toml}
 {font_section}{terminal_section}
+# =============================================================================
+# Custom Keybinds (Optional)
+# =============================================================================
+# Bind any key to any action. Later entries for the same key win.
+#
+# [[keybinds.bind]]
+# key = "alt+1"
+# action = "goto_tab"
+# n = 1
+#
+# [[keybinds.bind]]
+# key = "ctrl+shift+k"
+# action = "send_text"
+# text = "\u001b[27;6;107~"
+#
+# [[keybinds.bind]]
+# key = "ctrl+shift+w"
+# action = "unbind"           # or "passthrough" to always send the key to the app
+#
+# Key tables (tmux-style prefix): press the leader key, then a table key
+#
+# [[keybinds.table]]
+# name = "prefix"
+# key = "ctrl+a"
+# timeout = 1000              # ms, 0 = never
+# sticky = false              # true: stay until Escape/timeout
+#
+# [[keybinds.table.bind]]
+# key = "|"
+# action = "split"
+# direction = "right"
+
 # =============================================================================
 # Font Configuration (Optional)
 # =============================================================================
@@ -1134,6 +1473,7 @@ ime_disabled_apps = ["vim", "nvim", "vi", "vimdiff", "emacs", "nano", "less", "m
 # =============================================================================
 # [terminal]
 # scrollback_lines = 10000
+# scrollback_pager = "less -R +G"  # show_scrollback command (default: $PAGER)
 
 # =============================================================================
 # Keyboard Settings (Optional)
@@ -1154,6 +1494,111 @@ ime_disabled_apps = ["vim", "nvim", "vi", "vimdiff", "emacs", "nano", "less", "m
 # tap_to_click = true        # Enable tap-to-click on touchpads
 # natural_scroll = false     # Enable natural (reverse) scrolling
 # disable_while_typing = true # Disable touchpad while typing
+#
+# Mouse bindings: button ("left", "middle", "right", "wheel_up", "wheel_down"),
+# clicks (1-3), mods ("ctrl", "shift+alt", ...) and grabbed (true = applies
+# while the application has enabled mouse reporting).
+# Defaults: left drag selects, double/triple click selects word/line,
+# alt+left drag selects a rectangle, shift+left or right extends the
+# selection, ctrl+left opens a link, middle click pastes the primary
+# selection.
+# [[mouse.bind]]
+# button = "right"
+# action = "paste"
+#
+# [[mouse.bind]]
+# button = "left"
+# mods = "shift"
+# grabbed = true             # Select even when the app grabbed the mouse
+# action = "start_selection"
+
+# =============================================================================
+# Selection (Optional)
+# =============================================================================
+# Selecting text sets the primary selection (middle click / shift+insert
+# pastes it); the clipboard is only set by copy (ctrl+shift+c), copy mode
+# and OSC 52.
+# [selection]
+# copy_on_select = false     # true: selections also go to the clipboard
+# word_chars = "@-./_~?&=%+#"  # Word characters besides letters and digits
+# builtin = true             # Double click grabs whole URLs, emails, paths,
+#                            # quoted strings
+# [[selection.rule]]         # Extra rules, checked first
+# name = "jira"
+# regex = "[A-Z]+-[0-9]+"
+
+# =============================================================================
+# Clipboard (Optional)
+# =============================================================================
+# Every copy is kept in a history; ctrl+shift+b opens a picker to paste an
+# older entry.
+# [clipboard]
+# history_size = 50            # 0 disables the history
+# history_persist = false      # Keep it in $XDG_RUNTIME_DIR across restarts
+# history_skip_no_echo = true  # Skip copies made at password prompts
+# share = true                 # Share with your bcon instances on other VTs
+# Sync with external tools (run as the logged-in user, killed after the timeout)
+# copy_command = "wl-copy"     # Gets each copy on stdin
+# paste_command = "wl-paste -n" # Its stdout is pasted
+# command_timeout_ms = 2000
+# Paste safety
+# paste_sanitize = true        # Strip ESC and C1 controls from pasted text
+# paste_confirm = true         # Ask before pasting lines without bracketed paste
+# paste_warn_size = 65536      # Ask before pasting more bytes than this (0 = off)
+
+# =============================================================================
+# Remote Control (Optional)
+# =============================================================================
+# Scripts control tabs and panes through a JSON socket. Only the logged-in
+# user may connect. Off by default. Read at startup.
+# [remote]
+# enabled = false
+# socket = ""                  # Default: $XDG_RUNTIME_DIR/bcon-<pid>.sock
+
+# =============================================================================
+# Hooks (Optional)
+# =============================================================================
+# Commands run as the logged-in user on terminal events, with BCON_EVENT,
+# BCON_PANE, BCON_CWD and event details (BCON_EXIT_CODE, BCON_DURATION,
+# BCON_TITLE, BCON_BODY, BCON_URGENCY) in the environment.
+# [hooks]
+# bell = "paplay /usr/share/sounds/freedesktop/stereo/bell.oga"
+# notification = ""
+# command_finished = ""        # Non-zero exit, or longer than command_seconds
+# command_seconds = 30         # 0 = failures only
+# pane_exited = ""
+# focus_gained = ""            # VT switched to bcon
+# focus_lost = ""
+
+# =============================================================================
+# Hints (Optional)
+# =============================================================================
+# Label on-screen URLs, paths (with :line:col), git hashes, IPs and UUIDs;
+# type a label to copy it. Bind action = "hints" with mode = "paste" or
+# "open" for other behaviors.
+# [hints]
+# alphabet = "asdfghjklqwertyuiopzxcvbnm"
+# builtin = true             # Built-in patterns
+#
+# [[hints.pattern]]
+# name = "ticket"
+# regex = "[A-Z]+-[0-9]+"
+
+# =============================================================================
+# Links (Optional)
+# =============================================================================
+# Commands run as the logged-in user (never as root). Placeholders in handler
+# commands: {{url}}, {{path}}, {{line}}, {{col}} (inserted shell-quoted).
+# By default file:// links and hinted paths open $EDITOR in a new pane, and
+# other links are copied to the clipboard.
+# [links]
+# open_command = "xdg-open"  # Fallback for schemes without a handler
+# hover = true               # Highlight the whole OSC 8 link under the pointer
+#
+# [[links.handler]]
+# scheme = "https"
+# command = "w3m {{url}}"
+# pane = true                # Open in a new pane instead of the background
 
 # =============================================================================
 # Display Settings (Optional)
@@ -1274,7 +1719,7 @@ impl ParsedKeybinds {
 
 /// Parse keybind string
 /// Example: "ctrl+shift+c" -> (ctrl: true, shift: true, key: "c")
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ParsedKeybind {
     pub ctrl: bool,
     pub shift: bool,
@@ -1300,8 +1745,27 @@ impl ParsedKeybind {
         result
     }
 
+    /// Shifted symbol key ("|", "%", "?", ...) matched by keysym.
+    /// Unshifted punctuation and alphanumerics are matched by keycode instead.
+    fn symbol_keysym(&self) -> Option<u32> {
+        let mut chars = self.key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None)
+                if ch.is_ascii_punctuation() && !"=-\\[];'`,./".contains(ch) =>
+            {
+                // Latin-1 keysyms equal their code point
+                Some(ch as u32)
+            }
+            _ => None,
+        }
+    }
+
     /// Check if matches evdev keycode and modifiers
     pub fn matches(&self, ctrl: bool, shift: bool, alt: bool, keycode: u32, keysym: u32) -> bool {
+        if let Some(sym) = self.symbol_keysym() {
+            // Shift is implied by the symbol itself
+            return self.ctrl == ctrl && self.alt == alt && keysym == sym;
+        }
         if self.ctrl != ctrl || self.shift != shift || self.alt != alt {
             return false;
         }
@@ -1351,6 +1815,15 @@ impl ParsedKeybind {
             "space" => keycode == 57,
             "plus" | "=" => keycode == 13,
             "minus" | "-" => keycode == 12,
+            "backslash" | "\\" => keycode == 43,
+            "leftbracket" | "[" => keycode == 26,
+            "rightbracket" | "]" => keycode == 27,
+            "semicolon" | ";" => keycode == 39,
+            "apostrophe" | "'" => keycode == 40,
+            "grave" | "`" => keycode == 41,
+            "comma" | "," => keycode == 51,
+            "period" | "." => keycode == 52,
+            "slash" | "/" => keycode == 53,
             "enter" | "return" => keycode == 28,
             "tab" => keycode == 15,
             "escape" | "esc" => keycode == 1,
@@ -1513,4 +1986,230 @@ mod tests {
         assert!(kb.shift);
         assert_eq!(kb.key, "c");
     }
+
+    #[test]
+    fn test_keybind_bind_entries() {
+        let config: Config = toml::from_str(
+            r#"
+[[keybinds.bind]]
+key = "alt+1"
+action = "goto_tab"
+n = 1
+
+[[keybinds.bind]]
+key = ["ctrl+shift+e"]
+action = "split"
+direction = "down"
+ratio = 0.3
+
+[[keybinds.bind]]
+key = "ctrl+shift+w"
+action = "unbind"
+
+[[keybinds.bind]]
+key = "ctrl+shift+c"
+action = "send_text"
+text = "\u001b[99~"
+"#,
+        )
+        .unwrap();
+        let bindings = KeyBindings::from_config(&config.keybinds);
+
+        // alt+1 (keycode 2)
+        assert_eq!(
+            bindings.lookup(false, false, true, 2, 0),
+            Some(&Action::GotoTab { n: 1 })
+        );
+        // ctrl+shift+e (keycode 18)
+        assert_eq!(
+            bindings.lookup(true, true, false, 18, 0),
+            Some(&Action::Split {
+                direction: action::SplitDirection::Down,
+                ratio: 0.3
+            })
+        );
+        // Default close_pane binding removed
+        assert_eq!(bindings.lookup(true, true, false, 17, 0), None);
+        // Default copy binding overridden
+        assert_eq!(
+            bindings.lookup(true, true, false, 46, 0),
+            Some(&Action::SendText {
+                text: "\x1b[99~".to_string()
+            })
+        );
+        // Untouched defaults remain
+        assert_eq!(bindings.lookup(true, true, false, 47, 0), Some(&Action::Paste));
+        // shift+insert (keycode 110) pastes the primary selection
+        assert_eq!(
+            bindings.lookup(false, true, false, 110, 0),
+            Some(&Action::PastePrimary)
+        );
+    }
+
+    #[test]
+    fn test_keybind_symbol_keys() {
+        // Shifted symbols match by keysym regardless of Shift
+        let bar = ParsedKeybind::parse("|");
+        assert!(bar.matches(false, true, false, 43, '|' as u32));
+        assert!(!bar.matches(true, true, false, 43, '|' as u32));
+        // Unshifted punctuation matches by keycode
+        let backslash = ParsedKeybind::parse("ctrl+shift+\\");
+        assert!(backslash.matches(true, true, false, 43, '|' as u32));
+    }
+
+    #[test]
+    fn test_key_tables() {
+        let config: Config = toml::from_str(
+            r#"
+[[keybinds.table]]
+name = "prefix"
+key = "ctrl+a"
+timeout = 0
+
+[[keybinds.table.bind]]
+key = "|"
+action = "split"
+direction = "right"
+
+[[keybinds.table.bind]]
+key = "r"
+action = "activate_table"
+name = "resize"
+
+[[keybinds.table]]
+name = "resize"
+sticky = true
+
+[[keybinds.table.bind]]
+key = "h"
+action = "resize_pane"
+direction = "left"
+"#,
+        )
+        .unwrap();
+        let bindings = KeyBindings::from_config(&config.keybinds);
+
+        // ctrl+a (keycode 30) enters the prefix table
+        assert_eq!(
+            bindings.lookup(true, false, false, 30, 0),
+            Some(&Action::ActivateTable {
+                name: "prefix".to_string()
+            })
+        );
+        let prefix = bindings.table("prefix").unwrap();
+        assert!(!prefix.sticky);
+        assert!(prefix.timeout.is_none());
+        assert_eq!(
+            prefix.bindings.lookup(false, true, false, 43, '|' as u32),
+            Some(&Action::Split {
+                direction: action::SplitDirection::Right,
+                ratio: 0.5
+            })
+        );
+        // Table bindings don't leak into the root table
+        assert_eq!(bindings.lookup(false, false, false, 35, 0), None);
+
+        let resize = bindings.table("resize").unwrap();
+        assert!(resize.sticky);
+        assert_eq!(resize.timeout, Some(std::time::Duration::from_millis(1000)));
+        assert!(resize.bindings.lookup(false, false, false, 35, 0).is_some());
+    }
+
+    #[test]
+    fn test_mouse_bindings() {
+        use action::MouseButton;
+
+        let config: Config = toml::from_str(
+            r#"
+[[mouse.bind]]
+button = "right"
+action = "paste"
+
+[[mouse.bind]]
+button = "middle"
+action = "unbind"
+
+[[mouse.bind]]
+button = "left"
+mods = "shift"
+grabbed = true
+action = "start_selection"
+
+[[mouse.bind]]
+button = "wheel_up"
+mods = "ctrl"
+action = "font_increase"
+"#,
+        )
+        .unwrap();
+        let binds = MouseBindings::from_config(&config.mouse);
+
+        // Defaults stay in place
+        assert_eq!(
+            binds.lookup(MouseButton::Left, 2, false, false, false, false),
+            Some(&Action::SelectWord)
+        );
+        assert_eq!(
+            binds.lookup(MouseButton::Left, 1, false, false, true, false),
+            Some(&Action::StartBlockSelection)
+        );
+        assert_eq!(
+            binds.lookup(MouseButton::Left, 1, true, false, false, true),
+            Some(&Action::OpenLink)
+        );
+        // Unbound clicks while grabbed go to the application
+        assert_eq!(
+            binds.lookup(MouseButton::Left, 1, false, false, false, true),
+            None
+        );
+        // Overrides
+        assert_eq!(
+            binds.lookup(MouseButton::Right, 1, false, false, false, false),
+            Some(&Action::Paste)
+        );
+        assert_eq!(
+            binds.lookup(MouseButton::Middle, 1, false, false, false, false),
+            None
+        );
+        assert_eq!(
+            binds.lookup(MouseButton::Left, 1, false, true, false, true),
+            Some(&Action::StartSelection)
+        );
+        // Higher click counts fall back to the single-click binding
+        assert_eq!(
+            binds.lookup(MouseButton::Right, 3, false, false, false, false),
+            Some(&Action::Paste)
+        );
+        // Keyboard-only actions are rejected
+        assert_eq!(
+            binds.lookup(MouseButton::WheelUp, 1, true, false, false, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_link_handlers() {
+        let config: Config = toml::from_str(
+            r#"
+[links]
+open_command = "xdg-open"
+
+[[links.handler]]
+scheme = "HTTPS"
+command = "w3m {url}"
+pane = true
+"#,
+        )
+        .unwrap();
+        let links = &config.links;
+        assert!(links.hover);
+        let https = links.handler_for("https").unwrap();
+        assert!(https.pane);
+        assert_eq!(https.command, "w3m {url}");
+        // Built-in file handler
+        let file = links.handler_for("file").unwrap();
+        assert!(file.pane);
+        assert!(file.command.contains("{path}"));
+        assert!(links.handler_for("ftp").is_none());
+    }
 }
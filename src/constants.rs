@@ -187,6 +187,9 @@ pub const UI_TAB_ACTIVE_BG: [f32; 4] = [0.2, 0.3, 0.5, 1.0];
 /// Active tab indicator line
 pub const UI_TAB_INDICATOR: [f32; 4] = [0.4, 0.6, 1.0, 1.0];
 
+/// Active key table indicator background
+pub const UI_KEY_TABLE_BG: [f32; 4] = [0.6, 0.4, 0.1, 1.0];
+
 /// Toast notification background
 pub const UI_TOAST_BG: [f32; 4] = [0.15, 0.18, 0.25, 0.92];
 
@@ -199,6 +202,18 @@ pub const UI_PROGRESS_BG: [f32; 4] = [0.12, 0.14, 0.20, 0.92];
 /// Copy mode status bar background
 pub const UI_COPY_MODE_BG: [f32; 4] = [0.2, 0.15, 0.1, 0.95];
 
+/// Underline of the OSC 8 link under the mouse pointer
+pub const UI_LINK_HOVER: [f32; 4] = [0.4, 0.6, 1.0, 1.0];
+
+/// Hints mode label background
+pub const UI_HINT_LABEL_BG: [f32; 4] = [0.95, 0.75, 0.2, 1.0];
+
+/// Hints mode matched text tint
+pub const UI_HINT_MATCH: [f32; 4] = [0.95, 0.75, 0.2, 0.25];
+
+/// Search bar text for an invalid pattern
+pub const UI_SEARCH_ERROR: [f32; 4] = [1.0, 0.45, 0.4, 1.0];
+
 /// IME candidate window background
 pub const UI_CANDIDATE_BG: [f32; 4] = [0.15, 0.15, 0.18, 0.95];
 
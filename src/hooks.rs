@@ -0,0 +1,252 @@
+//! User hooks
+//!
+//! `[hooks]` commands run on terminal events: bell, notification, a failed
+//! or long command (OSC 133), a pane's program exiting, and bcon's VT
+//! gaining or losing focus. They run in the background through `sh -c` as
+//! the logged-in user (never as root), with the event in BCON_* variables.
+//! Pane events come from the same [`EventWatcher`] snapshots as the remote
+//! control event stream.
+//!
+//! [`EventWatcher`]: crate::remote::events::EventWatcher
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::config::HooksConfig;
+use crate::pane::tab::TabManager;
+use crate::pane::PaneId;
+use crate::remote::events::Event;
+
+/// Bell and notification hooks run at most this often per pane, focus hooks
+/// at most this often overall (a bell storm must not fork-bomb)
+const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs `[hooks]` commands
+pub struct Hooks {
+    config: HooksConfig,
+    /// Last run of each rate-limited hook, by hook name and pane
+    last_run: HashMap<(&'static str, Option<u16>), Instant>,
+}
+
+impl Hooks {
+    pub fn new(config: &HooksConfig) -> Self {
+        Self {
+            config: config.clone(),
+            last_run: HashMap::new(),
+        }
+    }
+
+    /// Whether any hook needs pane events
+    pub fn wants_pane_events(&self) -> bool {
+        let c = &self.config;
+        [
+            &c.bell,
+            &c.notification,
+            &c.command_finished,
+            &c.pane_exited,
+        ]
+        .iter()
+        .any(|command| !command.is_empty())
+    }
+
+    /// Run the hook for a pane event, if one is configured, as `uid`: the
+    /// user logged in on the pane (see [`EventWatcher::pane_uid`])
+    ///
+    /// [`EventWatcher::pane_uid`]: crate::remote::events::EventWatcher::pane_uid
+    pub fn on_event(&mut self, event: &Event, tab_mgr: &TabManager, uid: Option<u32>) {
+        let Some((name, command, mut env)) = hook_for(&self.config, event) else {
+            return;
+        };
+        let pane = match *event {
+            Event::Bell { pane }
+            | Event::Notification { pane, .. }
+            | Event::CommandFinished { pane, .. }
+            | Event::ChildExited { pane } => pane,
+            _ => return,
+        };
+        let Some(term) = tab_mgr.pane(PaneId(pane)).map(|p| &p.terminal) else {
+            return;
+        };
+        env.push(("BCON_PANE", pane.to_string()));
+        if let Some(cwd) = &term.current_directory {
+            env.push(("BCON_CWD", cwd.clone()));
+        }
+        if !self.allowed(name, event) {
+            return;
+        }
+        self.run(name, &command, &env, uid);
+    }
+
+    /// bcon's VT was switched to (`gained`) or away from
+    pub fn on_focus(&mut self, gained: bool, logged_in_uid: Option<u32>) {
+        let (name, command) = if gained {
+            ("focus_gained", self.config.focus_gained.clone())
+        } else {
+            ("focus_lost", self.config.focus_lost.clone())
+        };
+        if !command.is_empty() && !self.throttled(name, None) {
+            self.run(
+                name,
+                &command,
+                &[("BCON_EVENT", name.to_string())],
+                logged_in_uid,
+            );
+        }
+    }
+
+    fn run(
+        &mut self,
+        name: &'static str,
+        command: &str,
+        env: &[(&str, String)],
+        logged_in_uid: Option<u32>,
+    ) {
+        // Never run hooks as root: bcon itself may be root for DRM access
+        let uid = match unsafe { libc::getuid() } {
+            0 => logged_in_uid,
+            uid => Some(uid),
+        };
+        let Some(uid) = uid else {
+            debug!("Hook {} skipped: nobody logged in", name);
+            return;
+        };
+        if let Err(e) = crate::terminal::pty::spawn_detached_as_user(uid, command, env) {
+            warn!("Hook {} failed: {}", name, e);
+        }
+    }
+
+    /// Whether hook `name` may run for a pane event: bells and
+    /// notifications are rate-limited per pane, while commands finishing and
+    /// panes exiting are never dropped
+    fn allowed(&mut self, name: &'static str, event: &Event) -> bool {
+        match *event {
+            Event::Bell { pane } | Event::Notification { pane, .. } => {
+                !self.throttled(name, Some(pane))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether hook `name` ran for `pane` less than MIN_INTERVAL ago
+    /// (otherwise the run is recorded)
+    fn throttled(&mut self, name: &'static str, pane: Option<u16>) -> bool {
+        let now = Instant::now();
+        self.last_run
+            .retain(|_, last| now.duration_since(*last) < MIN_INTERVAL);
+        if self.last_run.contains_key(&(name, pane)) {
+            debug!(
+                "Hook {} skipped: ran less than {:?} ago",
+                name, MIN_INTERVAL
+            );
+            return true;
+        }
+        self.last_run.insert((name, pane), now);
+        false
+    }
+}
+
+/// Hook name, command and event variables
+type HookRun = (&'static str, String, Vec<(&'static str, String)>);
+
+/// The hook to run for a pane event
+fn hook_for(config: &HooksConfig, event: &Event) -> Option<HookRun> {
+    let (name, command, mut env) = match event {
+        Event::Bell { .. } => ("bell", &config.bell, Vec::new()),
+        Event::Notification {
+            title,
+            body,
+            urgency,
+            ..
+        } => (
+            "notification",
+            &config.notification,
+            vec![
+                ("BCON_TITLE", title.clone()),
+                ("BCON_BODY", body.clone()),
+                ("BCON_URGENCY", urgency.to_string()),
+            ],
+        ),
+        Event::CommandFinished {
+            exit_code,
+            duration,
+            ..
+        } => {
+            let failed = exit_code.is_some_and(|code| code != 0);
+            let long = config.command_seconds > 0
+                && duration.is_some_and(|d| d >= config.command_seconds as f64);
+            if !failed && !long {
+                return None;
+            }
+            let mut env = Vec::new();
+            if let Some(code) = exit_code {
+                env.push(("BCON_EXIT_CODE", code.to_string()));
+            }
+            if let Some(duration) = duration {
+                env.push(("BCON_DURATION", (*duration as u64).to_string()));
+            }
+            ("command_finished", &config.command_finished, env)
+        }
+        Event::ChildExited { .. } => ("pane_exited", &config.pane_exited, Vec::new()),
+        _ => return None,
+    };
+    if command.is_empty() {
+        return None;
+    }
+    env.insert(0, ("BCON_EVENT", name.to_string()));
+    Some((name, command.clone(), env))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_for() {
+        let config = HooksConfig {
+            bell: "paplay bell.oga".to_string(),
+            command_finished: "notify".to_string(),
+            command_seconds: 30,
+            ..Default::default()
+        };
+        let finished = |exit_code, duration| Event::CommandFinished {
+            pane: 1,
+            exit_code,
+            duration,
+        };
+
+        let (name, command, env) = hook_for(&config, &Event::Bell { pane: 1 }).unwrap();
+        assert_eq!((name, command.as_str()), ("bell", "paplay bell.oga"));
+        assert_eq!(env, vec![("BCON_EVENT", "bell".to_string())]);
+
+        let (_, _, env) = hook_for(&config, &finished(Some(2), Some(1.5))).unwrap();
+        assert_eq!(
+            env,
+            vec![
+                ("BCON_EVENT", "command_finished".to_string()),
+                ("BCON_EXIT_CODE", "2".to_string()),
+                ("BCON_DURATION", "1".to_string()),
+            ]
+        );
+        assert!(hook_for(&config, &finished(Some(0), Some(45.0))).is_some());
+        assert!(hook_for(&config, &finished(Some(0), Some(5.0))).is_none());
+        assert!(hook_for(&config, &finished(None, None)).is_none());
+        // Unconfigured hooks
+        assert!(hook_for(&config, &Event::ChildExited { pane: 1 }).is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_per_pane() {
+        let mut hooks = Hooks::new(&HooksConfig::default());
+        // Two panes exiting back to back both run, as does a repeated event
+        assert!(hooks.allowed("pane_exited", &Event::ChildExited { pane: 1 }));
+        assert!(hooks.allowed("pane_exited", &Event::ChildExited { pane: 2 }));
+        assert!(hooks.allowed("pane_exited", &Event::ChildExited { pane: 1 }));
+
+        // A bell storm in one pane does not silence the others
+        assert!(hooks.allowed("bell", &Event::Bell { pane: 1 }));
+        assert!(!hooks.allowed("bell", &Event::Bell { pane: 1 }));
+        assert!(hooks.allowed("bell", &Event::Bell { pane: 2 }));
+    }
+}
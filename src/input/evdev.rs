@@ -581,13 +581,7 @@ impl EvdevKeyboard {
                         };
 
                         // Key repeat tracking (modifiers don't repeat)
-                        let is_modifier = matches!(
-                            evdev_code,
-                            KEY_LEFTSHIFT | KEY_RIGHTSHIFT |
-                            KEY_LEFTCTRL | KEY_RIGHTCTRL |
-                            56 | 100 |  // Alt
-                            125 | 126 // Super
-                        );
+                        let is_modifier = is_modifier_keycode(evdev_code);
 
                         if key_state == KeyState::Pressed {
                             if !is_modifier {
@@ -839,6 +833,17 @@ const KEY_F10: u32 = 68;
 const KEY_F11: u32 = 87;
 const KEY_F12: u32 = 88;
 
+/// Check if an evdev keycode is a modifier key (Shift, Ctrl, Alt, Super)
+pub fn is_modifier_keycode(keycode: u32) -> bool {
+    matches!(
+        keycode,
+        KEY_LEFTSHIFT | KEY_RIGHTSHIFT |
+        KEY_LEFTCTRL | KEY_RIGHTCTRL |
+        56 | 100 |  // Alt
+        125 | 126 // Super
+    )
+}
+
 /// Check if key event is a VT switch request (Ctrl+Alt+Fn)
 /// Returns the target VT number (1-12) if it is a VT switch
 pub fn check_vt_switch(event: &RawKeyEvent) -> Option<u16> {
@@ -20,10 +20,15 @@ mod drawing;
 mod drm;
 mod font;
 mod gpu;
+mod hooks;
 mod input;
 mod pane;
+mod remote;
 mod session;
 mod terminal;
+#[cfg(test)]
+mod test_util;
+mod toast;
 mod utils;
 
 use anyhow::{anyhow, Context, Result};
@@ -77,9 +82,10 @@ use constants::{
     LINE_THICKNESS_SCALE, MAX_DISPLAY_SCALE, MAX_FONT_SIZE, MIN_DISPLAY_SCALE, MIN_FONT_SIZE,
     OUTLINE_STROKE_HALF, PROGRESS_DEFAULT, PROGRESS_ERROR, PROGRESS_SUCCESS, PROGRESS_WARNING,
     UI_CANDIDATE_BG, UI_CANDIDATE_SEL, UI_COPY_MODE_BG, UI_CORNER_RADIUS, UI_DIVIDER_COLOR,
-    UI_HEADER_BG, UI_HIGHLIGHT_RADIUS, UI_PANEL_BG, UI_PROGRESS_BG, UI_SHADOW_COLOR,
-    UI_SHADOW_OFFSET, UI_TAB_ACTIVE_BG, UI_TAB_BAR_BG, UI_TAB_INDICATOR, UI_TOAST_BG,
-    UI_TOAST_ERROR_BG, XKB_MOD_ALT, XKB_MOD_CONTROL, XKB_MOD_SHIFT,
+    UI_HEADER_BG, UI_HIGHLIGHT_RADIUS, UI_HINT_LABEL_BG, UI_HINT_MATCH, UI_KEY_TABLE_BG,
+    UI_LINK_HOVER, UI_PANEL_BG, UI_PROGRESS_BG, UI_SEARCH_ERROR, UI_SHADOW_COLOR, UI_SHADOW_OFFSET,
+    UI_TAB_ACTIVE_BG, UI_TAB_BAR_BG, UI_TAB_INDICATOR, UI_TOAST_BG, UI_TOAST_ERROR_BG, XKB_MOD_ALT,
+    XKB_MOD_CONTROL, XKB_MOD_SHIFT,
 };
 
 // ============================================================================
@@ -860,6 +866,7 @@ fn print_help() {
 
 USAGE:
     bcon [OPTIONS]
+    bcon msg COMMAND [OPTIONS]  Control the running bcon (see bcon msg --help)
 
 OPTIONS:
     -h, --help              Print this help message
@@ -883,6 +890,7 @@ EXAMPLES:
     bcon --init-config                Generate default config
     bcon --init-config=vim,jp         Generate config with vim and japanese presets
     bcon --init-config=vim,jp --force Overwrite existing config
+    bcon msg split --right -- htop    Run htop in a new split (from a bcon pane)
     sudo bcon                         Run with root privileges (required for DRM)
 
 CONFIG FILE:
@@ -1000,13 +1008,11 @@ fn test_shaper_mode() -> Result<()> {
     Ok(())
 }
 
-/// Setup and split a new terminal into the active tab
-fn new_term_setup(
+/// Apply config and shared clipboard state to a new pane's terminal
+fn configure_new_term(
     cfg: &config::Config,
-    tab_mgr: &mut pane::tab::TabManager,
-    mut new_term: terminal::Terminal,
-    direction: pane::Direction,
-    available_rect: pane::PaneRect,
+    tab_mgr: &pane::tab::TabManager,
+    new_term: &mut terminal::Terminal,
     cell_w: f32,
     cell_h: f32,
 ) {
@@ -1015,15 +1021,739 @@ fn new_term_setup(
     new_term.grid.set_ansi_palette(cfg.colors.to_palette());
     new_term.notifications_enabled = cfg.notifications.enabled;
     new_term.allow_kitty_remote = cfg.security.allow_kitty_remote;
-    tab_mgr.split(direction, new_term, available_rect);
+    new_term.copy_on_select = cfg.selection.copy_on_select;
+    new_term.copy_command = cfg.clipboard.copy_command.clone();
+    new_term.paste_command = cfg.clipboard.paste_command.clone();
+    new_term.clipboard_command_timeout = Duration::from_millis(cfg.clipboard.command_timeout_ms);
+    new_term.paste_safety = paste_safety(&cfg.clipboard);
+    new_term.clipboard_history = tab_mgr.active_terminal().clipboard_history.clone();
+    new_term.clipboard_broker = tab_mgr.active_terminal().clipboard_broker.clone();
+    new_term.copy_command_queue = tab_mgr.active_terminal().copy_command_queue.clone();
+}
+
+/// Setup and split a new terminal into the active tab
+fn new_term_setup(
+    cfg: &config::Config,
+    tab_mgr: &mut pane::tab::TabManager,
+    mut new_term: terminal::Terminal,
+    direction: pane::Direction,
+    ratio: f32,
+    available_rect: pane::PaneRect,
+    cell_w: f32,
+    cell_h: f32,
+) -> pane::PaneId {
+    configure_new_term(cfg, tab_mgr, &mut new_term, cell_w, cell_h);
+    let id = tab_mgr.split(direction, ratio, new_term, available_rect);
     tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+    id
+}
+
+/// Setup a new terminal in a new tab
+fn new_tab_setup(
+    cfg: &config::Config,
+    tab_mgr: &mut pane::tab::TabManager,
+    mut new_term: terminal::Terminal,
+    available_rect: pane::PaneRect,
+    cell_w: f32,
+    cell_h: f32,
+) -> pane::PaneId {
+    configure_new_term(cfg, tab_mgr, &mut new_term, cell_w, cell_h);
+    tab_mgr.new_tab(new_term, available_rect)
+}
+
+/// Spawn a terminal for a new pane: the login shell as the logged-in user
+/// when known (or `command` instead), otherwise the default shell.
+/// Commands never run without a user to run them as.
+fn spawn_terminal(
+    cfg: &config::Config,
+    extra_env: &[(&str, &str)],
+    cols: usize,
+    rows: usize,
+    uid: Option<u32>,
+    command: Option<&str>,
+) -> Result<terminal::Terminal> {
+    match (uid, command) {
+        (Some(uid), command) => terminal::Terminal::with_scrollback_as_user(
+            cols,
+            rows,
+            cfg.terminal.scrollback_lines,
+            &cfg.terminal.term_env,
+            extra_env,
+            uid,
+            command,
+        ),
+        (None, None) => terminal::Terminal::with_scrollback_env(
+            cols,
+            rows,
+            cfg.terminal.scrollback_lines,
+            &cfg.terminal.term_env,
+            extra_env,
+        ),
+        (None, Some(_)) => anyhow::bail!("no logged-in user to run the command as"),
+    }
+}
+
+/// Hand pane events to remote subscribers and [hooks]
+fn dispatch_pane_events(
+    events: &[remote::events::Event],
+    remote_server: Option<&remote::RemoteServer>,
+    hooks: &mut hooks::Hooks,
+    watcher: &remote::events::EventWatcher,
+    tab_mgr: &pane::tab::TabManager,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+) {
+    if let Some(server) = remote_server {
+        server.emit(events, |pane| {
+            pane_owner(tab_mgr, owners, pane::PaneId(pane))
+        });
+    }
+    for event in events {
+        let uid = watcher.pane_uid(pane::PaneId(event.pane()));
+        hooks.on_event(event, tab_mgr, uid);
+    }
+}
+
+/// User logged in on a pane; `owners` remembers it once the child is gone
+fn pane_owner(
+    tab_mgr: &pane::tab::TabManager,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+    id: pane::PaneId,
+) -> Option<u32> {
+    tab_mgr
+        .pane(id)
+        .and_then(|pane| pane.terminal.logged_in_uid())
+        .or_else(|| owners.get(&id).copied())
+}
+
+/// Record the user logged in on each pane (forgetting closed panes) and
+/// return those users
+fn refresh_pane_owners(
+    tab_mgr: &pane::tab::TabManager,
+    owners: &mut std::collections::HashMap<pane::PaneId, u32>,
+) -> Vec<u32> {
+    owners.retain(|id, _| tab_mgr.tab_of(*id).is_some());
+    for (id, pane) in tab_mgr.tabs.iter().flat_map(|tab| tab.panes.iter()) {
+        if let Some(uid) = pane.terminal.logged_in_uid() {
+            owners.insert(*id, uid);
+        }
+    }
+    let mut users: Vec<u32> = owners.values().copied().collect();
+    users.sort_unstable();
+    users.dedup();
+    users
+}
+
+/// Remote control is for the users logged in on the panes (and bcon's own
+/// user)
+fn remote_peer_allowed(
+    tab_mgr: &pane::tab::TabManager,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+    peer_uid: Option<u32>,
+) -> bool {
+    remote::peer_may_use(peer_uid, None)
+        || tab_mgr
+            .tabs
+            .iter()
+            .flat_map(|tab| tab.panes.keys())
+            .any(|id| remote::peer_may_use(peer_uid, pane_owner(tab_mgr, owners, *id)))
+}
+
+/// Pane a remote request targets: the given one, or the active pane. It must
+/// be one of the peer's panes.
+fn remote_target(
+    tab_mgr: &pane::tab::TabManager,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+    peer_uid: Option<u32>,
+    pane: Option<u16>,
+) -> Result<pane::PaneId> {
+    let id = match pane {
+        Some(id) if tab_mgr.tab_of(pane::PaneId(id)).is_some() => pane::PaneId(id),
+        Some(id) => anyhow::bail!("no pane {}", id),
+        None => tab_mgr.active_pane_id(),
+    };
+    if !remote::peer_may_use(peer_uid, pane_owner(tab_mgr, owners, id)) {
+        anyhow::bail!("permission denied for pane {}", id.0);
+    }
+    Ok(id)
+}
+
+/// Tab (0-based) a remote request targets: it must hold one of the peer's panes
+fn remote_tab(
+    tab_mgr: &pane::tab::TabManager,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+    peer_uid: Option<u32>,
+    index: usize,
+) -> Result<usize> {
+    let allowed = tab_mgr.tabs[index]
+        .panes
+        .keys()
+        .any(|id| remote::peer_may_use(peer_uid, pane_owner(tab_mgr, owners, *id)));
+    if !allowed {
+        anyhow::bail!("permission denied for tab {}", index + 1);
+    }
+    Ok(index)
+}
+
+/// Run a remote control request from `peer_uid` against the tabs
+#[allow(clippy::too_many_arguments)]
+fn run_remote_request(
+    request: remote::Request,
+    peer_uid: Option<u32>,
+    owners: &std::collections::HashMap<pane::PaneId, u32>,
+    tab_mgr: &mut pane::tab::TabManager,
+    cfg: &config::Config,
+    extra_env: &[(&str, &str)],
+    available_rect: pane::PaneRect,
+    cell_w: f32,
+    cell_h: f32,
+) -> Result<serde_json::Value> {
+    use remote::Request;
+    use serde_json::json;
+
+    let target =
+        |tab_mgr: &pane::tab::TabManager, pane| remote_target(tab_mgr, owners, peer_uid, pane);
+    let data = match request {
+        Request::Ls => {
+            // Only the peer's panes, and the tabs holding them
+            let active = tab_mgr.active_pane_id();
+            let tabs: Vec<_> = tab_mgr
+                .tabs
+                .iter()
+                .enumerate()
+                .filter_map(|(i, tab)| {
+                    let mut ids: Vec<_> = tab
+                        .panes
+                        .keys()
+                        .copied()
+                        .filter(|id| {
+                            remote::peer_may_use(peer_uid, pane_owner(tab_mgr, owners, *id))
+                        })
+                        .collect();
+                    if ids.is_empty() {
+                        return None;
+                    }
+                    ids.sort_by_key(|id| id.0);
+                    let panes: Vec<_> = ids
+                        .iter()
+                        .map(|id| {
+                            let term = &tab.panes[id].terminal;
+                            json!({
+                                "id": id.0,
+                                "active": *id == active,
+                                "focused_in_tab": *id == tab.active_pane,
+                                "zoomed": tab.zoomed_pane == Some(*id),
+                                "cols": term.grid.cols(),
+                                "rows": term.grid.rows(),
+                                "title": term.grid.window_title,
+                                "cwd": term.current_directory,
+                            })
+                        })
+                        .collect();
+                    Some(json!({
+                        "tab": i + 1,
+                        "title": tab.title,
+                        "active": i == tab_mgr.active_tab,
+                        "panes": panes,
+                    }))
+                })
+                .collect();
+            json!({ "tabs": tabs })
+        }
+        Request::Split {
+            pane,
+            direction,
+            ratio,
+        } => {
+            let target = target(tab_mgr, pane)?;
+            let term = &tab_mgr.pane(target).expect("target pane").terminal;
+            let (cols, rows) = (term.grid.cols(), term.grid.rows());
+            let new_term = spawn_terminal(cfg, extra_env, cols, rows, term.logged_in_uid(), None)?;
+            tab_mgr.focus_pane(target);
+            let id = new_term_setup(
+                cfg,
+                tab_mgr,
+                new_term,
+                direction.to_pane_direction(),
+                ratio,
+                available_rect,
+                cell_w,
+                cell_h,
+            );
+            json!({ "pane": id.0 })
+        }
+        Request::Launch {
+            command,
+            location,
+            pane,
+            direction,
+            ratio,
+        } => {
+            let target = target(tab_mgr, pane)?;
+            let term = &tab_mgr.pane(target).expect("target pane").terminal;
+            let (cols, rows) = (term.grid.cols(), term.grid.rows());
+            let uid = term.logged_in_uid();
+            let new_term = spawn_terminal(cfg, extra_env, cols, rows, uid, Some(&command))?;
+            let id = match location {
+                remote::LaunchLocation::Split => {
+                    tab_mgr.focus_pane(target);
+                    new_term_setup(
+                        cfg,
+                        tab_mgr,
+                        new_term,
+                        direction.to_pane_direction(),
+                        ratio,
+                        available_rect,
+                        cell_w,
+                        cell_h,
+                    )
+                }
+                remote::LaunchLocation::Tab => {
+                    new_tab_setup(cfg, tab_mgr, new_term, available_rect, cell_w, cell_h)
+                }
+            };
+            json!({ "pane": id.0 })
+        }
+        Request::Focus { pane, tab } => {
+            match (pane, tab) {
+                (Some(_), _) => {
+                    let target = target(tab_mgr, pane)?;
+                    tab_mgr.focus_pane(target);
+                }
+                (None, Some(n)) => {
+                    if !(1..=tab_mgr.tab_count()).contains(&n) {
+                        anyhow::bail!("no tab {}", n);
+                    }
+                    let index = remote_tab(tab_mgr, owners, peer_uid, n - 1)?;
+                    tab_mgr.goto_tab(index);
+                }
+                (None, None) => anyhow::bail!("focus needs a pane or a tab"),
+            }
+            serde_json::Value::Null
+        }
+        Request::Close { pane } => {
+            let target = target(tab_mgr, pane)?;
+            if tab_mgr.tab_count() == 1 && tab_mgr.active_tab().pane_count() == 1 {
+                anyhow::bail!("refusing to close the last pane");
+            }
+            let previous = tab_mgr.active_pane_id();
+            tab_mgr.focus_pane(target);
+            tab_mgr.close_active_pane(available_rect);
+            if previous != target {
+                tab_mgr.focus_pane(previous);
+            }
+            tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+            serde_json::Value::Null
+        }
+        Request::Resize {
+            pane,
+            direction,
+            amount,
+        } => {
+            let target = target(tab_mgr, pane)?;
+            let previous = tab_mgr.active_pane_id();
+            tab_mgr.focus_pane(target);
+            let (axis, sign) = direction.resize_axis();
+            let resized =
+                tab_mgr
+                    .active_tab_mut()
+                    .resize_active(sign * amount, axis, available_rect);
+            tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+            tab_mgr.focus_pane(previous);
+            if !resized {
+                anyhow::bail!("pane {} cannot be resized that way", target.0);
+            }
+            serde_json::Value::Null
+        }
+        Request::SendText { pane, text } => {
+            let target = target(tab_mgr, pane)?;
+            let term = &mut tab_mgr.pane_mut(target).expect("target pane").terminal;
+            term.write_to_pty(text.as_bytes())?;
+            serde_json::Value::Null
+        }
+        Request::GetText {
+            pane,
+            scrollback,
+            ansi,
+        } => {
+            use terminal::export;
+            let target = target(tab_mgr, pane)?;
+            let grid = &tab_mgr.pane(target).expect("target pane").terminal.grid;
+            let text = match (scrollback, ansi) {
+                (false, false) => export::screen_to_text(grid),
+                (false, true) => export::screen_to_ansi(grid),
+                (true, false) => export::to_text(grid),
+                (true, true) => export::to_ansi(grid),
+            };
+            json!({ "text": text })
+        }
+        Request::SetTabTitle { tab, title } => {
+            let index = match tab {
+                Some(n) if (1..=tab_mgr.tab_count()).contains(&n) => n - 1,
+                Some(n) => anyhow::bail!("no tab {}", n),
+                None => tab_mgr.active_tab,
+            };
+            let index = remote_tab(tab_mgr, owners, peer_uid, index)?;
+            let tab = &mut tab_mgr.tabs[index];
+            tab.title_locked = !title.is_empty();
+            tab.title = if title.is_empty() {
+                tab.active_pane()
+                    .terminal
+                    .grid
+                    .window_title
+                    .clone()
+                    .unwrap_or_default()
+            } else {
+                title
+            };
+            serde_json::Value::Null
+        }
+        Request::Subscribe { events } => {
+            let known = |e: &&String| remote::events::EVENT_NAMES.contains(&e.as_str());
+            if let Some(name) = events.iter().find(|e| !known(e)) {
+                anyhow::bail!("unknown event {}", name);
+            }
+            serde_json::Value::Null
+        }
+    };
+    Ok(data)
+}
+
+/// Clipboard history picker layout: (header height, entry height, visible entries)
+fn clipboard_picker_layout(panel_h: f32, cell_h: f32) -> (f32, f32, usize) {
+    let padding = 8.0_f32;
+    let header_h = cell_h + padding * 2.0;
+    let footer_h = cell_h + padding;
+    let item_h = cell_h * 2.0 + 4.0; // age+source line + preview line + gap
+    let max_visible = ((panel_h - header_h - footer_h) / item_h).floor().max(1.0) as usize;
+    (header_h, item_h, max_visible)
+}
+
+/// First entry shown by the picker (keeps the highlighted entry visible)
+fn clipboard_picker_first(selected: usize, max_visible: usize) -> usize {
+    (selected + 1).saturating_sub(max_visible)
+}
+
+/// Entry index under a click in the clipboard history picker
+fn clipboard_picker_hit(
+    y: f32,
+    panel_h: f32,
+    cell_h: f32,
+    selected: usize,
+    count: usize,
+) -> Option<usize> {
+    let (header_h, item_h, max_visible) = clipboard_picker_layout(panel_h, cell_h);
+    if y < header_h {
+        return None;
+    }
+    let row = ((y - header_h) / item_h) as usize;
+    let idx = clipboard_picker_first(selected, max_visible) + row;
+    (row < max_visible && idx < count).then_some(idx)
+}
+
+/// Start desktop notification forwarding when [notifications] desktop is on.
+/// Running as root, notifications go to the session bus of `uid`, the
+/// logged-in user (none before login or before that bus is up).
+fn start_desktop_notifier(
+    cfg: &config::NotificationConfig,
+    uid: Option<u32>,
+) -> Option<terminal::desktop_notify::DesktopNotifier> {
+    if !cfg.enabled || !cfg.desktop {
+        return None;
+    }
+    let (address, user) = if unsafe { libc::getuid() } == 0 {
+        // A session bus only lets its own user in: talk to it as that user
+        let uid = uid?;
+        let bus = format!("/run/user/{}/bus", uid);
+        if !std::path::Path::new(&bus).exists() {
+            log::debug!("Desktop notifications: no session bus at {} yet", bus);
+            return None;
+        }
+        match terminal::pty::UserAccount::lookup(uid) {
+            Ok(user) => (Some(format!("unix:path={}", bus)), Some(user)),
+            Err(e) => {
+                warn!("Desktop notifications disabled: {}", e);
+                return None;
+            }
+        }
+    } else {
+        (None, None)
+    };
+    match terminal::desktop_notify::DesktopNotifier::start(address, user) {
+        Ok(notifier) => Some(notifier),
+        Err(e) => {
+            warn!("Desktop notifications disabled: {}", e);
+            None
+        }
+    }
+}
+
+/// Send the notifications every pane received since the last call.
+/// `seen` holds each pane's notification_seq; None = only record them.
+fn forward_desktop_notifications(
+    notifier: &terminal::desktop_notify::DesktopNotifier,
+    tab_mgr: &pane::tab::TabManager,
+    seen: &mut Option<std::collections::HashMap<pane::PaneId, u64>>,
+    vt_active: bool,
+    muted: bool,
+) {
+    let send = seen.is_some() && !muted;
+    let seen = seen.get_or_insert_with(Default::default);
+    seen.retain(|id, _| tab_mgr.tab_of(*id).is_some());
+    for tab in &tab_mgr.tabs {
+        for (id, pane) in &tab.panes {
+            let term = &pane.terminal;
+            let last = seen.insert(*id, term.notification_seq).unwrap_or(0);
+            if !send || term.notification_seq <= last {
+                continue;
+            }
+            // New notifications are at the tail of the history
+            let new = (term.notification_seq - last) as usize;
+            let skip = term.notifications.len().saturating_sub(new);
+            let (focused, visible) = toast::pane_focus(tab_mgr, *id, vt_active);
+            for notification in term.notifications.iter().skip(skip) {
+                if notification.should_show(focused, visible) {
+                    notifier.send(id.0, notification);
+                }
+            }
+        }
+    }
+}
+
+/// Paste safety settings from [clipboard]
+fn paste_safety(cfg: &config::ClipboardConfig) -> terminal::paste::PasteSafety {
+    terminal::paste::PasteSafety {
+        sanitize: cfg.paste_sanitize,
+        confirm: cfg.paste_confirm,
+        warn_size: cfg.paste_warn_size,
+    }
+}
+
+/// Apply [clipboard] history settings to the history shared by all panes
+fn configure_clipboard_history(
+    history: &terminal::clipboard::SharedClipboardHistory,
+    cfg: &config::ClipboardConfig,
+) {
+    let path = cfg
+        .history_persist
+        .then(terminal::clipboard::default_history_path)
+        .flatten();
+    terminal::clipboard::ClipboardHistory::lock(history).configure(
+        cfg.history_size,
+        cfg.history_skip_no_echo,
+        path,
+    );
+}
+
+/// Share the clipboard with the bcon instances of user `uid` on other VTs,
+/// replacing the broker of every pane (None: stop sharing)
+fn start_clipboard_broker(
+    cfg: &config::Config,
+    tab_mgr: &mut pane::tab::TabManager,
+    uid: Option<u32>,
+) {
+    let history = tab_mgr.active_terminal().clipboard_history.clone();
+    let broker = uid
+        .and_then(terminal::clipboard_broker::default_socket_path)
+        .and_then(|path| {
+            terminal::clipboard_broker::ClipboardBroker::start(
+                &path,
+                &cfg.paths.clipboard_file,
+                history,
+            )
+        })
+        .map(std::sync::Arc::new);
+    for tab in tab_mgr.tabs.iter_mut() {
+        for pane in tab.panes.values_mut() {
+            pane.terminal.clipboard_broker = broker.clone();
+        }
+    }
+}
+
+/// Send a key press to fcitx5 if connected, otherwise encode it directly to the PTY
+fn forward_key(
+    term: &mut terminal::Terminal,
+    raw: &input::evdev::RawKeyEvent,
+    ime_client: Option<&input::ime::ImeClient>,
+) {
+    if let Some(ime) = ime_client {
+        ime.send_key(input::ime::ImeKeyEvent {
+            keysym: raw.keysym,
+            keycode: raw.keycode,
+            state: raw.xkb_state,
+            is_release: false,
+        });
+    } else {
+        // Send directly to PTY when IME client is unavailable
+        let sym = xkbcommon::xkb::Keysym::new(raw.keysym);
+        let kb_config = input::KeyboardConfig {
+            application_cursor_keys: term.grid.modes.application_cursor_keys,
+            modify_other_keys: term.grid.keyboard.modify_other_keys,
+            kitty_flags: term.grid.keyboard.kitty_flags,
+            key_action: raw.action,
+        };
+        let bytes = input::keysym_to_bytes_with_mods(
+            sym,
+            &raw.utf8,
+            raw.mods_ctrl,
+            raw.mods_alt,
+            raw.mods_shift,
+            &kb_config,
+        );
+        if !bytes.is_empty() {
+            let _ = term.write_to_pty(&bytes);
+        }
+    }
+}
+
+/// Outcome of opening a link
+enum LinkOpen {
+    /// Copied to the clipboard (no handler, or no user to run it as)
+    Copied,
+    /// Handler started in the background
+    Spawned,
+    /// Handler must run in a new pane: (command, uid)
+    Pane(String, u32),
+}
+
+/// Open a link (URL, OSC 8 target or hinted path) with its configured handler.
+/// Handlers run as the logged-in user; without one the link is copied.
+fn open_link(links: &config::LinksConfig, term: &mut terminal::Terminal, text: &str) -> LinkOpen {
+    let target = terminal::links::LinkTarget::parse(
+        text,
+        term.current_directory.as_deref(),
+        term.user_home_dir().as_deref(),
+    );
+    let handler = links.handler_for(&target.scheme);
+    let command = match &handler {
+        Some(h) => h.command.as_str(),
+        None => links.open_command.as_str(),
+    };
+
+    // Never run handlers as root: bcon itself may be root for DRM access
+    let uid = match unsafe { libc::getuid() } {
+        0 => term.logged_in_uid(),
+        uid => Some(uid),
+    };
+    let uid = match uid {
+        Some(uid) if !command.is_empty() => uid,
+        _ => {
+            term.copy_url_to_clipboard(&target.url);
+            return LinkOpen::Copied;
+        }
+    };
+
+    let command = terminal::links::expand_command(command, &target);
+    if handler.is_some_and(|h| h.pane) {
+        return LinkOpen::Pane(command, uid);
+    }
+    match terminal::pty::spawn_detached_as_user(uid, &command, &[]) {
+        Ok(()) => LinkOpen::Spawned,
+        Err(e) => {
+            warn!("Failed to open link with '{}': {}", command, e);
+            term.copy_url_to_clipboard(&target.url);
+            LinkOpen::Copied
+        }
+    }
+}
+
+/// Outcome of a mouse-bound action
+enum MouseActionResult {
+    Done,
+    /// A selection was started; dragging extends it
+    Drag,
+    /// A link was found (flash for feedback)
+    Flash,
+    /// A link handler must be opened in a new pane: (command, uid)
+    OpenPane(String, u32),
+}
+
+/// Run a mouse-bound action at a cell position
+fn run_mouse_action(
+    term: &mut terminal::Terminal,
+    action: &config::Action,
+    row: usize,
+    col: usize,
+    grid_rows: usize,
+    links: &config::LinksConfig,
+    selection_rules: &terminal::smart_selection::SelectionRules,
+) -> MouseActionResult {
+    match action {
+        config::Action::StartSelection | config::Action::StartBlockSelection => {
+            let line = term.display_line(row);
+            term.selection = Some(terminal::Selection {
+                anchor_row: line,
+                anchor_col: col,
+                end_row: line,
+                end_col: col,
+                block: *action == config::Action::StartBlockSelection,
+            });
+            return MouseActionResult::Drag;
+        }
+        config::Action::ExtendSelection => {
+            let line = term.display_line(row);
+            match term.selection {
+                Some(ref mut sel) => {
+                    sel.end_row = line;
+                    sel.end_col = col;
+                }
+                None => {
+                    term.selection = Some(terminal::Selection {
+                        anchor_row: line,
+                        anchor_col: col,
+                        end_row: line,
+                        end_col: col,
+                        block: false,
+                    });
+                }
+            }
+            return MouseActionResult::Drag;
+        }
+        config::Action::SelectWord => term.select_word(row, col, selection_rules),
+        config::Action::SelectLine => term.select_line(row),
+        config::Action::OpenLink => {
+            if let Some(url) = term.detect_url_at(row, col) {
+                return match open_link(links, term, &url) {
+                    LinkOpen::Pane(command, uid) => MouseActionResult::OpenPane(command, uid),
+                    LinkOpen::Copied | LinkOpen::Spawned => MouseActionResult::Flash,
+                };
+            }
+        }
+        config::Action::CopyLink => {
+            if let Some(url) = term.detect_url_at(row, col) {
+                term.copy_url_to_clipboard(&url);
+                return MouseActionResult::Flash;
+            }
+        }
+        config::Action::Copy => term.copy_selection(),
+        config::Action::Paste => {
+            let _ = term.paste_clipboard();
+        }
+        config::Action::PastePrimary => {
+            let _ = term.paste_primary();
+        }
+        config::Action::ScrollUp => term.scroll_back(grid_rows / 2),
+        config::Action::ScrollDown => term.scroll_forward(grid_rows / 2),
+        config::Action::Scroll { lines } => {
+            if *lines < 0 {
+                term.scroll_back(lines.unsigned_abs() as usize);
+            } else {
+                term.scroll_forward(*lines as usize);
+            }
+        }
+        config::Action::SendText { text } => {
+            let _ = term.write_to_pty(text.as_bytes());
+        }
+        _ => {}
+    }
+    MouseActionResult::Done
 }
 
 /// Update text selection range with Shift+Arrow keys
 fn handle_selection_key(term: &mut terminal::Terminal, keycode: u32, cols: usize) {
-    let cur_row = term.grid.cursor_row;
+    let cur_row = term.grid.scrollback_total() + term.grid.cursor_row as u64;
     let cur_col = term.grid.cursor_col;
-    let rows = term.grid.rows();
+    let first_line = term.first_line();
+    let last_line = term.grid.scrollback_total() + term.grid.rows() as u64 - 1;
 
     // Start selection with cursor position as anchor if no selection exists
     let sel = term.selection.get_or_insert(terminal::Selection {
@@ -1031,6 +1761,7 @@ fn handle_selection_key(term: &mut terminal::Terminal, keycode: u32, cols: usize
         anchor_col: cur_col,
         end_row: cur_row,
         end_col: cur_col,
+        block: false,
     });
 
     // Use evdev keycodes from the keycodes module
@@ -1040,7 +1771,7 @@ fn handle_selection_key(term: &mut terminal::Terminal, keycode: u32, cols: usize
         KEY_LEFT => {
             if sel.end_col > 0 {
                 sel.end_col -= 1;
-            } else if sel.end_row > 0 {
+            } else if sel.end_row > first_line {
                 sel.end_row -= 1;
                 sel.end_col = cols.saturating_sub(1);
             }
@@ -1048,16 +1779,16 @@ fn handle_selection_key(term: &mut terminal::Terminal, keycode: u32, cols: usize
         KEY_RIGHT => {
             if sel.end_col < cols - 1 {
                 sel.end_col += 1;
-            } else if sel.end_row < rows - 1 {
+            } else if sel.end_row < last_line {
                 sel.end_row += 1;
                 sel.end_col = 0;
             }
         }
         KEY_UP => {
-            sel.end_row = sel.end_row.saturating_sub(1);
+            sel.end_row = sel.end_row.saturating_sub(1).max(first_line);
         }
         KEY_DOWN => {
-            sel.end_row = (sel.end_row + 1).min(rows - 1);
+            sel.end_row = (sel.end_row + 1).min(last_line);
         }
         KEY_HOME => {
             sel.end_col = 0;
@@ -1147,6 +1878,113 @@ fn save_screenshot(
     Ok(())
 }
 
+/// Export the scrollback and screen of a pane, returning the file path.
+/// The file is private (0600) and, when bcon runs as root, owned by `uid`,
+/// the user logged in on the pane.
+fn save_scrollback(
+    grid: &terminal::grid::Grid,
+    format: config::ExportFormat,
+    screenshot_dir: &str,
+    user_home: Option<&str>,
+    uid: Option<u32>,
+    appearance: &config::AppearanceConfig,
+) -> Result<String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use terminal::export;
+
+    let screenshot_dir = expand_path(screenshot_dir, user_home);
+    let content = match format {
+        config::ExportFormat::Text => export::to_text(grid),
+        config::ExportFormat::Ansi => export::to_ansi(grid),
+        config::ExportFormat::Html => {
+            let to_u8 = |(r, g, b): (f32, f32, f32)| {
+                (
+                    (r * 255.0).round() as u8,
+                    (g * 255.0).round() as u8,
+                    (b * 255.0).round() as u8,
+                )
+            };
+            export::to_html(
+                grid,
+                to_u8(appearance.foreground_rgb()),
+                to_u8(appearance.background_rgb()),
+            )
+        }
+    };
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let path = format!(
+        "{}/bcon_scrollback_{}.{}",
+        screenshot_dir,
+        timestamp,
+        format.extension()
+    );
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&path)?;
+    if unsafe { libc::getuid() } == 0 {
+        if let Some(uid) = uid {
+            std::os::unix::fs::fchown(&file, Some(uid), None)?;
+        }
+    }
+    file.write_all(content.as_bytes())?;
+
+    info!("Scrollback saved: {}", path);
+    Ok(path)
+}
+
+/// Dump the scrollback to a private temp file owned by `uid` and build the
+/// pager command for it. The file is removed when the pager exits.
+fn scrollback_pager_command(
+    grid: &terminal::grid::Grid,
+    ansi: bool,
+    pager: &str,
+    uid: u32,
+) -> Result<String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use terminal::links::shell_quote;
+
+    let content = if ansi {
+        terminal::export::to_ansi(grid)
+    } else {
+        terminal::export::to_text(grid)
+    };
+    let path = std::env::temp_dir().join(format!(
+        "bcon_scrollback_{}_{}.txt",
+        std::process::id(),
+        chrono::Local::now().format("%Y%m%d_%H%M%S%f")
+    ));
+    // create_new: never follow a planted symlink in a shared temp dir
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(content.as_bytes())?;
+    std::os::unix::fs::fchown(&file, Some(uid), None)?;
+
+    let path = shell_quote(&path.to_string_lossy());
+    let pager = if pager.is_empty() {
+        "${PAGER:-less +G}"
+    } else {
+        pager
+    };
+    let run = if pager.contains("{path}") {
+        pager.replace("{path}", &path)
+    } else {
+        format!("{} {}", pager, path)
+    };
+    // Run under sh so the cleanup trap works whatever the user's shell is
+    let script = format!("f={}; trap 'rm -f -- \"$f\"' EXIT HUP TERM; {}", path, run);
+    Ok(format!("sh -c {}", shell_quote(&script)))
+}
+
 /// Truncate a string to fit within `max_cols` display columns.
 /// CJK characters count as 2 columns. Appends "…" if truncated.
 fn truncate_to_width(s: &str, max_cols: usize) -> String {
@@ -1171,6 +2009,11 @@ fn main() -> Result<()> {
     // Check command line arguments
     let args: Vec<String> = std::env::args().collect();
 
+    // bcon msg: remote control client
+    if args.get(1).is_some_and(|a| a == "msg") {
+        return remote::client::run(&args[2..]);
+    }
+
     // --help
     if args.iter().any(|a| a == "--help" || a == "-h") {
         print_help();
@@ -1300,37 +2143,16 @@ fn main() -> Result<()> {
     // Load config file
     let mut cfg = config::Config::load();
 
-    // Parse keybinds (multiple keys per action supported)
-    let mut kb_copy = config::ParsedKeybinds::parse(&cfg.keybinds.copy);
-    let mut kb_paste = config::ParsedKeybinds::parse(&cfg.keybinds.paste);
-    let mut kb_screenshot = config::ParsedKeybinds::parse(&cfg.keybinds.screenshot);
-    let mut kb_search = config::ParsedKeybinds::parse(&cfg.keybinds.search);
-    let mut kb_copy_mode = config::ParsedKeybinds::parse(&cfg.keybinds.copy_mode);
-    let mut kb_font_increase = config::ParsedKeybinds::parse(&cfg.keybinds.font_increase);
-    let mut kb_font_decrease = config::ParsedKeybinds::parse(&cfg.keybinds.font_decrease);
-    let mut kb_font_reset = config::ParsedKeybinds::parse(&cfg.keybinds.font_reset);
-    let mut kb_scroll_up = config::ParsedKeybinds::parse(&cfg.keybinds.scroll_up);
-    let mut kb_scroll_down = config::ParsedKeybinds::parse(&cfg.keybinds.scroll_down);
-    let mut kb_reset_terminal = config::ParsedKeybinds::parse(&cfg.keybinds.reset_terminal);
-    let mut kb_notification_panel = config::ParsedKeybinds::parse(&cfg.keybinds.notification_panel);
-    let mut kb_notification_mute = config::ParsedKeybinds::parse(&cfg.keybinds.notification_mute);
-    // Pane/tab keybinds
-    let mut kb_split_right = config::ParsedKeybinds::parse(&cfg.keybinds.split_right);
-    let mut kb_split_down = config::ParsedKeybinds::parse(&cfg.keybinds.split_down);
-    let mut kb_close_pane = config::ParsedKeybinds::parse(&cfg.keybinds.close_pane);
-    let mut kb_pane_left = config::ParsedKeybinds::parse(&cfg.keybinds.pane_left);
-    let mut kb_pane_right = config::ParsedKeybinds::parse(&cfg.keybinds.pane_right);
-    let mut kb_pane_up = config::ParsedKeybinds::parse(&cfg.keybinds.pane_up);
-    let mut kb_pane_down = config::ParsedKeybinds::parse(&cfg.keybinds.pane_down);
-    let mut kb_resize_left = config::ParsedKeybinds::parse(&cfg.keybinds.resize_left);
-    let mut kb_resize_right = config::ParsedKeybinds::parse(&cfg.keybinds.resize_right);
-    let mut kb_resize_up = config::ParsedKeybinds::parse(&cfg.keybinds.resize_up);
-    let mut kb_resize_down = config::ParsedKeybinds::parse(&cfg.keybinds.resize_down);
-    let mut kb_zoom_pane = config::ParsedKeybinds::parse(&cfg.keybinds.zoom_pane);
-    let mut kb_new_tab = config::ParsedKeybinds::parse(&cfg.keybinds.new_tab);
-    let mut kb_close_tab = config::ParsedKeybinds::parse(&cfg.keybinds.close_tab);
-    let mut kb_next_tab = config::ParsedKeybinds::parse(&cfg.keybinds.next_tab);
-    let mut kb_prev_tab = config::ParsedKeybinds::parse(&cfg.keybinds.prev_tab);
+    // Build key binding table (named keybinds + [[keybinds.bind]] entries)
+    let mut keybinds = config::KeyBindings::from_config(&cfg.keybinds);
+    let mut mouse_binds = config::MouseBindings::from_config(&cfg.mouse);
+    let mut hint_patterns =
+        terminal::hints::HintPatterns::new(&cfg.hints.user_patterns(), cfg.hints.builtin);
+    let mut selection_rules = terminal::smart_selection::SelectionRules::new(
+        &cfg.selection.word_chars,
+        &cfg.selection.user_rules(),
+        cfg.selection.builtin,
+    );
 
     // Config file change watcher (Linux only)
     // Watch the actual loaded config path, not just the default path
@@ -1846,17 +2668,34 @@ Make sure seatd/logind is running and you're on an active VT."
         input::ime::ensure_ime_environment();
     }
 
+    // Remote control socket (read at startup)
+    let remote_server = if cfg.remote.enabled {
+        let path = if cfg.remote.socket.is_empty() {
+            remote::default_socket_path()
+        } else {
+            expand_path(&cfg.remote.socket, None)
+        };
+        remote::RemoteServer::start(&path)
+    } else {
+        None
+    };
+
     // Pass DBUS_SESSION_BUS_ADDRESS to child process.
     // For non-root: shell is exec'd directly, so extra_env works.
     // For root/systemd: /bin/login calls clearenv(), so this is lost —
     // but /etc/profile.d/bcon-dbus.sh (written by ensure_ime_environment)
     // provides the address to login shells instead.
     let dbus_addr = input::ime::dbus_address();
-    let extra_env: Vec<(&str, &str)> = if let Some(ref addr) = dbus_addr {
+    let mut extra_env: Vec<(&str, &str)> = if let Some(ref addr) = dbus_addr {
         vec![("DBUS_SESSION_BUS_ADDRESS", addr.as_str())]
     } else {
         vec![]
     };
+    // Lets `bcon msg` in child shells find this instance (cleared by
+    // /bin/login too; bcon msg then looks for the newest socket)
+    if let Some(server) = &remote_server {
+        extra_env.push(("BCON_SOCKET", server.path()));
+    }
 
     info!("PTY fork...");
     let init_term = terminal::Terminal::with_scrollback_env(
@@ -1895,6 +2734,21 @@ Make sure seatd/logind is running and you're on an active VT."
 
         // Apply security settings from config
         term.allow_kitty_remote = cfg.security.allow_kitty_remote;
+
+        term.copy_on_select = cfg.selection.copy_on_select;
+        term.copy_command = cfg.clipboard.copy_command.clone();
+        term.paste_command = cfg.clipboard.paste_command.clone();
+        term.clipboard_command_timeout = Duration::from_millis(cfg.clipboard.command_timeout_ms);
+        term.paste_safety = paste_safety(&cfg.clipboard);
+
+        // Clipboard history (shared with panes and tabs created later)
+        configure_clipboard_history(&term.clipboard_history, &cfg.clipboard);
+    }
+
+    // Share the clipboard with this user's bcon instances on other VTs
+    // (running as root: once a user logs in, see the session check below)
+    if cfg.clipboard.share && unsafe { libc::getuid() } != 0 {
+        start_clipboard_broker(&cfg, &mut tab_mgr, Some(unsafe { libc::geteuid() }));
     }
 
     // Display /etc/issue (like getty does) if running as root on a VT.
@@ -2066,6 +2920,7 @@ Make sure seatd/logind is running and you're on an active VT."
     // Double/triple click detection
     let mut last_click_time = std::time::Instant::now();
     let mut click_count = 0u8;
+    let mut last_click_button = 0u32;
     let mut last_click_col = 0usize;
     let mut last_click_row = 0usize;
 
@@ -2079,11 +2934,30 @@ Make sure seatd/logind is running and you're on an active VT."
     // Font size change request (currently log output only)
     let mut font_size_delta: i32 = 0;
 
-    // Ctrl key state tracking (for URL click)
+    // Modifier state tracking (for mouse bindings)
     let mut ctrl_pressed = false;
+    let mut shift_pressed = false;
+    let mut alt_pressed = false;
 
     // Search mode
     let mut search_mode = false;
+    // Regex / case toggles persist across searches
+    let mut search_options = terminal::search::SearchOptions::default();
+    // Copy mode named registers ("a .. "z)
+    let mut copy_registers: std::collections::HashMap<char, String> =
+        std::collections::HashMap::new();
+
+    // What to do with the match picked in hints mode
+    let mut hint_mode = config::HintMode::Copy;
+
+    // Link handler or scrollback pager waiting to be opened in a new pane: (command, uid)
+    let mut pending_command_pane: Option<(String, u32)> = None;
+
+    // OSC 8 link under the mouse pointer (hover highlight)
+    let mut hover_link: Option<std::sync::Arc<terminal::grid::Hyperlink>> = None;
+
+    // Active key table (leader key mode)
+    let mut active_key_table: Option<config::ActiveKeyTable> = None;
 
     // Screenshot flag
     let mut take_screenshot = false;
@@ -2093,15 +2967,30 @@ Make sure seatd/logind is running and you're on an active VT."
     let mut notification_panel_scroll: usize = 0;
     let mut notification_muted = false;
 
-    // Toast notification entries
-    struct ToastEntry {
-        title: String,
-        body: String,
-        urgency: u8,
-        show_until: std::time::Instant,
-    }
-    let mut toast_notifications: Vec<ToastEntry> = Vec::new();
-    let mut last_notification_seq: u64 = 0;
+    // Clipboard history picker state (index of the highlighted entry, newest = 0)
+    let mut clipboard_picker_open = false;
+    let mut clipboard_picker_selected: usize = 0;
+
+    let mut event_watcher = remote::events::EventWatcher::default();
+    let mut hooks = hooks::Hooks::new(&cfg.hooks);
+    // Desktop notifications ([notifications] desktop) and the last
+    // notification forwarded per pane
+    let mut desktop_notifier = start_desktop_notifier(&cfg.notifications, None);
+    let mut desktop_seen = Some(std::collections::HashMap::new());
+    // Toast notifications from every pane
+    let mut toasts = toast::Toasts::default();
+    toasts.close_reports = desktop_notifier.is_none();
+    // Running as root, per-user services (clipboard broker, desktop
+    // notifications) follow the user logged in on the active pane, and
+    // remote control learns who is logged in on each pane; checked every few
+    // seconds
+    let session_watch = unsafe { libc::getuid() } == 0;
+    let clipboard_share = cfg.clipboard.share;
+    let mut session_uid: Option<u32> = None;
+    // User whose session bus desktop_notifier talks to
+    let mut desktop_uid: Option<u32> = None;
+    let mut session_check_at = Instant::now();
+    let mut pane_owners = std::collections::HashMap::new();
     // Track previous overlay state to force full FBO clear when overlays disappear
     let mut prev_had_overlays = false;
 
@@ -2188,24 +3077,124 @@ Make sure seatd/logind is running and you're on an active VT."
             Vec::new()
         };
 
-        // Borrow the active terminal for this iteration.
-        // The borrow is released at the end of each iteration (or via `continue 'main_loop`).
-        let term = tab_mgr.active_terminal_mut();
-
-        // Poll for session events (VT switching)
-        #[cfg(all(target_os = "linux", feature = "seatd"))]
-        if let Some(ref seat_session) = seat_session {
-            // Dispatch libseat events (drop borrow before processing events).
-            let _ = seat_session.borrow_mut().dispatch();
+        // Pane events for remote subscribers and [hooks] (before borrowing term)
+        let subscribed = remote_server.as_ref().is_some_and(|s| s.has_subscribers());
+        if subscribed || hooks.wants_pane_events() {
+            let events = event_watcher.poll(&tab_mgr);
+            dispatch_pane_events(
+                &events,
+                remote_server.as_ref(),
+                &mut hooks,
+                &event_watcher,
+                &tab_mgr,
+                &pane_owners,
+            );
+        } else {
+            event_watcher.clear();
+        }
 
-            // Drain events first to avoid holding a RefCell borrow while handling them.
-            let mut seat_events = Vec::new();
-            loop {
-                let event = { seat_session.borrow().try_recv_event() };
-                if let Some(event) = event {
-                    seat_events.push(event);
-                } else {
-                    break;
+        if session_watch && Instant::now() >= session_check_at {
+            session_check_at = Instant::now() + Duration::from_secs(2);
+            if let Some(server) = &remote_server {
+                server.set_users(refresh_pane_owners(&tab_mgr, &mut pane_owners));
+            }
+            let uid = tab_mgr.active_terminal().logged_in_uid();
+            if uid != session_uid {
+                session_uid = uid;
+                if clipboard_share {
+                    start_clipboard_broker(&cfg, &mut tab_mgr, uid);
+                }
+            }
+            // The user's bus may come up after login: retry until it is there
+            let desktop = cfg.notifications.enabled && cfg.notifications.desktop;
+            if desktop && uid != desktop_uid {
+                desktop_notifier = start_desktop_notifier(&cfg.notifications, uid);
+                desktop_uid = desktop_notifier.as_ref().and(uid);
+                desktop_seen = None;
+                toasts.close_reports = desktop_notifier.is_none();
+            }
+        }
+
+        // Desktop notifications: forward new ones, apply clicks and closes
+        if let Some(notifier) = &desktop_notifier {
+            forward_desktop_notifications(
+                notifier,
+                &tab_mgr,
+                &mut desktop_seen,
+                drm_master_held,
+                notification_muted,
+            );
+            for event in notifier.poll_events() {
+                let id = pane::PaneId(event.pane);
+                if let (Some(report), Some(pane)) = (&event.report, tab_mgr.pane_mut(id)) {
+                    if let Err(e) = pane.terminal.write_to_pty(report.as_bytes()) {
+                        log::debug!("Failed to report notification event: {}", e);
+                    }
+                }
+                if event.focus && tab_mgr.focus_pane(id) {
+                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                        pane.terminal.mark_all_dirty();
+                    }
+                    needs_redraw = true;
+                }
+            }
+        }
+
+        // Toasts: new notifications, OSC 99 close requests from applications,
+        // expiry, and the OSC 99 reports these produce
+        if toasts.collect(&tab_mgr, drm_master_held, notification_muted) {
+            needs_redraw = true;
+        }
+        for tab in tab_mgr.tabs.iter_mut() {
+            for (id, pane) in tab.panes.iter_mut() {
+                for close_id in pane.terminal.notification_closes.drain(..) {
+                    if toasts.close(*id, &close_id) {
+                        needs_redraw = true;
+                    }
+                    if let Some(notifier) = &desktop_notifier {
+                        notifier.close(id.0, &close_id);
+                    }
+                }
+            }
+        }
+        if toasts.expire(std::time::Instant::now()) {
+            needs_redraw = true;
+        }
+
+        // Pastes waiting for paste_command (may raise a paste confirmation)
+        for tab in tab_mgr.tabs.iter_mut() {
+            for pane in tab.panes.values_mut() {
+                if pane.terminal.poll_paste_command() {
+                    needs_redraw = true;
+                }
+            }
+        }
+        for (id, report) in toasts.take_reports() {
+            if let Some(pane) = tab_mgr.pane_mut(id) {
+                if let Err(e) = pane.terminal.write_to_pty(report.as_bytes()) {
+                    log::debug!("Failed to report notification event: {}", e);
+                }
+            }
+        }
+
+        // Borrow the active terminal for this iteration.
+        // The borrow is released at the end of each iteration (or via `continue 'main_loop`).
+        let term = tab_mgr.active_terminal_mut();
+
+        // Poll for session events (VT switching)
+        #[cfg(all(target_os = "linux", feature = "seatd"))]
+        if let Some(ref seat_session) = seat_session {
+            // Dispatch libseat events (drop borrow before processing events).
+            let _ = seat_session.borrow_mut().dispatch();
+
+            // Drain events first to avoid holding a RefCell borrow while handling them.
+            let mut seat_events = Vec::new();
+            loop {
+                let event = { seat_session.borrow().try_recv_event() };
+                if let Some(event) = event {
+                    seat_events.push(event);
+                } else {
+                    break;
                 }
             }
 
@@ -2219,6 +3208,7 @@ Make sure seatd/logind is running and you're on an active VT."
                         if let Err(e) = term.send_focus_event(false) {
                             log::debug!("Failed to send FocusOut event: {}", e);
                         }
+                        hooks.on_focus(false, term.logged_in_uid());
 
                         // Suspend input devices.
                         if let Some(ref mut evdev) = evdev_keyboard {
@@ -2324,6 +3314,7 @@ Make sure seatd/logind is running and you're on an active VT."
                         if let Err(e) = term.send_focus_event(true) {
                             log::debug!("Failed to send FocusIn event: {}", e);
                         }
+                        hooks.on_focus(true, term.logged_in_uid());
                     }
                 }
             }
@@ -2342,6 +3333,7 @@ Make sure seatd/logind is running and you're on an active VT."
                         if let Err(e) = term.send_focus_event(false) {
                             log::debug!("Failed to send FocusOut event: {}", e);
                         }
+                        hooks.on_focus(false, term.logged_in_uid());
 
                         // Suspend input devices before releasing VT.
                         if let Some(ref mut evdev) = evdev_keyboard {
@@ -2420,6 +3412,7 @@ Make sure seatd/logind is running and you're on an active VT."
                         if let Err(e) = term.send_focus_event(true) {
                             log::debug!("Failed to send FocusIn event: {}", e);
                         }
+                        hooks.on_focus(true, term.logged_in_uid());
                     }
                 }
             }
@@ -2432,6 +3425,41 @@ Make sure seatd/logind is running and you're on an active VT."
             break;
         }
 
+        // Remote control requests (also served while the VT is switched away)
+        if let Some(first) = remote_server.as_ref().and_then(|s| s.try_recv()) {
+            let _ = term;
+            let mut next = Some(first);
+            while let Some(pending) = next {
+                let peer_uid = pending.peer_uid;
+                let response = if !remote_peer_allowed(&tab_mgr, &pane_owners, peer_uid) {
+                    warn!("Remote control request from uid {:?} refused", peer_uid);
+                    remote::Response::error("permission denied")
+                } else {
+                    match run_remote_request(
+                        pending.request.clone(),
+                        peer_uid,
+                        &pane_owners,
+                        &mut tab_mgr,
+                        &cfg,
+                        &extra_env,
+                        available_rect,
+                        cell_w,
+                        cell_h,
+                    ) {
+                        Ok(data) => remote::Response::ok(data),
+                        Err(e) => remote::Response::error(e.to_string()),
+                    }
+                };
+                pending.reply(response);
+                next = remote_server.as_ref().and_then(|s| s.try_recv());
+            }
+            for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                pane.terminal.mark_all_dirty();
+            }
+            needs_redraw = true;
+            continue 'main_loop;
+        }
+
         // Skip most processing if we don't have DRM master (VT switched away)
         if !drm_master_held {
             // If DRM master was never acquired, check for timeout.
@@ -2462,9 +3490,26 @@ Make sure seatd/logind is running and you're on an active VT."
             needs_redraw = true;
         }
 
+        // Key table timeout
+        if active_key_table.as_ref().is_some_and(|t| t.expired(now)) {
+            active_key_table = None;
+            needs_redraw = true;
+        }
+
         // Check child process alive — auto-close dead panes
         if !term.is_alive() {
             let _ = term;
+            // The pane is gone by the next event poll: report the exit now
+            if let Some(event) = event_watcher.child_exited(tab_mgr.active_pane_id()) {
+                dispatch_pane_events(
+                    &[event],
+                    remote_server.as_ref(),
+                    &mut hooks,
+                    &event_watcher,
+                    &tab_mgr,
+                    &pane_owners,
+                );
+            }
             // Try to close the dead pane; if it was the last one, exit
             if !tab_mgr.close_active_pane(available_rect) {
                 info!("Last pane terminated, exiting");
@@ -2480,6 +3525,39 @@ Make sure seatd/logind is running and you're on an active VT."
             continue 'main_loop;
         }
 
+        // Open a command in a new pane (link handler from a click or hint, scrollback pager)
+        if let Some((command, uid)) = pending_command_pane.take() {
+            let _ = term;
+            match terminal::Terminal::with_scrollback_as_user(
+                grid_cols,
+                grid_rows,
+                cfg.terminal.scrollback_lines,
+                &cfg.terminal.term_env,
+                &extra_env,
+                uid,
+                Some(&command),
+            ) {
+                Ok(new_term) => {
+                    new_term_setup(
+                        &cfg,
+                        &mut tab_mgr,
+                        new_term,
+                        pane::Direction::Horizontal,
+                        0.5,
+                        available_rect,
+                        cell_w,
+                        cell_h,
+                    );
+                }
+                Err(e) => warn!("Failed to open pane for '{}': {}", command, e),
+            }
+            for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                pane.terminal.mark_all_dirty();
+            }
+            needs_redraw = true;
+            continue 'main_loop;
+        }
+
         // Config hot-reload (Linux only)
         #[cfg(target_os = "linux")]
         if let Some(ref watcher) = config_watcher {
@@ -2487,41 +3565,36 @@ Make sure seatd/logind is running and you're on an active VT."
                 info!("Config file change detected, reloading...");
                 let new_cfg = config::Config::load();
 
-                // Re-parse keybinds
-                kb_copy = config::ParsedKeybinds::parse(&new_cfg.keybinds.copy);
-                kb_paste = config::ParsedKeybinds::parse(&new_cfg.keybinds.paste);
-                kb_screenshot = config::ParsedKeybinds::parse(&new_cfg.keybinds.screenshot);
-                kb_search = config::ParsedKeybinds::parse(&new_cfg.keybinds.search);
-                kb_copy_mode = config::ParsedKeybinds::parse(&new_cfg.keybinds.copy_mode);
-                kb_font_increase = config::ParsedKeybinds::parse(&new_cfg.keybinds.font_increase);
-                kb_font_decrease = config::ParsedKeybinds::parse(&new_cfg.keybinds.font_decrease);
-                kb_font_reset = config::ParsedKeybinds::parse(&new_cfg.keybinds.font_reset);
-                kb_scroll_up = config::ParsedKeybinds::parse(&new_cfg.keybinds.scroll_up);
-                kb_scroll_down = config::ParsedKeybinds::parse(&new_cfg.keybinds.scroll_down);
-                kb_reset_terminal = config::ParsedKeybinds::parse(&new_cfg.keybinds.reset_terminal);
-                kb_notification_panel =
-                    config::ParsedKeybinds::parse(&new_cfg.keybinds.notification_panel);
-                kb_notification_mute =
-                    config::ParsedKeybinds::parse(&new_cfg.keybinds.notification_mute);
-                // Pane/tab keybinds
-                kb_split_right = config::ParsedKeybinds::parse(&new_cfg.keybinds.split_right);
-                kb_split_down = config::ParsedKeybinds::parse(&new_cfg.keybinds.split_down);
-                kb_close_pane = config::ParsedKeybinds::parse(&new_cfg.keybinds.close_pane);
-                kb_pane_left = config::ParsedKeybinds::parse(&new_cfg.keybinds.pane_left);
-                kb_pane_right = config::ParsedKeybinds::parse(&new_cfg.keybinds.pane_right);
-                kb_pane_up = config::ParsedKeybinds::parse(&new_cfg.keybinds.pane_up);
-                kb_pane_down = config::ParsedKeybinds::parse(&new_cfg.keybinds.pane_down);
-                kb_resize_left = config::ParsedKeybinds::parse(&new_cfg.keybinds.resize_left);
-                kb_resize_right = config::ParsedKeybinds::parse(&new_cfg.keybinds.resize_right);
-                kb_resize_up = config::ParsedKeybinds::parse(&new_cfg.keybinds.resize_up);
-                kb_resize_down = config::ParsedKeybinds::parse(&new_cfg.keybinds.resize_down);
-                kb_zoom_pane = config::ParsedKeybinds::parse(&new_cfg.keybinds.zoom_pane);
-                kb_new_tab = config::ParsedKeybinds::parse(&new_cfg.keybinds.new_tab);
-                kb_close_tab = config::ParsedKeybinds::parse(&new_cfg.keybinds.close_tab);
-                kb_next_tab = config::ParsedKeybinds::parse(&new_cfg.keybinds.next_tab);
-                kb_prev_tab = config::ParsedKeybinds::parse(&new_cfg.keybinds.prev_tab);
+                // Rebuild key binding table
+                keybinds = config::KeyBindings::from_config(&new_cfg.keybinds);
+                mouse_binds = config::MouseBindings::from_config(&new_cfg.mouse);
+                hint_patterns = terminal::hints::HintPatterns::new(
+                    &new_cfg.hints.user_patterns(),
+                    new_cfg.hints.builtin,
+                );
+                selection_rules = terminal::smart_selection::SelectionRules::new(
+                    &new_cfg.selection.word_chars,
+                    &new_cfg.selection.user_rules(),
+                    new_cfg.selection.builtin,
+                );
                 term.notifications_enabled = new_cfg.notifications.enabled;
+                hooks = hooks::Hooks::new(&new_cfg.hooks);
+                let desktop = new_cfg.notifications.enabled && new_cfg.notifications.desktop;
+                if desktop != desktop_notifier.is_some() {
+                    desktop_notifier = start_desktop_notifier(&new_cfg.notifications, session_uid);
+                    desktop_uid = desktop_notifier.as_ref().and(session_uid);
+                    // Forward only notifications received from now on
+                    desktop_seen = None;
+                    toasts.close_reports = desktop_notifier.is_none();
+                }
                 term.allow_kitty_remote = new_cfg.security.allow_kitty_remote;
+                term.copy_on_select = new_cfg.selection.copy_on_select;
+                term.copy_command = new_cfg.clipboard.copy_command.clone();
+                term.paste_command = new_cfg.clipboard.paste_command.clone();
+                term.clipboard_command_timeout =
+                    Duration::from_millis(new_cfg.clipboard.command_timeout_ms);
+                term.paste_safety = paste_safety(&new_cfg.clipboard);
+                configure_clipboard_history(&term.clipboard_history, &new_cfg.clipboard);
 
                 // Update IME disable app list
                 cfg = new_cfg;
@@ -2648,8 +3721,10 @@ Make sure seatd/logind is running and you're on an active VT."
                     }
                 }
 
-                // Update Ctrl state (for URL click detection)
+                // Update modifier state (for mouse bindings)
                 ctrl_pressed = raw.mods_ctrl;
+                shift_pressed = raw.mods_shift;
+                alt_pressed = raw.mods_alt;
 
                 if !raw.is_press {
                     // Send release events to IME if connected
@@ -2670,71 +3745,250 @@ Make sure seatd/logind is running and you're on an active VT."
                 let alt = raw.mods_alt;
                 let keysym = raw.keysym;
 
-                // Scroll up (configurable)
-                if kb_scroll_up.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    term.scroll_back(grid_rows / 2);
-                    needs_redraw = true;
-                    continue;
-                }
-                // Scroll down (configurable)
-                if kb_scroll_down.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    term.scroll_forward(grid_rows / 2);
-                    needs_redraw = true;
-                    continue;
-                }
-
-                // Copy (configurable)
-                if kb_copy.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    term.copy_selection();
-                    needs_redraw = true;
-                    continue;
-                }
-
-                // Paste (configurable)
-                if kb_paste.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term.paste_clipboard();
+                // Paste confirmation (modal): Enter pastes, Escape cancels, other keys are ignored
+                if term.pending_paste.is_some() {
+                    match keysym {
+                        xkbcommon::xkb::keysyms::KEY_Return
+                        | xkbcommon::xkb::keysyms::KEY_KP_Enter => {
+                            if let Err(e) = term.confirm_paste() {
+                                log::warn!("Paste failed: {}", e);
+                            }
+                        }
+                        xkbcommon::xkb::keysyms::KEY_Escape => term.cancel_paste(),
+                        _ => {}
+                    }
                     needs_redraw = true;
                     continue;
                 }
 
-                // Screenshot (configurable)
-                if kb_screenshot.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    // Set screenshot flag (execute after rendering)
-                    take_screenshot = true;
+                let action = if let Some(mut active) = active_key_table.take() {
+                    // Modifier presses (e.g. Shift for "|") don't leave the table
+                    if input::evdev::is_modifier_keycode(raw.keycode) {
+                        active_key_table = Some(active);
+                        continue;
+                    }
                     needs_redraw = true;
-                    continue;
-                }
+                    let table_action = keybinds
+                        .table(&active.name)
+                        .and_then(|t| t.bindings.lookup(ctrl, shift, alt, raw.keycode, keysym))
+                        .cloned();
+                    match table_action {
+                        Some(a) => {
+                            if active.sticky {
+                                active.touch();
+                                active_key_table = Some(active);
+                            }
+                            Some(a)
+                        }
+                        // Escape or an unbound key leaves the table (key is swallowed)
+                        None => continue,
+                    }
+                } else {
+                    keybinds
+                        .lookup(ctrl, shift, alt, raw.keycode, keysym)
+                        .cloned()
+                };
 
-                // Reset terminal modes (configurable)
-                if kb_reset_terminal.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    term.reset_enhanced_modes();
-                    info!("Terminal modes reset by user");
-                    needs_redraw = true;
+                // Passthrough: forward to the application, skipping all bcon handling
+                if action == Some(config::Action::Passthrough) {
+                    forward_key(term, raw, ime_client.as_ref());
                     continue;
                 }
 
-                // Notification mute toggle
-                if kb_notification_mute.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    notification_muted = !notification_muted;
-                    info!(
-                        "Notifications {}",
-                        if notification_muted {
-                            "muted"
+                // Actions that work in every mode (including overlays)
+                match action {
+                    Some(config::Action::ScrollUp) => {
+                        term.scroll_back(grid_rows / 2);
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::ScrollDown) => {
+                        term.scroll_forward(grid_rows / 2);
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::Scroll { lines }) => {
+                        if lines < 0 {
+                            term.scroll_back(lines.unsigned_abs() as usize);
                         } else {
-                            "unmuted"
+                            term.scroll_forward(lines as usize);
                         }
-                    );
-                    needs_redraw = true;
-                    continue;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::Copy) => {
+                        term.copy_selection();
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::Paste) => {
+                        let _ = term.paste_clipboard();
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::PastePrimary) => {
+                        let _ = term.paste_primary();
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::Screenshot) => {
+                        // Set screenshot flag (execute after rendering)
+                        take_screenshot = true;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::ExportScrollback { format }) => {
+                        let user_home = term.user_home_dir();
+                        match save_scrollback(
+                            &term.grid,
+                            format,
+                            &cfg.paths.screenshot_dir,
+                            user_home.as_deref(),
+                            term.logged_in_uid(),
+                            &cfg.appearance,
+                        ) {
+                            Ok(_) => {
+                                bell_flash_until = Some(
+                                    std::time::Instant::now()
+                                        + Duration::from_millis(BELL_FLASH_DURATION_MS),
+                                );
+                            }
+                            Err(e) => log::warn!("Scrollback export failed: {}", e),
+                        }
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::ShowScrollback { ansi }) => {
+                        // Never run the pager as root: bcon itself may be root for DRM access
+                        let uid = match unsafe { libc::getuid() } {
+                            0 => term.logged_in_uid(),
+                            uid => Some(uid),
+                        };
+                        match uid {
+                            Some(uid) => match scrollback_pager_command(
+                                &term.grid,
+                                ansi,
+                                &cfg.terminal.scrollback_pager,
+                                uid,
+                            ) {
+                                Ok(command) => pending_command_pane = Some((command, uid)),
+                                Err(e) => log::warn!("Scrollback dump failed: {}", e),
+                            },
+                            None => log::warn!("No logged-in user to run the scrollback pager as"),
+                        }
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::ResetTerminal) => {
+                        term.reset_enhanced_modes();
+                        info!("Terminal modes reset by user");
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::SendText { ref text }) => {
+                        let _ = term.write_to_pty(text.as_bytes());
+                        continue;
+                    }
+                    Some(config::Action::ActivateTable { ref name }) => {
+                        match keybinds.table(name) {
+                            Some(table) => {
+                                active_key_table = Some(config::ActiveKeyTable::new(table));
+                            }
+                            None => warn!("Unknown key table: {}", name),
+                        }
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::DeactivateTable) => {
+                        active_key_table = None;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    Some(config::Action::NotificationMute) => {
+                        notification_muted = !notification_muted;
+                        info!(
+                            "Notifications {}",
+                            if notification_muted {
+                                "muted"
+                            } else {
+                                "unmuted"
+                            }
+                        );
+                        needs_redraw = true;
+                        continue;
+                    }
+                    // Notification panel toggle
+                    // Modal overlay — opens even during copy mode (higher z-order)
+                    Some(config::Action::NotificationPanel) => {
+                        notification_panel_open = !notification_panel_open;
+                        notification_panel_scroll = 0;
+                        clipboard_picker_open = false;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    // Clipboard history picker toggle (modal, same z-order as the panel)
+                    Some(config::Action::ClipboardHistory) => {
+                        clipboard_picker_open = !clipboard_picker_open;
+                        clipboard_picker_selected = 0;
+                        notification_panel_open = false;
+                        needs_redraw = true;
+                        continue;
+                    }
+                    _ => {}
                 }
 
-                // Notification panel toggle (configurable)
-                // Modal overlay — opens even during copy mode (higher z-order)
-                if kb_notification_panel.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    notification_panel_open = !notification_panel_open;
-                    notification_panel_scroll = 0;
+                // Clipboard history picker key handling (modal)
+                // Recognized keys are consumed; unrecognized keys close the picker and fall through
+                if clipboard_picker_open {
+                    let count =
+                        terminal::clipboard::ClipboardHistory::lock(&term.clipboard_history).len();
+                    let consumed = match raw.keysym {
+                        xkbcommon::xkb::keysyms::KEY_Escape => {
+                            clipboard_picker_open = false;
+                            true
+                        }
+                        xkbcommon::xkb::keysyms::KEY_Down | xkbcommon::xkb::keysyms::KEY_j => {
+                            if clipboard_picker_selected + 1 < count {
+                                clipboard_picker_selected += 1;
+                            }
+                            true
+                        }
+                        xkbcommon::xkb::keysyms::KEY_Up | xkbcommon::xkb::keysyms::KEY_k => {
+                            clipboard_picker_selected = clipboard_picker_selected.saturating_sub(1);
+                            true
+                        }
+                        xkbcommon::xkb::keysyms::KEY_Return
+                        | xkbcommon::xkb::keysyms::KEY_KP_Enter => {
+                            if let Err(e) = term.paste_history_entry(clipboard_picker_selected) {
+                                log::warn!("Clipboard history paste failed: {}", e);
+                            }
+                            clipboard_picker_open = false;
+                            true
+                        }
+                        xkbcommon::xkb::keysyms::KEY_d => {
+                            terminal::clipboard::ClipboardHistory::lock(&term.clipboard_history)
+                                .remove(clipboard_picker_selected);
+                            clipboard_picker_selected =
+                                clipboard_picker_selected.min(count.saturating_sub(2));
+                            true
+                        }
+                        xkbcommon::xkb::keysyms::KEY_c => {
+                            terminal::clipboard::ClipboardHistory::lock(&term.clipboard_history)
+                                .clear();
+                            clipboard_picker_selected = 0;
+                            true
+                        }
+                        _ => {
+                            // Unrecognized key: close picker and fall through
+                            clipboard_picker_open = false;
+                            false
+                        }
+                    };
                     needs_redraw = true;
-                    continue;
+                    if consumed {
+                        continue;
+                    }
+                    // Fall through to normal key handling
                 }
 
                 // Notification panel key handling (modal)
@@ -2771,8 +4025,7 @@ Make sure seatd/logind is running and you're on an active VT."
                         }
                         xkbcommon::xkb::keysyms::KEY_c => {
                             term.notifications.clear();
-                            toast_notifications.clear();
-                            last_notification_seq = term.notification_seq;
+                            toasts.clear();
                             notification_panel_scroll = 0;
                             true
                         }
@@ -2789,226 +4042,254 @@ Make sure seatd/logind is running and you're on an active VT."
                     // Fall through to normal key handling
                 }
 
-                // === Pane management keybinds ===
+                // === Pane management actions ===
                 // These drop `term` before accessing tab_mgr, then `continue 'main_loop`
-
-                if kb_split_right.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let logged_uid = term.logged_in_uid();
-                    let _ = term;
-                    let new_term = if let Some(uid) = logged_uid {
-                        terminal::Terminal::with_scrollback_as_user(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env, uid,
-                        )
-                    } else {
-                        terminal::Terminal::with_scrollback_env(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env,
-                        )
-                    };
-                    if let Ok(new_term) = new_term {
-                        new_term_setup(&cfg, &mut tab_mgr, new_term,
-                            pane::Direction::Horizontal, available_rect, cell_w, cell_h);
-                    }
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_split_down.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let logged_uid = term.logged_in_uid();
-                    let _ = term;
-                    let new_term = if let Some(uid) = logged_uid {
-                        terminal::Terminal::with_scrollback_as_user(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env, uid,
-                        )
-                    } else {
-                        terminal::Terminal::with_scrollback_env(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env,
-                        )
-                    };
-                    if let Ok(new_term) = new_term {
-                        new_term_setup(&cfg, &mut tab_mgr, new_term,
-                            pane::Direction::Vertical, available_rect, cell_w, cell_h);
-                    }
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_close_pane.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    if !tab_mgr.close_active_pane(available_rect) {
-                        info!("Last pane closed, exiting");
-                        break 'main_loop;
-                    }
-                    tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_pane_left.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().navigate(pane::NavDirection::Left);
-                    // Mark all panes dirty so FBO is fully re-rendered (images, dividers)
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_pane_right.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().navigate(pane::NavDirection::Right);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_pane_up.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().navigate(pane::NavDirection::Up);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_pane_down.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().navigate(pane::NavDirection::Down);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_resize_left.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().resize_active(-0.05, pane::Direction::Horizontal, available_rect);
-                    tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
-                    }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_resize_right.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().resize_active(0.05, pane::Direction::Horizontal, available_rect);
-                    tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+                match action {
+                    Some(config::Action::Split { direction, ratio }) => {
+                        let logged_uid = term.logged_in_uid();
+                        let _ = term;
+                        let new_term = if let Some(uid) = logged_uid {
+                            terminal::Terminal::with_scrollback_as_user(
+                                grid_cols,
+                                grid_rows,
+                                cfg.terminal.scrollback_lines,
+                                &cfg.terminal.term_env,
+                                &extra_env,
+                                uid,
+                                None,
+                            )
+                        } else {
+                            terminal::Terminal::with_scrollback_env(
+                                grid_cols,
+                                grid_rows,
+                                cfg.terminal.scrollback_lines,
+                                &cfg.terminal.term_env,
+                                &extra_env,
+                            )
+                        };
+                        if let Ok(new_term) = new_term {
+                            new_term_setup(
+                                &cfg,
+                                &mut tab_mgr,
+                                new_term,
+                                direction.to_pane_direction(),
+                                ratio,
+                                available_rect,
+                                cell_w,
+                                cell_h,
+                            );
+                        }
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_resize_up.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().resize_active(-0.05, pane::Direction::Vertical, available_rect);
-                    tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+                    Some(config::Action::ClosePane) => {
+                        let _ = term;
+                        if !tab_mgr.close_active_pane(available_rect) {
+                            info!("Last pane closed, exiting");
+                            break 'main_loop;
+                        }
+                        tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_resize_down.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().resize_active(0.05, pane::Direction::Vertical, available_rect);
-                    tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+                    Some(config::Action::NotificationActivate) => {
+                        let _ = term;
+                        // Reports are written with the next iteration's toast update
+                        if let Some(id) = toasts.activate() {
+                            if tab_mgr.focus_pane(id) {
+                                for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                                    pane.terminal.mark_all_dirty();
+                                }
+                            }
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_zoom_pane.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.active_tab_mut().toggle_zoom();
-                    if tab_mgr.is_zoomed() {
-                        let cols = (available_rect.width / cell_w).floor() as usize;
-                        let rows = (available_rect.height / cell_h).floor() as usize;
-                        let t = tab_mgr.active_terminal_mut();
-                        t.resize(cols.max(1), rows.max(1));
-                    } else {
+                    Some(config::Action::FocusPane { direction }) => {
+                        let _ = term;
+                        tab_mgr
+                            .active_tab_mut()
+                            .navigate(direction.to_nav_direction());
+                        // Mark all panes dirty so FBO is fully re-rendered (images, dividers)
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
+                    }
+                    Some(config::Action::ResizePane { direction, amount }) => {
+                        let _ = term;
+                        let (axis, sign) = direction.resize_axis();
+                        tab_mgr
+                            .active_tab_mut()
+                            .resize_active(sign * amount, axis, available_rect);
                         tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+                    Some(config::Action::ZoomPane) => {
+                        let _ = term;
+                        tab_mgr.active_tab_mut().toggle_zoom();
+                        if tab_mgr.is_zoomed() {
+                            let cols = (available_rect.width / cell_w).floor() as usize;
+                            let rows = (available_rect.height / cell_h).floor() as usize;
+                            let t = tab_mgr.active_terminal_mut();
+                            t.resize(cols.max(1), rows.max(1));
+                        } else {
+                            tab_mgr.resize_terminals_to_rects(cell_w, cell_h);
+                        }
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_new_tab.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let logged_uid = term.logged_in_uid();
-                    let _ = term;
-                    let new_term = if let Some(uid) = logged_uid {
-                        terminal::Terminal::with_scrollback_as_user(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env, uid,
-                        )
-                    } else {
-                        terminal::Terminal::with_scrollback_env(
-                            grid_cols, grid_rows,
-                            cfg.terminal.scrollback_lines, &cfg.terminal.term_env, &extra_env,
-                        )
-                    };
-                    if let Ok(mut new_term) = new_term {
-                        new_term.set_cell_size(cell_w as u32, cell_h as u32);
-                        new_term.set_clipboard_path(&cfg.paths.clipboard_file);
-                        new_term.grid.set_ansi_palette(cfg.colors.to_palette());
-                        new_term.notifications_enabled = cfg.notifications.enabled;
-                        new_term.allow_kitty_remote = cfg.security.allow_kitty_remote;
-                        tab_mgr.new_tab(new_term, available_rect);
+                    Some(config::Action::NewTab) => {
+                        let logged_uid = term.logged_in_uid();
+                        let _ = term;
+                        let new_term = if let Some(uid) = logged_uid {
+                            terminal::Terminal::with_scrollback_as_user(
+                                grid_cols,
+                                grid_rows,
+                                cfg.terminal.scrollback_lines,
+                                &cfg.terminal.term_env,
+                                &extra_env,
+                                uid,
+                                None,
+                            )
+                        } else {
+                            terminal::Terminal::with_scrollback_env(
+                                grid_cols,
+                                grid_rows,
+                                cfg.terminal.scrollback_lines,
+                                &cfg.terminal.term_env,
+                                &extra_env,
+                            )
+                        };
+                        if let Ok(new_term) = new_term {
+                            new_tab_setup(
+                                &cfg,
+                                &mut tab_mgr,
+                                new_term,
+                                available_rect,
+                                cell_w,
+                                cell_h,
+                            );
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
-                }
-                if kb_close_tab.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    if !tab_mgr.close_active_tab() {
-                        if !tab_mgr.close_active_pane(available_rect) {
-                            info!("Last tab closed, exiting");
-                            break 'main_loop;
+                    Some(config::Action::CloseTab) => {
+                        let _ = term;
+                        if !tab_mgr.close_active_tab() {
+                            if !tab_mgr.close_active_pane(available_rect) {
+                                info!("Last tab closed, exiting");
+                                break 'main_loop;
+                            }
                         }
+                        // Mark all panes dirty so FBO is fully re-rendered with new active tab
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    // Mark all panes dirty so FBO is fully re-rendered with new active tab
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+                    Some(config::Action::NextTab)
+                    | Some(config::Action::PrevTab)
+                    | Some(config::Action::GotoTab { .. }) => {
+                        let _ = term;
+                        match action {
+                            Some(config::Action::NextTab) => tab_mgr.next_tab(),
+                            Some(config::Action::PrevTab) => tab_mgr.prev_tab(),
+                            Some(config::Action::GotoTab { n }) => {
+                                tab_mgr.goto_tab(n.saturating_sub(1));
+                            }
+                            _ => {}
+                        }
+                        // Mark all panes dirty so FBO is fully re-rendered
+                        for pane in tab_mgr.active_tab_mut().panes.values_mut() {
+                            pane.terminal.mark_all_dirty();
+                        }
+                        needs_redraw = true;
+                        continue 'main_loop;
                     }
-                    needs_redraw = true;
-                    continue 'main_loop;
+                    _ => {}
                 }
-                if kb_next_tab.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.next_tab();
-                    // Mark all panes dirty so FBO is fully re-rendered
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+
+                // Hints mode start (configurable)
+                if let Some(config::Action::Hints { mode }) = action {
+                    if term.copy_mode.is_none() && !search_mode {
+                        if term.start_hints(&hint_patterns, &cfg.hints.alphabet) {
+                            hint_mode = mode;
+                        } else {
+                            info!("Hints: no matches on screen");
+                        }
                     }
                     needs_redraw = true;
-                    continue 'main_loop;
+                    continue;
                 }
-                if kb_prev_tab.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    let _ = term;
-                    tab_mgr.prev_tab();
-                    for pane in tab_mgr.active_tab_mut().panes.values_mut() {
-                        pane.terminal.mark_all_dirty();
+
+                // Key input processing in hints mode
+                if let Some(ref mut hints) = term.hints {
+                    let input = match raw.keysym {
+                        xkbcommon::xkb::keysyms::KEY_Escape => None,
+                        xkbcommon::xkb::keysyms::KEY_BackSpace => {
+                            hints.pop_key();
+                            Some(terminal::hints::HintInput::Pending)
+                        }
+                        _ => match raw.utf8.chars().next() {
+                            Some(ch) if ch.is_ascii_alphanumeric() => Some(hints.push_key(ch)),
+                            _ => Some(terminal::hints::HintInput::NoMatch),
+                        },
+                    };
+                    match input {
+                        // Escape: Cancel
+                        None => term.end_hints(),
+                        Some(terminal::hints::HintInput::Selected(m)) => {
+                            term.end_hints();
+                            match hint_mode {
+                                config::HintMode::Copy => {
+                                    term.set_clipboard(
+                                        &m.text,
+                                        terminal::clipboard::ClipSource::Hint,
+                                    );
+                                    info!("Hint copied: {}", m.text);
+                                }
+                                config::HintMode::Paste => {
+                                    let _ = term.paste_text(&m.text);
+                                }
+                                config::HintMode::Open => {
+                                    match open_link(&cfg.links, term, &m.text) {
+                                        LinkOpen::Pane(command, uid) => {
+                                            pending_command_pane = Some((command, uid));
+                                        }
+                                        LinkOpen::Copied | LinkOpen::Spawned => {
+                                            bell_flash_until = Some(
+                                                std::time::Instant::now()
+                                                    + Duration::from_millis(BELL_FLASH_DURATION_MS),
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Some(_) => {}
                     }
                     needs_redraw = true;
-                    continue 'main_loop;
+                    continue;
                 }
 
                 // Copy mode start (configurable)
-                if kb_copy_mode.matches(ctrl, shift, alt, raw.keycode, keysym) {
+                if action == Some(config::Action::CopyMode) {
                     if term.copy_mode.is_none() {
                         term.enter_copy_mode();
                     }
@@ -3016,61 +4297,34 @@ Make sure seatd/logind is running and you're on an active VT."
                     continue;
                 }
 
-                // Key input processing in copy mode
-                if term.copy_mode.is_some() {
+                // Key input processing in copy mode (the search bar takes keys while open)
+                if term.copy_mode.is_some() && !search_mode {
                     match raw.keysym {
-                        // Escape: Exit copy mode
+                        // Escape: Cancel a pending key, or exit copy mode
                         xkbcommon::xkb::keysyms::KEY_Escape => {
-                            term.exit_copy_mode();
-                        }
-                        // h / Left arrow: Move left
-                        xkbcommon::xkb::keysyms::KEY_h | xkbcommon::xkb::keysyms::KEY_Left => {
-                            term.copy_mode_move(0, -1);
-                        }
-                        // j / Down arrow: Move down
-                        xkbcommon::xkb::keysyms::KEY_j | xkbcommon::xkb::keysyms::KEY_Down => {
-                            term.copy_mode_move(1, 0);
-                        }
-                        // k / Up arrow: Move up
-                        xkbcommon::xkb::keysyms::KEY_k | xkbcommon::xkb::keysyms::KEY_Up => {
-                            term.copy_mode_move(-1, 0);
-                        }
-                        // l / Right arrow: Move right
-                        xkbcommon::xkb::keysyms::KEY_l | xkbcommon::xkb::keysyms::KEY_Right => {
-                            term.copy_mode_move(0, 1);
-                        }
-                        // v: Toggle selection
-                        xkbcommon::xkb::keysyms::KEY_v => {
-                            term.copy_mode_toggle_selection();
-                        }
-                        // y: Yank (copy and exit)
-                        xkbcommon::xkb::keysyms::KEY_y => {
-                            term.copy_mode_yank();
-                        }
-                        // g: Go to top
-                        xkbcommon::xkb::keysyms::KEY_g => {
-                            term.copy_mode_goto_top();
-                        }
-                        // G: Go to bottom
-                        xkbcommon::xkb::keysyms::KEY_G => {
-                            term.copy_mode_goto_bottom();
-                        }
-                        // 0 / Home: Line start
-                        xkbcommon::xkb::keysyms::KEY_0 | xkbcommon::xkb::keysyms::KEY_Home => {
-                            term.copy_mode_goto_line_start();
-                        }
-                        // $ / End: Line end
-                        xkbcommon::xkb::keysyms::KEY_dollar | xkbcommon::xkb::keysyms::KEY_End => {
-                            term.copy_mode_goto_line_end();
-                        }
-                        // w: Word forward
-                        xkbcommon::xkb::keysyms::KEY_w => {
-                            term.copy_mode_word_forward();
-                        }
-                        // b: Word backward
-                        xkbcommon::xkb::keysyms::KEY_b => {
-                            term.copy_mode_word_backward();
+                            let pending = term
+                                .copy_mode
+                                .as_mut()
+                                .map(|cm| {
+                                    let had = cm.pending.is_some()
+                                        || cm.count > 0
+                                        || cm.register.is_some();
+                                    cm.pending = None;
+                                    cm.count = 0;
+                                    cm.register = None;
+                                    had
+                                })
+                                .unwrap_or(false);
+                            if !pending {
+                                term.exit_copy_mode();
+                            }
                         }
+                        xkbcommon::xkb::keysyms::KEY_Left => term.copy_mode_move(0, -1),
+                        xkbcommon::xkb::keysyms::KEY_Down => term.copy_mode_move(1, 0),
+                        xkbcommon::xkb::keysyms::KEY_Up => term.copy_mode_move(-1, 0),
+                        xkbcommon::xkb::keysyms::KEY_Right => term.copy_mode_move(0, 1),
+                        xkbcommon::xkb::keysyms::KEY_Home => term.copy_mode_goto_line_start(),
+                        xkbcommon::xkb::keysyms::KEY_End => term.copy_mode_goto_line_end(),
                         // Ctrl+u: Half page up
                         xkbcommon::xkb::keysyms::KEY_u if ctrl => {
                             term.copy_mode_page_up();
@@ -3079,10 +4333,65 @@ Make sure seatd/logind is running and you're on an active VT."
                         xkbcommon::xkb::keysyms::KEY_d if ctrl => {
                             term.copy_mode_page_down();
                         }
-                        // /: Enter search mode
-                        xkbcommon::xkb::keysyms::KEY_slash => {
-                            search_mode = true;
-                            term.start_search();
+                        // Ctrl+v: Block selection
+                        xkbcommon::xkb::keysyms::KEY_v if ctrl => {
+                            term.copy_mode_toggle_selection(terminal::copy_mode::VisualMode::Block);
+                        }
+                        // Vim keys (counts, motions, marks, registers...)
+                        _ if !ctrl && !alt => {
+                            let Some(ch) = raw.utf8.chars().next() else {
+                                continue;
+                            };
+                            match term.copy_mode_key(ch) {
+                                terminal::copy_mode::CopyModeAction::None => {}
+                                terminal::copy_mode::CopyModeAction::Exit => {
+                                    term.exit_copy_mode();
+                                }
+                                terminal::copy_mode::CopyModeAction::Search => {
+                                    search_mode = true;
+                                    term.start_search(search_options);
+                                }
+                                terminal::copy_mode::CopyModeAction::Yank { register, text } => {
+                                    if !text.is_empty() {
+                                        match register {
+                                            // "A appends to register a
+                                            Some(r) if r.is_ascii_uppercase() => {
+                                                copy_registers
+                                                    .entry(r.to_ascii_lowercase())
+                                                    .or_default()
+                                                    .push_str(&text);
+                                            }
+                                            Some(r) => {
+                                                copy_registers.insert(r, text);
+                                            }
+                                            None => {
+                                                term.set_clipboard(
+                                                    &text,
+                                                    terminal::clipboard::ClipSource::CopyMode,
+                                                );
+                                                info!(
+                                                    "Clipboard: {} characters copied",
+                                                    text.len()
+                                                );
+                                            }
+                                        }
+                                    }
+                                }
+                                terminal::copy_mode::CopyModeAction::Paste(register) => {
+                                    match register {
+                                        Some(r) => {
+                                            if let Some(text) =
+                                                copy_registers.get(&r.to_ascii_lowercase())
+                                            {
+                                                let _ = term.paste_text(text);
+                                            }
+                                        }
+                                        None => {
+                                            let _ = term.paste_clipboard();
+                                        }
+                                    }
+                                }
+                            }
                         }
                         _ => {}
                     }
@@ -3091,10 +4400,10 @@ Make sure seatd/logind is running and you're on an active VT."
                 }
 
                 // Search mode start (configurable)
-                if kb_search.matches(ctrl, shift, alt, raw.keycode, keysym) {
+                if action == Some(config::Action::Search) {
                     if !search_mode {
                         search_mode = true;
-                        term.start_search();
+                        term.start_search(search_options);
                         info!("Search mode started");
                     }
                     needs_redraw = true;
@@ -3113,41 +4422,58 @@ Make sure seatd/logind is running and you're on an active VT."
                                 term.end_search();
                                 info!("Search mode ended");
                             }
-                            // Enter: Execute search
+                            // Enter: Confirm search (results are already incremental)
                             xkbcommon::xkb::keysyms::KEY_Return
                             | xkbcommon::xkb::keysyms::KEY_KP_Enter => {
-                                term.execute_search();
+                                if !search.executed {
+                                    term.execute_search();
+                                }
+                                if let Some(ref mut s) = term.search {
+                                    s.confirmed = true;
+                                }
                                 term.scroll_to_current_match();
-                                // If in copy mode, end search and move cursor to match position
+                                // If in copy mode, close the search bar and move the cursor
+                                // to the match; results stay for n / N
                                 if term.copy_mode.is_some() {
-                                    if let Some(ref s) = term.search {
-                                        if !s.matches.is_empty() {
-                                            let (abs_row, start_col, _) =
-                                                s.matches[s.current_match];
-                                            let scrollback_len = term.grid.scrollback_len();
-                                            // Convert to display coordinates
-                                            if abs_row >= scrollback_len {
-                                                let display_row = abs_row - scrollback_len;
-                                                if let Some(ref mut cm) = term.copy_mode {
-                                                    cm.cursor_row = display_row;
-                                                    cm.cursor_col = start_col;
-                                                }
-                                            }
-                                        }
+                                    if let Some(ref mut s) = term.search {
+                                        s.cancel_jump();
+                                    }
+                                    if let Some((abs_row, start_col)) =
+                                        term.current_search_match_pos()
+                                    {
+                                        term.copy_mode_set_cursor(abs_row, start_col);
                                     }
                                     search_mode = false;
-                                    term.end_search();
                                 }
                             }
-                            // Backspace: Delete one character
+                            // Backspace: Delete one character and search again
                             xkbcommon::xkb::keysyms::KEY_BackSpace => {
                                 search.query.pop();
-                                // Clear matches when query changes
-                                search.matches.clear();
+                                search.confirmed = false;
+                                term.execute_search();
                             }
-                            // n / N: Move between matches (only after search executed)
+                            // Alt+R: Toggle regex / Alt+C: Cycle case mode
+                            xkbcommon::xkb::keysyms::KEY_r
+                            | xkbcommon::xkb::keysyms::KEY_R
+                            | xkbcommon::xkb::keysyms::KEY_c
+                            | xkbcommon::xkb::keysyms::KEY_C
+                                if alt =>
+                            {
+                                if matches!(
+                                    raw.keysym,
+                                    xkbcommon::xkb::keysyms::KEY_r | xkbcommon::xkb::keysyms::KEY_R
+                                ) {
+                                    search.options.regex = !search.options.regex;
+                                } else {
+                                    search.options.case = search.options.case.next();
+                                }
+                                search_options = search.options;
+                                // Re-run the search with the new options
+                                term.execute_search();
+                            }
+                            // n / N: Move between matches (only after Enter)
                             xkbcommon::xkb::keysyms::KEY_n | xkbcommon::xkb::keysyms::KEY_N
-                                if has_matches =>
+                                if has_matches && search.confirmed =>
                             {
                                 if shift {
                                     search.prev_match();
@@ -3158,10 +4484,11 @@ Make sure seatd/logind is running and you're on an active VT."
                             }
                             // Normal character: Add to query
                             _ => {
-                                if !raw.utf8.is_empty() && !ctrl {
+                                if !raw.utf8.is_empty() && !ctrl && !alt {
                                     search.query.push_str(&raw.utf8);
-                                    // Clear matches when query changes
-                                    search.matches.clear();
+                                    search.confirmed = false;
+                                    // Incremental: search as the query changes
+                                    term.execute_search();
                                 }
                             }
                         }
@@ -3170,16 +4497,12 @@ Make sure seatd/logind is running and you're on an active VT."
                     continue;
                 }
 
-                // Font size increase (configurable)
                 // Font size change (increase / decrease / reset)
-                let font_size_change = if kb_font_increase.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    Some(font_size_delta + 2)
-                } else if kb_font_decrease.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    Some(font_size_delta - 2)
-                } else if kb_font_reset.matches(ctrl, shift, alt, raw.keycode, keysym) {
-                    Some(0)
-                } else {
-                    None
+                let font_size_change = match action {
+                    Some(config::Action::FontIncrease) => Some(font_size_delta + 2),
+                    Some(config::Action::FontDecrease) => Some(font_size_delta - 2),
+                    Some(config::Action::FontReset) => Some(0),
+                    _ => None,
                 };
                 if let Some(new_delta) = font_size_change {
                     font_size_delta = new_delta;
@@ -3208,6 +4531,7 @@ Make sure seatd/logind is running and you're on an active VT."
                 // Shift+Arrow: Text selection
                 if shift && matches!(raw.keycode, 105 | 106 | 103 | 108 | 102 | 107) {
                     handle_selection_key(term, raw.keycode, grid_cols);
+                    term.selection_to_primary();
                     needs_redraw = true;
                     continue;
                 }
@@ -3219,38 +4543,72 @@ Make sure seatd/logind is running and you're on an active VT."
                 }
 
                 // Process keys: send to fcitx5 if connected, otherwise directly to PTY
-                if let Some(ref ime) = ime_client {
-                    ime.send_key(input::ime::ImeKeyEvent {
-                        keysym: raw.keysym,
-                        keycode: raw.keycode,
-                        state: raw.xkb_state,
-                        is_release: false,
-                    });
-                } else {
-                    // Send directly to PTY when IME client is unavailable
-                    let sym = xkbcommon::xkb::Keysym::new(raw.keysym);
-                    let kb_config = input::KeyboardConfig {
-                        application_cursor_keys: term.grid.modes.application_cursor_keys,
-                        modify_other_keys: term.grid.keyboard.modify_other_keys,
-                        kitty_flags: term.grid.keyboard.kitty_flags,
-                        key_action: raw.action,
-                    };
-                    let bytes = input::keysym_to_bytes_with_mods(
-                        sym,
-                        &raw.utf8,
-                        raw.mods_ctrl,
-                        raw.mods_alt,
-                        raw.mods_shift,
-                        &kb_config,
-                    );
-                    if !bytes.is_empty() {
-                        let _ = term.write_to_pty(&bytes);
-                    }
-                }
+                forward_key(term, raw, ime_client.as_ref());
             }
 
             // Mouse event processing
             for mouse in &mouse_events {
+                // Clipboard history picker mouse handling (modal)
+                if clipboard_picker_open {
+                    let panel_w = (screen_w as f32 * 0.25).clamp(250.0, 450.0);
+                    let panel_x = screen_w as f32 - panel_w;
+                    let count =
+                        terminal::clipboard::ClipboardHistory::lock(&term.clipboard_history).len();
+                    match mouse {
+                        input::MouseEvent::ButtonPress { button, x, y, .. } => {
+                            if *button == input::BTN_LEFT {
+                                if (*x as f32) < panel_x {
+                                    // Click outside picker: close it
+                                    clipboard_picker_open = false;
+                                } else if let Some(idx) = clipboard_picker_hit(
+                                    *y as f32,
+                                    screen_h as f32,
+                                    cell_h,
+                                    clipboard_picker_selected,
+                                    count,
+                                ) {
+                                    // Click on an entry: paste it
+                                    if let Err(e) = term.paste_history_entry(idx) {
+                                        log::warn!("Clipboard history paste failed: {}", e);
+                                    }
+                                    clipboard_picker_open = false;
+                                }
+                                needs_redraw = true;
+                            }
+                            continue;
+                        }
+                        input::MouseEvent::Scroll { delta, x, .. } => {
+                            if (*x as f32) >= panel_x {
+                                // Scroll inside picker: move the highlight
+                                if *delta < 0.0 {
+                                    clipboard_picker_selected =
+                                        clipboard_picker_selected.saturating_sub(1);
+                                } else if clipboard_picker_selected + 1 < count {
+                                    clipboard_picker_selected += 1;
+                                }
+                            } else {
+                                clipboard_picker_open = false;
+                            }
+                            needs_redraw = true;
+                            continue;
+                        }
+                        input::MouseEvent::Move { x, y, .. } => {
+                            mouse_x = *x;
+                            mouse_y = *y;
+                            if let Some(ref hc) = hw_cursor {
+                                hc.move_to(mouse_x, mouse_y);
+                            } else {
+                                needs_redraw = true;
+                            }
+                            continue;
+                        }
+                        // Release events are consumed while the picker is open
+                        _ => {
+                            continue;
+                        }
+                    }
+                }
+
                 // Notification panel mouse handling (modal)
                 if notification_panel_open {
                     let panel_w = (screen_w as f32 * 0.25).max(250.0).min(450.0);
@@ -3359,83 +4717,76 @@ Make sure seatd/logind is running and you're on an active VT."
                         let col = ((*x - mouse_offset_x).max(0.0) / cell_w as f64) as usize;
                         let row = ((*y - mouse_offset_y).max(0.0) / cell_h as f64) as usize;
 
-                        // Ctrl+Left click: Copy URL to clipboard
-                        if ctrl_pressed && *button == input::BTN_LEFT {
-                            let clamped_row = row.min(grid_rows.saturating_sub(1));
-                            let clamped_col = col.min(grid_cols.saturating_sub(1));
-                            if let Some(url) = term.detect_url_at(clamped_row, clamped_col) {
-                                term.copy_url_to_clipboard(&url);
-                                // Visual feedback with bell flash
-                                bell_flash_until = Some(
-                                    std::time::Instant::now()
-                                        + Duration::from_millis(BELL_FLASH_DURATION_MS),
-                                );
-                            }
-                            needs_redraw = true;
-                            continue;
+                        let clamped_row = row.min(grid_rows.saturating_sub(1));
+                        let clamped_col = col.min(grid_cols.saturating_sub(1));
+                        let grabbed = term.mouse_mode_enabled();
+
+                        // Double/triple click detection
+                        let now = std::time::Instant::now();
+                        let elapsed = now.duration_since(last_click_time).as_millis();
+                        let same_pos = col == last_click_col && row == last_click_row;
+                        if elapsed < DOUBLE_CLICK_THRESHOLD_MS
+                            && same_pos
+                            && *button == last_click_button
+                        {
+                            click_count = (click_count + 1).min(3);
+                        } else {
+                            click_count = 1;
                         }
+                        last_click_time = now;
+                        last_click_button = *button;
+                        last_click_col = col;
+                        last_click_row = row;
+
+                        let binding = config::MouseButton::from_code(*button).and_then(|b| {
+                            mouse_binds.lookup(
+                                b,
+                                click_count,
+                                ctrl_pressed,
+                                shift_pressed,
+                                alt_pressed,
+                                grabbed,
+                            )
+                        });
 
-                        // Send to PTY if mouse mode is enabled
-                        if term.mouse_mode_enabled() {
+                        if let Some(action) = binding {
+                            match run_mouse_action(
+                                term,
+                                action,
+                                clamped_row,
+                                clamped_col,
+                                grid_rows,
+                                &cfg.links,
+                                &selection_rules,
+                            ) {
+                                MouseActionResult::Drag => mouse_selecting = true,
+                                MouseActionResult::OpenPane(command, uid) => {
+                                    pending_command_pane = Some((command, uid));
+                                    mouse_selecting = false;
+                                }
+                                MouseActionResult::Flash => {
+                                    // Visual feedback with bell flash
+                                    bell_flash_until = Some(
+                                        std::time::Instant::now()
+                                            + Duration::from_millis(BELL_FLASH_DURATION_MS),
+                                    );
+                                    mouse_selecting = false;
+                                }
+                                MouseActionResult::Done => mouse_selecting = false,
+                            }
+                        } else if grabbed {
+                            // Unbound click: send to PTY (application grabbed the mouse)
                             let btn = match *button {
                                 input::BTN_LEFT => 0,
                                 input::BTN_MIDDLE => 1,
                                 input::BTN_RIGHT => 2,
                                 _ => 0,
                             };
-                            let pixel_coords = sgr_pixel_coords(term, *x, *y, mouse_offset_x, mouse_offset_y);
-                            let _ = term.send_mouse_press(
-                                btn,
-                                col.min(grid_cols.saturating_sub(1)),
-                                row.min(grid_rows.saturating_sub(1)),
-                                pixel_coords,
-                            );
+                            let pixel_coords =
+                                sgr_pixel_coords(term, *x, *y, mouse_offset_x, mouse_offset_y);
+                            let _ =
+                                term.send_mouse_press(btn, clamped_col, clamped_row, pixel_coords);
                             mouse_button_held = Some(btn);
-                        } else if *button == input::BTN_LEFT {
-                            let now = std::time::Instant::now();
-                            let elapsed = now.duration_since(last_click_time).as_millis();
-                            let same_pos = col == last_click_col && row == last_click_row;
-
-                            // Double/triple click detection
-                            if elapsed < DOUBLE_CLICK_THRESHOLD_MS && same_pos {
-                                click_count = (click_count + 1).min(3);
-                            } else {
-                                click_count = 1;
-                            }
-                            last_click_time = now;
-                            last_click_col = col;
-                            last_click_row = row;
-
-                            let clamped_row = row.min(grid_rows.saturating_sub(1));
-                            let clamped_col = col.min(grid_cols.saturating_sub(1));
-
-                            match click_count {
-                                2 => {
-                                    // Double click: Word selection
-                                    term.select_word(clamped_row, clamped_col);
-                                    mouse_selecting = false;
-                                }
-                                3 => {
-                                    // Triple click: Line selection
-                                    term.select_line(clamped_row);
-                                    mouse_selecting = false;
-                                }
-                                _ => {
-                                    // Single click: Start normal selection
-                                    term.selection = Some(terminal::Selection {
-                                        anchor_row: clamped_row,
-                                        anchor_col: clamped_col,
-                                        end_row: clamped_row,
-                                        end_col: clamped_col,
-                                    });
-                                    mouse_selecting = true;
-                                }
-                            }
-                        }
-
-                        if *button == input::BTN_MIDDLE && !term.mouse_mode_enabled() {
-                            // Middle button: Paste (only when mouse mode is disabled)
-                            let _ = term.paste_clipboard();
                         }
                         needs_redraw = true;
                     }
@@ -3444,8 +4795,38 @@ Make sure seatd/logind is running and you're on an active VT."
                         let col = ((*x - mouse_offset_x).max(0.0) / cell_w as f64) as usize;
                         let row = ((*y - mouse_offset_y).max(0.0) / cell_h as f64) as usize;
 
-                        // Send move event if mouse mode is enabled
-                        if term.mouse_mode_enabled() {
+                        // Hover highlight: track the OSC 8 link under the pointer
+                        if cfg.links.hover {
+                            let in_pane = *x >= mouse_offset_x
+                                && *y >= mouse_offset_y
+                                && col < grid_cols
+                                && row < grid_rows;
+                            let link = if in_pane {
+                                term.display_cell(row, col).hyperlink.clone()
+                            } else {
+                                None
+                            };
+                            let changed = match (&hover_link, &link) {
+                                (Some(a), Some(b)) => !std::sync::Arc::ptr_eq(a, b),
+                                (None, None) => false,
+                                _ => true,
+                            };
+                            if changed {
+                                hover_link = link;
+                                needs_redraw = true;
+                            }
+                        }
+
+                        if mouse_selecting {
+                            // Dragging: Update selection range
+                            let line = term.display_line(row.min(grid_rows.saturating_sub(1)));
+                            if let Some(ref mut sel) = term.selection {
+                                sel.end_row = line;
+                                sel.end_col = col.min(grid_cols.saturating_sub(1));
+                            }
+                            needs_redraw = true;
+                        } else if term.mouse_mode_enabled() {
+                            // Send move event if mouse mode is enabled
                             let pixel_coords = sgr_pixel_coords(term, *x, *y, mouse_offset_x, mouse_offset_y);
                             let _ = term.send_mouse_move(
                                 col.min(grid_cols.saturating_sub(1)),
@@ -3453,13 +4834,6 @@ Make sure seatd/logind is running and you're on an active VT."
                                 mouse_button_held,
                                 pixel_coords,
                             );
-                        } else if mouse_selecting {
-                            // Dragging: Update selection range
-                            if let Some(ref mut sel) = term.selection {
-                                sel.end_row = row.min(grid_rows.saturating_sub(1));
-                                sel.end_col = col.min(grid_cols.saturating_sub(1));
-                            }
-                            needs_redraw = true;
                         }
                         mouse_x = *x;
                         mouse_y = *y;
@@ -3475,8 +4849,20 @@ Make sure seatd/logind is running and you're on an active VT."
                         let col = ((*x - mouse_offset_x).max(0.0) / cell_w as f64) as usize;
                         let row = ((*y - mouse_offset_y).max(0.0) / cell_h as f64) as usize;
 
-                        // Send to PTY if mouse mode is enabled
-                        if term.mouse_mode_enabled() {
+                        if mouse_selecting {
+                            // Drag finished: Confirm selection into the primary selection
+                            mouse_selecting = false;
+                            if let Some(sel) = &term.selection {
+                                if sel.anchor_row == sel.end_row && sel.anchor_col == sel.end_col {
+                                    // Single click (no drag): clear selection
+                                    term.selection = None;
+                                } else {
+                                    term.selection_to_primary();
+                                }
+                            }
+                        } else if mouse_button_held.is_some() && term.mouse_mode_enabled() {
+                            // Send to PTY if the press was (a click that ran a
+                            // bound action sends no release)
                             let btn = match *button {
                                 input::BTN_LEFT => 0,
                                 input::BTN_MIDDLE => 1,
@@ -3490,17 +4876,6 @@ Make sure seatd/logind is running and you're on an active VT."
                                 row.min(grid_rows.saturating_sub(1)),
                                 pixel_coords,
                             );
-                        } else if *button == input::BTN_LEFT {
-                            // Left button release: Confirm selection + auto copy
-                            mouse_selecting = false;
-                            if let Some(sel) = &term.selection {
-                                if sel.anchor_row == sel.end_row && sel.anchor_col == sel.end_col {
-                                    // Single click (no drag): clear selection
-                                    term.selection = None;
-                                } else {
-                                    term.copy_selection();
-                                }
-                            }
                         }
                         // Always reset button state
                         mouse_button_held = None;
@@ -3510,11 +4885,48 @@ Make sure seatd/logind is running and you're on an active VT."
                         let col = ((*x - mouse_offset_x).max(0.0) / cell_w as f64) as usize;
                         let row = ((*y - mouse_offset_y).max(0.0) / cell_h as f64) as usize;
 
-                        // Send wheel to PTY if any mouse mode is active
-                        // (xterm sends wheel events even in X10 mode)
-                        let use_mouse_wheel = term.mouse_mode_enabled();
+                        let grabbed = term.mouse_mode_enabled();
+                        let wheel = if *delta < 0.0 {
+                            config::MouseButton::WheelUp
+                        } else {
+                            config::MouseButton::WheelDown
+                        };
+                        let binding = mouse_binds.lookup(
+                            wheel,
+                            1,
+                            ctrl_pressed,
+                            shift_pressed,
+                            alt_pressed,
+                            grabbed,
+                        );
 
-                        if use_mouse_wheel {
+                        if let Some(action) = binding {
+                            // Scroll accumulation (negative=up/history, positive=down/live)
+                            if scroll_accum * delta < 0.0 {
+                                scroll_accum = 0.0;
+                            }
+                            scroll_accum += delta;
+                            // Run the action once per accumulated line
+                            let clamped_row = row.min(grid_rows.saturating_sub(1));
+                            let clamped_col = col.min(grid_cols.saturating_sub(1));
+                            while scroll_accum.abs() >= 1.0 {
+                                if let MouseActionResult::OpenPane(command, uid) = run_mouse_action(
+                                    term,
+                                    action,
+                                    clamped_row,
+                                    clamped_col,
+                                    grid_rows,
+                                    &cfg.links,
+                                    &selection_rules,
+                                ) {
+                                    pending_command_pane = Some((command, uid));
+                                }
+                                scroll_accum -= scroll_accum.signum();
+                                needs_redraw = true;
+                            }
+                        } else if grabbed {
+                            // Send wheel to PTY if any mouse mode is active
+                            // (xterm sends wheel events even in X10 mode)
                             let d = if *delta < 0.0 { -1i8 } else { 1i8 };
                             let pixel_coords = sgr_pixel_coords(term, *x, *y, mouse_offset_x, mouse_offset_y);
                             let _ = term.send_mouse_wheel(
@@ -3523,20 +4935,6 @@ Make sure seatd/logind is running and you're on an active VT."
                                 row.min(grid_rows.saturating_sub(1)),
                                 pixel_coords,
                             );
-                        } else {
-                            // Scroll accumulation (negative=up/history, positive=down/live)
-                            scroll_accum += delta;
-                            // Execute scroll when accumulated to 1 line
-                            while scroll_accum >= 1.0 {
-                                term.scroll_forward(1);
-                                scroll_accum -= 1.0;
-                                needs_redraw = true;
-                            }
-                            while scroll_accum <= -1.0 {
-                                term.scroll_back(1);
-                                scroll_accum += 1.0;
-                                needs_redraw = true;
-                            }
                         }
                     }
                     input::MouseEvent::Gesture(action) => {
@@ -3725,7 +5123,9 @@ Make sure seatd/logind is running and you're on an active VT."
         // Update tab title from active pane's window title (OSC 0/2)
         let new_title = tab_mgr.active_tab().active_pane().terminal.grid.window_title.clone();
         if let Some(title) = new_title {
-            tab_mgr.active_tab_mut().title = title;
+            if !tab_mgr.active_tab().title_locked {
+                tab_mgr.active_tab_mut().title = title;
+            }
         }
         let term = tab_mgr.active_terminal_mut();
 
@@ -3747,10 +5147,11 @@ Make sure seatd/logind is running and you're on an active VT."
                 }
             }
 
-            // Return to live position on new output
-            term.scroll_to_bottom();
-            // Clear selection on new output
-            term.selection = None;
+            // Return to live position on new output, unless text is being
+            // selected (the selection and copy mode follow their lines)
+            if term.selection.is_none() && term.copy_mode.is_none() {
+                term.scroll_to_bottom();
+            }
 
             // Start flash if bell notification
             if term.grid.bell_triggered {
@@ -3759,46 +5160,16 @@ Make sure seatd/logind is running and you're on an active VT."
                     Some(std::time::Instant::now() + Duration::from_millis(BELL_FLASH_DURATION_MS));
             }
 
-            // Detect new notifications and create toast entries (skip when muted)
-            let current_seq = term.notification_seq;
-            if current_seq > last_notification_seq && !notification_muted {
-                let new_count = (current_seq - last_notification_seq) as usize;
-                let now = std::time::Instant::now();
-                // New notifications are at the tail of the deque
-                let len = term.notifications.len();
-                let start = len.saturating_sub(new_count);
-                for i in start..len {
-                    if let Some(notif) = term.notifications.get(i) {
-                        toast_notifications.push(ToastEntry {
-                            title: notif.title.clone(),
-                            body: notif.body.clone(),
-                            urgency: notif.urgency,
-                            show_until: now + Duration::from_secs(5),
-                        });
-                    }
-                }
-                // Cap toast notifications to prevent unbounded growth
-                const MAX_TOAST_NOTIFICATIONS: usize = 50;
-                while toast_notifications.len() > MAX_TOAST_NOTIFICATIONS {
-                    toast_notifications.remove(0);
-                }
-                needs_redraw = true;
-            }
-            last_notification_seq = current_seq;
-        }
-
-        // Expire old toast notifications
-        {
-            let now = std::time::Instant::now();
-            let had_toasts = !toast_notifications.is_empty();
-            toast_notifications.retain(|t| now < t.show_until);
-            if had_toasts && toast_notifications.is_empty() {
-                needs_redraw = true; // Redraw to clear expired toasts
-            }
         }
 
-        // Continue redraw if toasts or progress bar are visible
-        if !toast_notifications.is_empty() || term.active_progress.is_some() {
+        // Stream scrollback search results and follow new output
+        // (copy mode keeps the results of a closed search bar for n / N)
+        if term.poll_search() {
+            needs_redraw = true;
+        }
+
+        // Continue redraw if the progress bar is visible
+        if term.active_progress.is_some() {
             needs_redraw = true;
         }
 
@@ -3951,6 +5322,8 @@ Make sure seatd/logind is running and you're on an active VT."
                 || !preedit.is_empty()  // IME preedit changes without dirty tracking
                 || candidate_state.is_some() // IME candidate window
                 || notification_panel_open
+                || clipboard_picker_open
+                || term.pending_paste.is_some()
         };
 
         // Force full clear when overlays just disappeared (FBO still has old overlay content)
@@ -4099,7 +5472,7 @@ Make sure seatd/logind is running and you're on an active VT."
             let row_selection = term
                 .selection
                 .as_ref()
-                .and_then(|sel| sel.cols_for_row(row, grid.cols()));
+                .and_then(|sel| sel.cols_for_row(term.display_line(row), grid.cols()));
 
             for col in 0..grid.cols() {
                 let cell = term.display_cell(row, col);
@@ -4248,7 +5621,7 @@ Make sure seatd/logind is running and you're on an active VT."
             let row_selection = term
                 .selection
                 .as_ref()
-                .and_then(|s| s.cols_for_row(row, max_cols));
+                .and_then(|s| s.cols_for_row(term.display_line(row), max_cols));
 
             // Pre-compute search matches for this row (active pane only)
             let row_search_matches = if is_active && search_mode {
@@ -5880,15 +7253,30 @@ Make sure seatd/logind is running and you're on an active VT."
             // Text
             text_renderer.begin();
 
-            let status = if term
-                .copy_mode
-                .as_ref()
-                .map(|cm| cm.selecting)
-                .unwrap_or(false)
-            {
-                "[VISUAL] v:toggle  y:yank  hjkl:move  /:search  Esc:exit"
-            } else {
-                "[COPY] v:select  y:yank  hjkl:move  /:search  Esc:exit"
+            let status = match term.copy_mode.as_ref() {
+                Some(cm) => {
+                    use terminal::copy_mode::VisualMode;
+                    let mode = match (cm.selecting, cm.mode) {
+                        (true, VisualMode::Line) => {
+                            "[V-LINE] V:toggle  y:yank  hjkl:move  /:search  Esc:exit"
+                        }
+                        (true, VisualMode::Block) => {
+                            "[V-BLOCK] C-v:toggle  y:yank  hjkl:move  /:search  Esc:exit"
+                        }
+                        (true, VisualMode::Char) => {
+                            "[VISUAL] v:toggle  y:yank  hjkl:move  /:search  Esc:exit"
+                        }
+                        _ => "[COPY] v/V/C-v:select  y:yank  hjkl:move  /:search  Esc:exit",
+                    };
+                    // Typed count / pending key / register, like vim's showcmd
+                    let pending = cm.pending_display();
+                    if pending.is_empty() {
+                        mode.to_string()
+                    } else {
+                        format!("{}  {}", mode, pending)
+                    }
+                }
+                None => String::new(),
             };
 
             // Background color for LCD compositing (matches ui_renderer bar)
@@ -5898,7 +7286,7 @@ Make sure seatd/logind is running and you're on an active VT."
                 glyph_atlas.ensure_glyph(ch);
             }
             text_renderer.push_text_with_bg(
-                status,
+                &status,
                 padding,
                 bar_y + 4.0 + ascent,
                 [1.0, 0.9, 0.7, 1.0],
@@ -5910,6 +7298,90 @@ Make sure seatd/logind is running and you're on an active VT."
             text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
         }
 
+        // === Hovered OSC 8 link: underline every cell of the link ===
+        if let Some(ref link) = hover_link {
+            text_renderer.begin();
+            for row in 0..grid.rows() {
+                for col in 0..grid.cols() {
+                    let same = term
+                        .display_cell(row, col)
+                        .hyperlink
+                        .as_ref()
+                        .is_some_and(|l| terminal::grid::Hyperlink::same_link(l, link));
+                    if same {
+                        text_renderer.push_rect(
+                            margin_x + col as f32 * cell_w,
+                            margin_y + (row + 1) as f32 * cell_h - 2.0,
+                            cell_w,
+                            2.0,
+                            UI_LINK_HOVER,
+                            &glyph_atlas,
+                        );
+                    }
+                }
+            }
+            text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
+        }
+
+        // === Hints labels ===
+        if let Some(ref hints) = term.hints {
+            let label_pad = 2.0_f32;
+
+            ui_renderer.begin();
+            for m in hints
+                .matches
+                .iter()
+                .filter(|m| m.label.starts_with(&hints.typed))
+            {
+                let x = margin_x + m.start_col as f32 * cell_w;
+                let y = margin_y + m.row as f32 * cell_h;
+                // Tint the matched text
+                ui_renderer.push_rounded_rect(
+                    x,
+                    y,
+                    (m.end_col - m.start_col) as f32 * cell_w,
+                    cell_h,
+                    0.0,
+                    UI_HINT_MATCH,
+                );
+                // Label box over the start of the match
+                let rest = m.label.len() - hints.typed.len();
+                ui_renderer.push_rounded_rect(
+                    x,
+                    y,
+                    rest as f32 * cell_w + label_pad * 2.0,
+                    cell_h,
+                    UI_HIGHLIGHT_RADIUS,
+                    UI_HINT_LABEL_BG,
+                );
+            }
+            ui_renderer.flush(gl, screen_w, screen_h);
+
+            text_renderer.begin();
+            let label_bg = rgb(UI_HINT_LABEL_BG);
+            for m in hints
+                .matches
+                .iter()
+                .filter(|m| m.label.starts_with(&hints.typed))
+            {
+                // Only the keys still to type
+                let rest = &m.label[hints.typed.len()..];
+                for ch in rest.chars() {
+                    glyph_atlas.ensure_glyph(ch);
+                }
+                text_renderer.push_text_with_bg(
+                    rest,
+                    margin_x + m.start_col as f32 * cell_w + label_pad,
+                    margin_y + m.row as f32 * cell_h + ascent,
+                    [0.1, 0.1, 0.1, 1.0],
+                    label_bg,
+                    &glyph_atlas,
+                );
+            }
+            glyph_atlas.upload_if_dirty(gl);
+            text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
+        }
+
         // === Search bar (screen bottom) ===
         if search_mode {
             if let Some(ref search) = term.search {
@@ -5979,26 +7451,56 @@ Make sure seatd/logind is running and you're on an active VT."
                     &glyph_atlas,
                 );
 
-                // Match count display
+                // Right side, drawn right to left: option flags, match count, error
+                let mut right_segments: Vec<(String, [f32; 4])> = Vec::new();
+                let mut flags = String::new();
+                if search.options.regex {
+                    flags.push_str("regex ");
+                }
+                flags.push_str(search.options.case.label());
+                right_segments.push((flags, [0.5, 0.5, 0.55, 1.0]));
+                // "…" while the scrollback scan is still running
+                let scanning = if search.scanning { "…" } else { "" };
                 if !search.matches.is_empty() {
-                    let match_info =
-                        format!("{}/{}", search.current_match + 1, search.matches.len());
-                    for ch in match_info.chars() {
+                    right_segments.push((
+                        format!(
+                            "{}/{}{}",
+                            search.current_match + 1,
+                            search.matches.len(),
+                            scanning
+                        ),
+                        [0.7, 0.7, 0.7, 1.0],
+                    ));
+                } else if search.executed && search.error.is_none() {
+                    right_segments.push((format!("0/0{}", scanning), [0.7, 0.7, 0.7, 1.0]));
+                }
+                if let Some(ref err) = search.error {
+                    right_segments.push((format!("invalid pattern: {}", err), UI_SEARCH_ERROR));
+                }
+
+                let mut info_right = screen_w as f32 - padding;
+                for (text, color) in &right_segments {
+                    for ch in text.chars() {
                         glyph_atlas.ensure_glyph(ch);
                     }
-                    let info_cols: usize = match_info
+                    let info_cols: usize = text
                         .chars()
                         .map(|ch| unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0))
                         .sum();
-                    let info_x = screen_w as f32 - padding - info_cols as f32 * cell_w;
+                    let info_x = info_right - info_cols as f32 * cell_w;
+                    // Stop before overlapping the query
+                    if info_x < cursor_x + cell_w * 2.0 {
+                        break;
+                    }
                     text_renderer.push_text_with_bg(
-                        &match_info,
+                        text,
                         info_x,
                         bar_y + 4.0 + ascent,
-                        [0.7, 0.7, 0.7, 1.0],
+                        *color,
                         search_bar_bg,
                         &glyph_atlas,
                     );
+                    info_right = info_x - cell_w * 2.0;
                 }
 
                 glyph_atlas.upload_if_dirty(gl);
@@ -6202,6 +7704,256 @@ Make sure seatd/logind is running and you're on an active VT."
             text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
         }
 
+        // === Clipboard history picker (right side) ===
+        if clipboard_picker_open {
+            let panel_w = (screen_w as f32 * 0.25).clamp(250.0, 450.0);
+            let panel_x = screen_w as f32 - panel_w;
+            let panel_h = screen_h as f32;
+            let padding = 8.0_f32;
+            let (header_h, item_h, max_visible) = clipboard_picker_layout(panel_h, cell_h);
+            let footer_h = cell_h + padding;
+            let history = terminal::clipboard::ClipboardHistory::lock(&term.clipboard_history);
+            let entries = history.entries();
+            let first = clipboard_picker_first(clipboard_picker_selected, max_visible);
+
+            // Background, header, footer and highlighted entry
+            ui_renderer.begin();
+            ui_renderer.push_rounded_rect(panel_x, 0.0, panel_w, panel_h, 0.0, UI_PANEL_BG);
+            ui_renderer.push_rounded_rect(panel_x, 0.0, panel_w, header_h, 0.0, UI_HEADER_BG);
+            ui_renderer.push_rounded_rect(
+                panel_x,
+                panel_h - footer_h,
+                panel_w,
+                footer_h,
+                0.0,
+                UI_HEADER_BG,
+            );
+            if clipboard_picker_selected < entries.len() {
+                let sel_y = header_h + (clipboard_picker_selected - first) as f32 * item_h;
+                ui_renderer.push_rounded_rect(
+                    panel_x + padding * 0.5,
+                    sel_y,
+                    panel_w - padding,
+                    item_h - 4.0,
+                    UI_HIGHLIGHT_RADIUS,
+                    UI_CANDIDATE_SEL,
+                );
+            }
+            ui_renderer.flush(gl, screen_w, screen_h);
+
+            text_renderer.begin();
+            let panel_bg = rgb(UI_PANEL_BG);
+            let header_bg = rgb(UI_HEADER_BG);
+            let sel_bg = rgb(UI_CANDIDATE_SEL);
+
+            // Header: "Clipboard History" + count
+            let header_text = "Clipboard History";
+            for ch in header_text.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            text_renderer.push_text_with_bg(
+                header_text,
+                panel_x + padding,
+                (padding + ascent).round(),
+                [1.0, 1.0, 1.0, 1.0],
+                header_bg,
+                &glyph_atlas,
+            );
+            let count_text = format!("{}", entries.len());
+            for ch in count_text.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            let count_x = panel_x + panel_w - padding - count_text.len() as f32 * cell_w;
+            text_renderer.push_text_with_bg(
+                &count_text,
+                count_x,
+                (padding + ascent).round(),
+                [0.6, 0.6, 0.65, 1.0],
+                header_bg,
+                &glyph_atlas,
+            );
+
+            // Entries, newest first: "<age> <source>" then the first line of text
+            let max_cols = ((panel_w - padding * 3.0) / cell_w) as usize;
+            for (idx, entry) in entries.iter().enumerate().skip(first).take(max_visible) {
+                let item_y = header_h + (idx - first) as f32 * item_h;
+                let bg = if idx == clipboard_picker_selected {
+                    sel_bg
+                } else {
+                    panel_bg
+                };
+
+                let elapsed = entry.age_secs();
+                let age = if elapsed < 60 {
+                    format!("{}s", elapsed)
+                } else if elapsed < 3600 {
+                    format!("{}m", elapsed / 60)
+                } else {
+                    format!("{}h", elapsed / 3600)
+                };
+                let lines = entry.text.lines().count();
+                let meta = if lines > 1 {
+                    format!("{} {} ({} lines)", age, entry.source.label(), lines)
+                } else {
+                    format!("{} {}", age, entry.source.label())
+                };
+                for ch in meta.chars() {
+                    glyph_atlas.ensure_glyph(ch);
+                }
+                text_renderer.push_text_with_bg(
+                    &meta,
+                    panel_x + padding,
+                    (item_y + ascent).round(),
+                    [0.45, 0.45, 0.5, 1.0],
+                    bg,
+                    &glyph_atlas,
+                );
+
+                let first_line = entry
+                    .text
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .unwrap_or("")
+                    .replace('\t', " ");
+                let preview = truncate_to_width(first_line.trim(), max_cols);
+                for ch in preview.chars() {
+                    if !font::emoji::is_emoji(ch) {
+                        glyph_atlas.ensure_glyph(ch);
+                    }
+                }
+                text_renderer.push_text_with_bg(
+                    &preview,
+                    panel_x + padding * 2.0,
+                    (item_y + cell_h + ascent).round(),
+                    [0.95, 0.95, 1.0, 1.0],
+                    bg,
+                    &glyph_atlas,
+                );
+            }
+
+            let footer_text = "[Enter]paste  [d]elete  [c]lear  [Esc]";
+            for ch in footer_text.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            text_renderer.push_text_with_bg(
+                footer_text,
+                panel_x + padding,
+                (panel_h - footer_h + padding * 0.5 + ascent).round(),
+                [0.5, 0.5, 0.55, 1.0],
+                header_bg,
+                &glyph_atlas,
+            );
+
+            glyph_atlas.upload_if_dirty(gl);
+            text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
+        }
+
+        // === Paste confirmation (centered) ===
+        if let Some(ref pending) = term.pending_paste {
+            const PREVIEW_LINES: usize = 6;
+            let padding = 12.0_f32;
+            let summary = pending.warning.summary();
+            let max_cols = ((screen_w as f32 * 0.8 - padding * 2.0) / cell_w).max(20.0) as usize;
+            let box_cols =
+                unicode_width::UnicodeWidthStr::width(summary.as_str()).clamp(40, max_cols);
+            let preview: Vec<String> = pending
+                .text
+                .lines()
+                .take(PREVIEW_LINES)
+                .map(|l| {
+                    let l: String = l
+                        .chars()
+                        .map(|c| if c.is_control() { '?' } else { c })
+                        .collect();
+                    truncate_to_width(&l, box_cols)
+                })
+                .collect();
+            let more = pending.warning.lines.saturating_sub(PREVIEW_LINES);
+            // Summary, blank, preview (+ "more" line), blank, footer
+            let text_rows = preview.len() + usize::from(more > 0) + 4;
+            let box_w = box_cols as f32 * cell_w + padding * 2.0;
+            let box_h = text_rows as f32 * cell_h + padding * 2.0;
+            let box_x = ((screen_w as f32 - box_w) / 2.0).max(0.0).round();
+            let box_y = ((screen_h as f32 - box_h) / 2.0).max(0.0).round();
+
+            ui_renderer.begin();
+            ui_renderer.push_shadow_rounded_rect(
+                box_x,
+                box_y,
+                box_w,
+                box_h,
+                UI_CORNER_RADIUS,
+                UI_PANEL_BG,
+                UI_SHADOW_OFFSET,
+                UI_SHADOW_COLOR,
+            );
+            ui_renderer.flush(gl, screen_w, screen_h);
+
+            text_renderer.begin();
+            let panel_bg = rgb(UI_PANEL_BG);
+            let text_x = box_x + padding;
+            let mut y = box_y + padding;
+            let summary = truncate_to_width(&summary, box_cols);
+            for ch in summary.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            text_renderer.push_text_with_bg(
+                &summary,
+                text_x,
+                (y + ascent).round(),
+                UI_SEARCH_ERROR,
+                panel_bg,
+                &glyph_atlas,
+            );
+            y += cell_h * 2.0;
+            for line in &preview {
+                for ch in line.chars() {
+                    if !font::emoji::is_emoji(ch) {
+                        glyph_atlas.ensure_glyph(ch);
+                    }
+                }
+                text_renderer.push_text_with_bg(
+                    line,
+                    text_x,
+                    (y + ascent).round(),
+                    [0.85, 0.85, 0.9, 1.0],
+                    panel_bg,
+                    &glyph_atlas,
+                );
+                y += cell_h;
+            }
+            if more > 0 {
+                let more_text = format!("... {} more lines", more);
+                for ch in more_text.chars() {
+                    glyph_atlas.ensure_glyph(ch);
+                }
+                text_renderer.push_text_with_bg(
+                    &more_text,
+                    text_x,
+                    (y + ascent).round(),
+                    [0.5, 0.5, 0.55, 1.0],
+                    panel_bg,
+                    &glyph_atlas,
+                );
+                y += cell_h;
+            }
+            y += cell_h;
+            let footer_text = "[Enter]paste  [Esc]cancel";
+            for ch in footer_text.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            text_renderer.push_text_with_bg(
+                footer_text,
+                text_x,
+                (y + ascent).round(),
+                [0.6, 0.6, 0.65, 1.0],
+                panel_bg,
+                &glyph_atlas,
+            );
+            glyph_atlas.upload_if_dirty(gl);
+            text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
+        }
+
         // Bell flash is drawn after FBO blit (outside FBO cache)
 
         // === Screenshot ===
@@ -6310,8 +8062,54 @@ Make sure seatd/logind is running and you're on an active VT."
             text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
         }
 
+        // === Key table indicator (right end of tab bar, or bottom-right corner) ===
+        if let Some(ref active) = active_key_table {
+            let label = active.name.as_str();
+            let label_cols: usize = label
+                .chars()
+                .map(|ch| unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0))
+                .sum();
+            let pad = cell_w;
+            let badge_w = label_cols as f32 * cell_w + pad * 2.0;
+            let badge_h = cell_h + 4.0;
+            let badge_x = screen_w as f32 - badge_w - 4.0;
+            let badge_y = if !tab_bar_info.is_empty() {
+                // Centered in the tab bar
+                screen_h as f32 - (cell_h + 6.0) + 1.0
+            } else {
+                screen_h as f32 - badge_h - 4.0
+            };
+
+            ui_renderer.begin();
+            ui_renderer.push_rounded_rect(
+                badge_x,
+                badge_y,
+                badge_w,
+                badge_h,
+                UI_CORNER_RADIUS,
+                UI_KEY_TABLE_BG,
+            );
+            ui_renderer.flush(gl, screen_w, screen_h);
+
+            text_renderer.begin();
+            for ch in label.chars() {
+                glyph_atlas.ensure_glyph(ch);
+            }
+            let baseline = badge_y + (badge_h + glyph_atlas.ascent) * 0.5;
+            text_renderer.push_text_with_bg(
+                label,
+                badge_x + pad,
+                baseline.round(),
+                [1.0, 1.0, 1.0, 1.0],
+                rgb(UI_KEY_TABLE_BG),
+                &glyph_atlas,
+            );
+            glyph_atlas.upload_if_dirty(gl);
+            text_renderer.flush(gl, &glyph_atlas, screen_w, screen_h);
+        }
+
         // === Toast notifications + progress bar (drawn outside FBO, on top of tab bar) ===
-        if !toast_notifications.is_empty() || term.active_progress.is_some() {
+        if !toasts.is_empty() || term.active_progress.is_some() {
             let toast_w = (screen_w as f32 * 0.30).min(400.0);
             let toast_h = cell_h * 2.0 + 12.0;
             let progress_bar_h = 24.0_f32;
@@ -6342,9 +8140,9 @@ Make sure seatd/logind is running and you're on an active VT."
                 );
             }
 
-            for toast in toast_notifications.iter().rev().take(3) {
+            for toast in toasts.iter().rev().take(3) {
                 toast_y -= toast_h + 6.0;
-                let bg = match toast.urgency {
+                let bg = match toast.notification.urgency {
                     2 => UI_TOAST_ERROR_BG,
                     _ => UI_TOAST_BG,
                 };
@@ -6418,9 +8216,9 @@ Make sure seatd/logind is running and you're on an active VT."
             let toast_text_w = toast_w - 16.0;
             let max_chars = (toast_text_w / cell_w).floor() as usize;
 
-            for toast in toast_notifications.iter().rev().take(3) {
+            for toast in toasts.iter().rev().take(3) {
                 toast_y -= toast_h + 6.0;
-                let item_bg = match toast.urgency {
+                let item_bg = match toast.notification.urgency {
                     2 => rgb(UI_TOAST_ERROR_BG),
                     _ => toast_bg,
                 };
@@ -6429,7 +8227,7 @@ Make sure seatd/logind is running and you're on an active VT."
 
                 let title_x = toast_x + 8.0;
                 let title_baseline = (toast_y + 4.0 + ascent).round();
-                let title = truncate_to_width(&toast.title, max_chars);
+                let title = truncate_to_width(&toast.notification.title, max_chars);
                 for ch in title.chars() {
                     if !font::emoji::is_emoji(ch) {
                         glyph_atlas.ensure_glyph(ch);
@@ -6444,9 +8242,9 @@ Make sure seatd/logind is running and you're on an active VT."
                     &glyph_atlas,
                 );
 
-                if !toast.body.is_empty() {
+                if !toast.notification.body.is_empty() {
                     let body_baseline = (toast_y + 4.0 + cell_h + ascent).round();
-                    let body = truncate_to_width(&toast.body, max_chars);
+                    let body = truncate_to_width(&toast.notification.body, max_chars);
                     for ch in body.chars() {
                         if !font::emoji::is_emoji(ch) {
                             glyph_atlas.ensure_glyph(ch);
@@ -6481,9 +8279,9 @@ Make sure seatd/logind is running and you're on an active VT."
                     emoji_toast_y -= progress_bar_h + 6.0;
                 }
 
-                for toast in toast_notifications.iter().rev().take(3) {
+                for toast in toasts.iter().rev().take(3) {
                     emoji_toast_y -= toast_h + 6.0;
-                    let title = truncate_to_width(&toast.title, max_chars);
+                    let title = truncate_to_width(&toast.notification.title, max_chars);
                     let title_y = emoji_toast_y + 4.0;
 
                     // Scan title for emoji
@@ -6503,8 +8301,8 @@ Make sure seatd/logind is running and you're on an active VT."
                     }
 
                     // Scan body for emoji
-                    if !toast.body.is_empty() {
-                        let body = truncate_to_width(&toast.body, max_chars);
+                    if !toast.notification.body.is_empty() {
+                        let body = truncate_to_width(&toast.notification.body, max_chars);
                         let body_y = emoji_toast_y + 4.0 + cell_h;
                         let mut cx = toast_x + 8.0;
                         for ch in body.chars() {
@@ -6533,46 +8331,48 @@ Make sure seatd/logind is running and you're on an active VT."
             text_renderer.begin();
 
             if let Some(ref cm) = term.copy_mode {
-                // Copy mode cursor (yellow outline)
-                let cursor_x = margin_x + cm.cursor_col as f32 * cell_w;
-                let cursor_y = margin_y + cm.cursor_row as f32 * cell_h;
-                let border = 2.0_f32;
-                // Top edge
-                text_renderer.push_rect(
-                    cursor_x,
-                    cursor_y,
-                    cell_w,
-                    border,
-                    [1.0, 0.8, 0.0, 0.9],
-                    &glyph_atlas,
-                );
-                // Bottom edge
-                text_renderer.push_rect(
-                    cursor_x,
-                    cursor_y + cell_h - border,
-                    cell_w,
-                    border,
-                    [1.0, 0.8, 0.0, 0.9],
-                    &glyph_atlas,
-                );
-                // Left edge
-                text_renderer.push_rect(
-                    cursor_x,
-                    cursor_y,
-                    border,
-                    cell_h,
-                    [1.0, 0.8, 0.0, 0.9],
-                    &glyph_atlas,
-                );
-                // Right edge
-                text_renderer.push_rect(
-                    cursor_x + cell_w - border,
-                    cursor_y,
-                    border,
-                    cell_h,
-                    [1.0, 0.8, 0.0, 0.9],
-                    &glyph_atlas,
-                );
+                // Copy mode cursor (yellow outline), while its line is in view
+                if let Some(cursor_row) = term.line_display_row(cm.cursor_row) {
+                    let cursor_x = margin_x + cm.cursor_col as f32 * cell_w;
+                    let cursor_y = margin_y + cursor_row as f32 * cell_h;
+                    let border = 2.0_f32;
+                    // Top edge
+                    text_renderer.push_rect(
+                        cursor_x,
+                        cursor_y,
+                        cell_w,
+                        border,
+                        [1.0, 0.8, 0.0, 0.9],
+                        &glyph_atlas,
+                    );
+                    // Bottom edge
+                    text_renderer.push_rect(
+                        cursor_x,
+                        cursor_y + cell_h - border,
+                        cell_w,
+                        border,
+                        [1.0, 0.8, 0.0, 0.9],
+                        &glyph_atlas,
+                    );
+                    // Left edge
+                    text_renderer.push_rect(
+                        cursor_x,
+                        cursor_y,
+                        border,
+                        cell_h,
+                        [1.0, 0.8, 0.0, 0.9],
+                        &glyph_atlas,
+                    );
+                    // Right edge
+                    text_renderer.push_rect(
+                        cursor_x + cell_w - border,
+                        cursor_y,
+                        border,
+                        cell_h,
+                        [1.0, 0.8, 0.0, 0.9],
+                        &glyph_atlas,
+                    );
+                }
             } else if term.scroll_offset == 0 && grid.modes.cursor_visible {
                 // Normal cursor (hidden during scrollback display)
                 // In blink mode, toggle visibility with cursor_blink_visible
@@ -6604,7 +8404,7 @@ Make sure seatd/logind is running and you're on an active VT."
                     if let Some((sel_start, sel_end)) = term
                         .selection
                         .as_ref()
-                        .and_then(|s| s.cols_for_row(cursor_row, grid.cols()))
+                        .and_then(|s| s.cols_for_row(term.display_line(cursor_row), grid.cols()))
                     {
                         if cursor_col >= sel_start && cursor_col < sel_end {
                             let a = selection_alpha;
@@ -14,6 +14,8 @@ pub struct Tab {
     pub active_pane: PaneId,
     pub zoomed_pane: Option<PaneId>,
     pub title: String,
+    /// Title set by remote control: not replaced by the pane's OSC 0/2 title
+    pub title_locked: bool,
 }
 
 impl Tab {
@@ -28,6 +30,7 @@ impl Tab {
             active_pane: pane_id,
             zoomed_pane: None,
             title: String::new(),
+            title_locked: false,
         }
     }
 
@@ -58,16 +61,19 @@ impl Tab {
         self.panes.iter().map(|(id, p)| (*id, p.rect)).collect()
     }
 
-    /// Split the active pane
+    /// Split the active pane. `ratio` is the fraction given to the new pane.
     pub fn split(
         &mut self,
         new_pane_id: PaneId,
         terminal: Terminal,
         direction: Direction,
+        ratio: f32,
         available: PaneRect,
     ) {
+        // Tree ratio is the fraction allocated to the first (existing) pane
+        let ratio = (1.0 - ratio).clamp(0.1, 0.9);
         self.tree
-            .split(self.active_pane, new_pane_id, direction, 0.5);
+            .split(self.active_pane, new_pane_id, direction, ratio);
 
         // Add new pane with a temporary rect (will be set by relayout)
         let pane = Pane::new(new_pane_id, terminal, PaneRect::new(0.0, 0.0, 0.0, 0.0));
@@ -225,11 +231,13 @@ impl TabManager {
     pub fn split(
         &mut self,
         direction: Direction,
+        ratio: f32,
         terminal: Terminal,
         available: PaneRect,
     ) -> PaneId {
         let new_id = self.next_pane_id();
-        self.active_tab_mut().split(new_id, terminal, direction, available);
+        self.active_tab_mut()
+            .split(new_id, terminal, direction, ratio, available);
         new_id
     }
 
@@ -290,6 +298,16 @@ impl TabManager {
         }
     }
 
+    /// Switch to tab by index (0-based). Returns false if out of range.
+    pub fn goto_tab(&mut self, index: usize) -> bool {
+        if index < self.tabs.len() {
+            self.active_tab = index;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Process PTY output for ALL tabs (prevents buffer overflow in background tabs)
     pub fn process_all_pty(&mut self) -> bool {
         let mut any_output = false;
@@ -319,6 +337,33 @@ impl TabManager {
         }
     }
 
+    /// Index of the tab holding a pane
+    pub fn tab_of(&self, id: PaneId) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.panes.contains_key(&id))
+    }
+
+    /// Get a pane in any tab
+    pub fn pane(&self, id: PaneId) -> Option<&super::Pane> {
+        self.tabs.iter().find_map(|tab| tab.panes.get(&id))
+    }
+
+    /// Get a pane in any tab mutably
+    pub fn pane_mut(&mut self, id: PaneId) -> Option<&mut super::Pane> {
+        self.tabs.iter_mut().find_map(|tab| tab.panes.get_mut(&id))
+    }
+
+    /// Make a pane and its tab active. Returns false if the pane does not exist.
+    pub fn focus_pane(&mut self, id: PaneId) -> bool {
+        match self.tab_of(id) {
+            Some(index) => {
+                self.active_tab = index;
+                self.tabs[index].active_pane = id;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get the active pane ID
     pub fn active_pane_id(&self) -> PaneId {
         self.active_tab().active_pane
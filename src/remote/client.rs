@@ -0,0 +1,471 @@
+//! `bcon msg`: command-line client for the remote control socket
+//!
+//! The socket is `--socket`, then `$BCON_SOCKET` (exported to every shell
+//! bcon starts), then the newest `bcon-<pid>.sock` in `$XDG_RUNTIME_DIR`
+//! or `/tmp`, which covers login shells whose environment was cleared and
+//! the per-user sockets of a bcon running as root. `$BCON_SOCKET` and
+//! scanned sockets must belong to the current user, so another user's
+//! socket planted in `/tmp` is never picked.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::{LaunchLocation, Request, Response};
+use crate::config::action::{PaneDirection, SplitDirection};
+use crate::terminal::links::shell_quote;
+
+const USAGE: &str = "\
+USAGE:
+    bcon msg [--socket=PATH] [--json] COMMAND [OPTIONS]
+
+COMMANDS:
+    ls                                     List tabs and panes
+    split [--right|--down] [--pane=ID] [--ratio=R] [-- COMMAND...]
+                                           Split a pane, running a shell or COMMAND
+    launch [--tab] [--right|--down] [--pane=ID] [--ratio=R] -- COMMAND...
+                                           Run COMMAND in a new split or tab
+    focus (--pane=ID | --tab=N)            Focus a pane or a tab
+    close [--pane=ID]                      Close a pane
+    resize (--left|--right|--up|--down) [--amount=A] [--pane=ID]
+                                           Grow or shrink a pane
+    send-text [--pane=ID] [TEXT...]        Send TEXT (or stdin) to a pane
+    get-text [--pane=ID] [--extent=screen|all] [--ansi]
+                                           Print a pane's screen or scrollback
+    set-tab-title [--tab=N] [TITLE]        Set a tab title (none = follow the pane)
+    subscribe [EVENT...]                   Print events as JSON lines until interrupted
+                                           (pane_created, pane_closed, focus_changed,
+                                           title_changed, bell, notification,
+                                           command_finished, child_exited)
+
+Panes default to the active pane and tabs to the active tab.
+--json prints the raw JSON response.
+";
+
+/// Parsed command line
+#[derive(Debug, Default)]
+struct Args {
+    socket: Option<String>,
+    json: bool,
+    command: String,
+    pane: Option<u16>,
+    tab: Option<usize>,
+    split: Option<SplitDirection>,
+    resize: Option<PaneDirection>,
+    ratio: Option<f32>,
+    amount: Option<f32>,
+    new_tab: bool,
+    all: bool,
+    ansi: bool,
+    /// Positional arguments (TEXT, TITLE)
+    words: Vec<String>,
+    /// Arguments after `--`
+    program: Vec<String>,
+}
+
+/// Run `bcon msg` with the arguments following `msg`
+pub fn run(args: &[String]) -> Result<()> {
+    let help = args
+        .iter()
+        .take_while(|a| *a != "--")
+        .any(|a| a == "--help" || a == "-h");
+    if args.is_empty() || help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let args = parse_args(&normalize_tab_flag(args))?;
+    let request = build_request(&args)?;
+    let path = socket_path(args.socket.as_deref())?;
+    let (response, reader) = send(&path, &request)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string(&response)?);
+    } else if let Some(data) = &response.data {
+        print_data(&request, data);
+    }
+    if !response.ok {
+        bail!("{}", response.error.unwrap_or_default());
+    }
+    if let Request::Subscribe { .. } = request {
+        for line in reader.lines() {
+            println!("{}", line?);
+        }
+    }
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<Args> {
+    fn number<T: std::str::FromStr>(name: &str, value: Option<&str>) -> Result<T> {
+        let value = value.ok_or_else(|| anyhow!("--{} needs a value", name))?;
+        value
+            .parse()
+            .map_err(|_| anyhow!("invalid --{} value: {}", name, value))
+    }
+
+    let mut parsed = Args::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            parsed.program = iter.by_ref().cloned().collect();
+            break;
+        }
+        let Some(option) = arg.strip_prefix("--") else {
+            if parsed.command.is_empty() {
+                parsed.command = arg.clone();
+            } else {
+                parsed.words.push(arg.clone());
+            }
+            continue;
+        };
+        let (name, inline) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (option, None),
+        };
+        let takes_value = matches!(name, "socket" | "pane" | "ratio" | "amount" | "extent");
+        let value = if takes_value {
+            inline.or_else(|| iter.next().cloned())
+        } else {
+            inline
+        };
+        let value = value.as_deref();
+        match name {
+            "socket" => parsed.socket = value.map(str::to_string),
+            "json" => parsed.json = true,
+            "pane" => parsed.pane = Some(number(name, value)?),
+            "ratio" => parsed.ratio = Some(number(name, value)?),
+            "amount" => parsed.amount = Some(number(name, value)?),
+            "ansi" => parsed.ansi = true,
+            "extent" => {
+                parsed.all = match value {
+                    Some("screen") => false,
+                    Some("all") => true,
+                    _ => bail!("--extent is screen or all"),
+                }
+            }
+            // --tab=N selects a tab, a bare --tab opens one
+            "tab" if value.is_some() => parsed.tab = Some(number(name, value)?),
+            "tab" => parsed.new_tab = true,
+            "left" => parsed.resize = Some(PaneDirection::Left),
+            "up" => parsed.resize = Some(PaneDirection::Up),
+            "right" | "down" => {
+                let (split, resize) = if name == "right" {
+                    (SplitDirection::Right, PaneDirection::Right)
+                } else {
+                    (SplitDirection::Down, PaneDirection::Down)
+                };
+                parsed.split = Some(split);
+                parsed.resize = Some(resize);
+            }
+            _ => bail!("unknown option --{}", name),
+        }
+    }
+    Ok(parsed)
+}
+
+/// `--tab 2` selects tab 2, a bare `--tab` is a flag: rewrite the former as `--tab=2`
+fn normalize_tab_flag(args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            out.push(arg.clone());
+            out.extend(iter.cloned());
+            break;
+        }
+        if arg == "--tab" {
+            match iter.peek() {
+                Some(next) if next.parse::<usize>().is_ok() => {
+                    out.push(format!("--tab={}", next));
+                    iter.next();
+                }
+                _ => out.push("--tab".to_string()),
+            }
+        } else {
+            out.push(arg.clone());
+        }
+    }
+    out
+}
+
+/// One string for `sh -c`: a single argument is used as is, several are quoted
+fn program_string(program: &[String]) -> Option<String> {
+    match program {
+        [] => None,
+        [command] => Some(command.clone()),
+        words => Some(
+            words
+                .iter()
+                .map(|w| shell_quote(w))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    }
+}
+
+fn build_request(args: &Args) -> Result<Request> {
+    let direction = args.split.unwrap_or_default();
+    let ratio = args.ratio.unwrap_or(0.5);
+    let request = match args.command.as_str() {
+        "ls" => Request::Ls,
+        "split" => match program_string(&args.program) {
+            Some(command) => Request::Launch {
+                command,
+                location: LaunchLocation::Split,
+                pane: args.pane,
+                direction,
+                ratio,
+            },
+            None => Request::Split {
+                pane: args.pane,
+                direction,
+                ratio,
+            },
+        },
+        "launch" => Request::Launch {
+            command: program_string(&args.program)
+                .ok_or_else(|| anyhow!("launch needs a command after --"))?,
+            location: if args.new_tab {
+                LaunchLocation::Tab
+            } else {
+                LaunchLocation::Split
+            },
+            pane: args.pane,
+            direction,
+            ratio,
+        },
+        "focus" => {
+            if args.pane.is_none() && args.tab.is_none() {
+                bail!("focus needs --pane or --tab");
+            }
+            Request::Focus {
+                pane: args.pane,
+                tab: args.tab,
+            }
+        }
+        "close" => Request::Close { pane: args.pane },
+        "resize" => Request::Resize {
+            pane: args.pane,
+            direction: args
+                .resize
+                .ok_or_else(|| anyhow!("resize needs --left, --right, --up or --down"))?,
+            amount: args.amount.unwrap_or(0.05),
+        },
+        "send-text" => {
+            let text = if args.words.is_empty() || args.words == ["-"] {
+                let mut text = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut text)
+                    .context("failed to read stdin")?;
+                text
+            } else {
+                args.words.join(" ")
+            };
+            Request::SendText {
+                pane: args.pane,
+                text,
+            }
+        }
+        "get-text" => Request::GetText {
+            pane: args.pane,
+            scrollback: args.all,
+            ansi: args.ansi,
+        },
+        "set-tab-title" => Request::SetTabTitle {
+            tab: args.tab,
+            title: args.words.join(" "),
+        },
+        "subscribe" => Request::Subscribe {
+            events: args.words.clone(),
+        },
+        "" => bail!("missing command (see bcon msg --help)"),
+        other => bail!("unknown command '{}' (see bcon msg --help)", other),
+    };
+    Ok(request)
+}
+
+/// Socket to talk to: `--socket`, `$BCON_SOCKET`, or the newest bcon socket
+fn socket_path(explicit: Option<&str>) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(path) = std::env::var_os("BCON_SOCKET").filter(|p| !p.is_empty()) {
+        // Under a root bcon this is root's socket: users have their own
+        if std::fs::symlink_metadata(&path).is_ok_and(|m| trusted_socket(&m)) {
+            return Ok(PathBuf::from(path));
+        }
+    }
+    let dirs = std::env::var("XDG_RUNTIME_DIR")
+        .into_iter()
+        .chain(std::iter::once("/tmp".to_string()));
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_instance = name
+                .strip_prefix("bcon-")
+                .and_then(|n| n.strip_suffix(".sock"))
+                .is_some_and(|pid| !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit()));
+            if !is_instance {
+                continue;
+            }
+            // DirEntry::metadata does not follow symlinks
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !trusted_socket(&metadata) {
+                continue;
+            }
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if newest.as_ref().is_none_or(|(t, _)| modified > *t) {
+                newest = Some((modified, entry.path()));
+            }
+        }
+    }
+    newest
+        .map(|(_, path)| path)
+        .ok_or_else(|| anyhow!("no bcon instance found (BCON_SOCKET is not set)"))
+}
+
+/// A socket owned by the current user
+fn trusted_socket(metadata: &std::fs::Metadata) -> bool {
+    metadata.file_type().is_socket() && metadata.uid() == unsafe { libc::geteuid() }
+}
+
+fn send(path: &std::path::Path, request: &Request) -> Result<(Response, BufReader<UnixStream>)> {
+    let mut stream = UnixStream::connect(path)
+        .with_context(|| format!("cannot connect to {}", path.display()))?;
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.is_empty() {
+        bail!("bcon closed the connection");
+    }
+    Ok((serde_json::from_str(&line)?, reader))
+}
+
+/// Human-readable output
+fn print_data(request: &Request, data: &serde_json::Value) {
+    match request {
+        Request::Ls => {
+            let empty = Vec::new();
+            let tabs = data["tabs"].as_array().unwrap_or(&empty);
+            for tab in tabs {
+                let marker = if tab["active"] == true { "*" } else { " " };
+                println!(
+                    "{}tab {}: {}",
+                    marker,
+                    tab["tab"],
+                    tab["title"].as_str().unwrap_or("")
+                );
+                for pane in tab["panes"].as_array().unwrap_or(&empty) {
+                    let marker = if pane["active"] == true { "*" } else { " " };
+                    println!(
+                        "  {}pane {:<3} {:>3}x{:<3} {:<24} {}",
+                        marker,
+                        pane["id"],
+                        pane["cols"],
+                        pane["rows"],
+                        pane["title"].as_str().unwrap_or(""),
+                        pane["cwd"].as_str().unwrap_or("")
+                    );
+                }
+            }
+        }
+        Request::GetText { .. } => print!("{}", data["text"].as_str().unwrap_or("")),
+        _ => {
+            if let Some(pane) = data.get("pane") {
+                println!("{}", pane);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    fn request(args: &[&str]) -> Result<Request> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        build_request(&parse_args(&normalize_tab_flag(&args))?)
+    }
+
+    #[test]
+    fn test_build_request() {
+        assert_eq!(
+            request(&["split", "--right", "--", "htop"]).unwrap(),
+            Request::Launch {
+                command: "htop".to_string(),
+                location: LaunchLocation::Split,
+                pane: None,
+                direction: SplitDirection::Right,
+                ratio: 0.5,
+            }
+        );
+        assert_eq!(
+            request(&["split", "--down", "--pane", "3"]).unwrap(),
+            Request::Split {
+                pane: Some(3),
+                direction: SplitDirection::Down,
+                ratio: 0.5,
+            }
+        );
+        assert!(matches!(
+            request(&["launch", "--tab", "--", "less", "a b.txt"]).unwrap(),
+            Request::Launch { command, location: LaunchLocation::Tab, .. }
+                if command == "'less' 'a b.txt'"
+        ));
+        assert_eq!(
+            request(&["get-text", "--extent=all"]).unwrap(),
+            Request::GetText {
+                pane: None,
+                scrollback: true,
+                ansi: false,
+            }
+        );
+        assert_eq!(
+            request(&["focus", "--tab", "2"]).unwrap(),
+            Request::Focus {
+                pane: None,
+                tab: Some(2)
+            }
+        );
+        assert!(matches!(
+            request(&["resize", "--left", "--amount=0.1"]).unwrap(),
+            Request::Resize { direction: PaneDirection::Left, amount, .. } if amount == 0.1
+        ));
+        assert!(request(&["resize"]).is_err());
+        assert!(request(&["launch"]).is_err());
+        assert!(request(&["frobnicate"]).is_err());
+        assert!(request(&["ls", "--bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_trusted_socket() {
+        let socket = temp_path("client_test.sock");
+        let _ = std::fs::remove_file(&socket);
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert!(trusted_socket(&std::fs::symlink_metadata(&socket).unwrap()));
+
+        // A symlink to it is not a socket
+        let link = temp_path("client_test.link");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&socket, &link).unwrap();
+        assert!(!trusted_socket(&std::fs::symlink_metadata(&link).unwrap()));
+
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_file(&socket);
+    }
+}
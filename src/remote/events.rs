@@ -0,0 +1,314 @@
+//! Event subscription
+//!
+//! A connection that sends `{"cmd": "subscribe"}` receives one JSON object
+//! per line for every event from then on. Events are found by comparing a
+//! snapshot of every pane with the previous one, once per main loop
+//! iteration, and only while someone is subscribed (or `[hooks]` need
+//! them).
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::pane::tab::TabManager;
+use crate::pane::PaneId;
+
+/// An event sent to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    PaneCreated {
+        pane: u16,
+        tab: usize,
+    },
+    PaneClosed {
+        pane: u16,
+    },
+    /// Active pane changed (tab is 1-based)
+    FocusChanged {
+        pane: u16,
+        tab: usize,
+    },
+    /// Window title set through OSC 0 / 2
+    TitleChanged {
+        pane: u16,
+        title: String,
+    },
+    Bell {
+        pane: u16,
+    },
+    /// OSC 9 / OSC 99 notification
+    Notification {
+        pane: u16,
+        title: String,
+        body: String,
+        urgency: u8,
+    },
+    /// OSC 133;D (duration in seconds, from OSC 133;C)
+    CommandFinished {
+        pane: u16,
+        exit_code: Option<i32>,
+        duration: Option<f64>,
+    },
+    /// The pane's child process exited (the pane closes next)
+    ChildExited {
+        pane: u16,
+    },
+}
+
+/// Event names accepted by `subscribe`
+pub const EVENT_NAMES: &[&str] = &[
+    "pane_created",
+    "pane_closed",
+    "focus_changed",
+    "title_changed",
+    "bell",
+    "notification",
+    "command_finished",
+    "child_exited",
+];
+
+impl Event {
+    /// Pane the event is about
+    pub fn pane(&self) -> u16 {
+        match *self {
+            Event::PaneCreated { pane, .. }
+            | Event::PaneClosed { pane }
+            | Event::FocusChanged { pane, .. }
+            | Event::TitleChanged { pane, .. }
+            | Event::Bell { pane }
+            | Event::Notification { pane, .. }
+            | Event::CommandFinished { pane, .. }
+            | Event::ChildExited { pane } => pane,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::PaneCreated { .. } => "pane_created",
+            Event::PaneClosed { .. } => "pane_closed",
+            Event::FocusChanged { .. } => "focus_changed",
+            Event::TitleChanged { .. } => "title_changed",
+            Event::Bell { .. } => "bell",
+            Event::Notification { .. } => "notification",
+            Event::CommandFinished { .. } => "command_finished",
+            Event::ChildExited { .. } => "child_exited",
+        }
+    }
+}
+
+/// What the watcher remembers about a pane
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PaneState {
+    tab: usize,
+    title: Option<String>,
+    bells: u64,
+    notification_seq: u64,
+    commands: u64,
+    alive: bool,
+    /// User logged in on the pane, kept once the child is gone
+    uid: Option<u32>,
+}
+
+/// Turns pane state changes into events
+#[derive(Default)]
+pub struct EventWatcher {
+    /// None until the first poll (the first snapshot is not reported)
+    panes: Option<HashMap<PaneId, PaneState>>,
+    active: Option<PaneId>,
+}
+
+impl EventWatcher {
+    /// Forget the snapshot (nobody needs events)
+    pub fn clear(&mut self) {
+        self.panes = None;
+        self.active = None;
+    }
+
+    /// The pane's child exited and the pane closes before the next poll can
+    /// see it: its ChildExited event, unless already reported
+    pub fn child_exited(&mut self, id: PaneId) -> Option<Event> {
+        let state = self.panes.as_mut()?.get_mut(&id)?;
+        if !state.alive {
+            return None;
+        }
+        state.alive = false;
+        Some(Event::ChildExited { pane: id.0 })
+    }
+
+    /// User logged in on a pane, as last seen while it was alive
+    pub fn pane_uid(&self, id: PaneId) -> Option<u32> {
+        self.panes.as_ref()?.get(&id)?.uid
+    }
+
+    /// Events since the last poll
+    pub fn poll(&mut self, tab_mgr: &TabManager) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut panes = HashMap::new();
+        for (i, tab) in tab_mgr.tabs.iter().enumerate() {
+            for (id, pane) in &tab.panes {
+                let term = &pane.terminal;
+                let old = self.panes.as_ref().and_then(|p| p.get(id));
+                let state = PaneState {
+                    tab: i + 1,
+                    title: term.grid.window_title.clone(),
+                    bells: term.grid.bell_count,
+                    notification_seq: term.notification_seq,
+                    commands: term.grid.shell.commands_finished,
+                    alive: term.is_alive(),
+                    uid: term.logged_in_uid().or(old.and_then(|o| o.uid)),
+                };
+                if let Some(old) = old {
+                    // New notifications are at the tail of the history
+                    let new = state.notification_seq.saturating_sub(old.notification_seq) as usize;
+                    let skip = term.notifications.len().saturating_sub(new);
+                    for n in term.notifications.iter().skip(skip) {
+                        events.push(Event::Notification {
+                            pane: id.0,
+                            title: n.title.clone(),
+                            body: n.body.clone(),
+                            urgency: n.urgency,
+                        });
+                    }
+                    let new = state.commands.saturating_sub(old.commands) as usize;
+                    let skip = term.finished_commands.len().saturating_sub(new);
+                    for c in term.finished_commands.iter().skip(skip) {
+                        events.push(Event::CommandFinished {
+                            pane: id.0,
+                            exit_code: c.exit_code,
+                            duration: c.duration.map(|d| d.as_secs_f64()),
+                        });
+                    }
+                }
+                panes.insert(*id, state);
+            }
+        }
+        let active = tab_mgr.active_pane_id();
+        let previous = self.panes.replace(panes);
+        let previous_active = self.active.replace(active);
+        if let Some(previous) = previous {
+            events.extend(diff(
+                &previous,
+                self.panes.as_ref().expect("snapshot"),
+                previous_active,
+                active,
+            ));
+        }
+        events
+    }
+}
+
+/// Events between two snapshots (notifications and commands are read from
+/// the terminal in `poll`)
+fn diff(
+    old: &HashMap<PaneId, PaneState>,
+    new: &HashMap<PaneId, PaneState>,
+    old_active: Option<PaneId>,
+    active: PaneId,
+) -> Vec<Event> {
+    let mut ids: Vec<_> = old.keys().chain(new.keys()).copied().collect();
+    ids.sort_by_key(|id| id.0);
+    ids.dedup();
+
+    let mut events = Vec::new();
+    for id in ids {
+        let pane = id.0;
+        match (old.get(&id), new.get(&id)) {
+            (None, Some(state)) => events.push(Event::PaneCreated {
+                pane,
+                tab: state.tab,
+            }),
+            (Some(_), None) => events.push(Event::PaneClosed { pane }),
+            (Some(old), Some(state)) => {
+                if state.title != old.title {
+                    events.push(Event::TitleChanged {
+                        pane,
+                        title: state.title.clone().unwrap_or_default(),
+                    });
+                }
+                if state.bells != old.bells {
+                    events.push(Event::Bell { pane });
+                }
+                if old.alive && !state.alive {
+                    events.push(Event::ChildExited { pane });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    if old_active != Some(active) {
+        if let Some(state) = new.get(&active) {
+            events.push(Event::FocusChanged {
+                pane: active.0,
+                tab: state.tab,
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(tab: usize, title: &str, bells: u64, alive: bool) -> PaneState {
+        PaneState {
+            tab,
+            title: Some(title.to_string()),
+            bells,
+            alive,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = HashMap::from([
+            (PaneId(0), state(1, "zsh", 0, true)),
+            (PaneId(1), state(1, "vim", 0, true)),
+        ]);
+        let new = HashMap::from([
+            (PaneId(0), state(1, "make", 2, false)),
+            (PaneId(2), state(2, "htop", 0, true)),
+        ]);
+        let events = diff(&old, &new, Some(PaneId(0)), PaneId(2));
+        assert_eq!(
+            events,
+            vec![
+                Event::TitleChanged {
+                    pane: 0,
+                    title: "make".to_string()
+                },
+                Event::Bell { pane: 0 },
+                Event::ChildExited { pane: 0 },
+                Event::PaneClosed { pane: 1 },
+                Event::PaneCreated { pane: 2, tab: 2 },
+                Event::FocusChanged { pane: 2, tab: 2 },
+            ]
+        );
+        assert!(diff(&new, &new, Some(PaneId(2)), PaneId(2)).is_empty());
+
+        for event in &events {
+            let json = serde_json::to_value(event).unwrap();
+            assert_eq!(json["event"], event.name());
+            assert!(EVENT_NAMES.contains(&event.name()));
+        }
+    }
+
+    #[test]
+    fn test_child_exited_once() {
+        let mut watcher = EventWatcher::default();
+        assert_eq!(watcher.child_exited(PaneId(0)), None);
+        watcher.panes = Some(HashMap::from([
+            (PaneId(0), state(1, "zsh", 0, true)),
+            (PaneId(1), state(1, "vim", 0, false)),
+        ]));
+        assert_eq!(
+            watcher.child_exited(PaneId(0)),
+            Some(Event::ChildExited { pane: 0 })
+        );
+        assert_eq!(watcher.child_exited(PaneId(0)), None);
+        // Already reported by a poll
+        assert_eq!(watcher.child_exited(PaneId(1)), None);
+    }
+}
@@ -0,0 +1,604 @@
+//! Remote control
+//!
+//! bcon listens on a unix socket (`$XDG_RUNTIME_DIR/bcon-<pid>.sock`) so
+//! scripts can drive tabs and panes. The protocol is one JSON object per
+//! line in each direction: a request such as
+//! `{"cmd": "send_text", "pane": 3, "text": "ls\n"}` is answered with
+//! `{"ok": true, "data": ...}` or `{"ok": false, "error": "..."}`.
+//!
+//! Each connection is served on its own thread. Requests are handed to the
+//! main loop, which owns the tabs, together with the peer's uid
+//! (SO_PEERCRED). A peer only sees and drives the panes its user is logged
+//! in on (all of them when it runs as bcon's own user); other users are
+//! turned away when they connect. Sockets have mode 0600: when bcon runs as
+//! root, each user logged in on a pane gets its own socket
+//! (`/run/user/<uid>/bcon-<pid>.sock`) owned by that user.
+//! `bcon msg` ([`client`]) is the command-line client. A `subscribe`
+//! request turns the connection into a stream of [`events`].
+
+pub mod client;
+pub mod events;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::action::{PaneDirection, SplitDirection};
+use crate::terminal::clipboard_broker::peer_uid;
+
+/// Longest accepted request line
+const MAX_REQUEST: u64 = 16 * 1024 * 1024;
+
+/// How long a connection waits for the main loop to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events queued for a subscriber that is not reading (more are dropped)
+const EVENT_QUEUE: usize = 1024;
+
+/// Connections served at once (more are refused)
+const MAX_CONNECTIONS: usize = 16;
+
+/// Whether `peer` may use a pane whose logged-in user is `owner`: that user,
+/// or bcon's own user
+pub fn peer_may_use(peer: Option<u32>, owner: Option<u32>) -> bool {
+    peer.is_some_and(|uid| uid == unsafe { libc::geteuid() } || owner == Some(uid))
+}
+
+/// Default socket path
+pub fn default_socket_path() -> String {
+    let dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    format!("{}/bcon-{}.sock", dir, std::process::id())
+}
+
+/// Socket for `uid` when bcon runs as root: in its `/run/user/<uid>`, if
+/// that exists
+fn user_socket_path(uid: u32) -> Option<String> {
+    let dir = format!("/run/user/{}", uid);
+    std::path::Path::new(&dir)
+        .is_dir()
+        .then(|| format!("{}/bcon-{}.sock", dir, std::process::id()))
+}
+
+/// A remote control command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// List tabs and their panes
+    Ls,
+    /// Split a pane (default: the active one) and run a shell in the new pane
+    Split {
+        pane: Option<u16>,
+        #[serde(default)]
+        direction: SplitDirection,
+        #[serde(default = "default_ratio")]
+        ratio: f32,
+    },
+    /// Run a command in a new split or a new tab
+    Launch {
+        command: String,
+        #[serde(default)]
+        location: LaunchLocation,
+        /// Pane to split (location = "split")
+        pane: Option<u16>,
+        #[serde(default)]
+        direction: SplitDirection,
+        #[serde(default = "default_ratio")]
+        ratio: f32,
+    },
+    /// Focus a pane (switching to its tab) or a tab (1-based)
+    Focus {
+        pane: Option<u16>,
+        tab: Option<usize>,
+    },
+    /// Close a pane
+    Close { pane: Option<u16> },
+    /// Grow or shrink a pane
+    Resize {
+        pane: Option<u16>,
+        direction: PaneDirection,
+        #[serde(default = "default_resize_amount")]
+        amount: f32,
+    },
+    /// Write text to a pane's application
+    SendText { pane: Option<u16>, text: String },
+    /// Read a pane's screen, or its scrollback and screen
+    GetText {
+        pane: Option<u16>,
+        #[serde(default)]
+        scrollback: bool,
+        /// Keep colors and links as escape sequences
+        #[serde(default)]
+        ansi: bool,
+    },
+    /// Set a tab's title (empty = follow the pane title again)
+    SetTabTitle { tab: Option<usize>, title: String },
+    /// Stream events on this connection (all of them when `events` is empty)
+    Subscribe {
+        #[serde(default)]
+        events: Vec<String>,
+    },
+}
+
+fn default_ratio() -> f32 {
+    0.5
+}
+
+fn default_resize_amount() -> f32 {
+    0.05
+}
+
+/// Where `launch` opens its command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchLocation {
+    #[default]
+    Split,
+    Tab,
+}
+
+/// Answer to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    pub fn ok(data: serde_json::Value) -> Self {
+        Self {
+            ok: true,
+            data: (!data.is_null()).then_some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// A request waiting for the main loop
+pub struct PendingRequest {
+    pub request: Request,
+    /// Uid of the connected process (None if SO_PEERCRED failed)
+    pub peer_uid: Option<u32>,
+    reply: mpsc::Sender<Response>,
+}
+
+impl PendingRequest {
+    /// Send the answer back to the connection
+    pub fn reply(self, response: Response) {
+        let _ = self.reply.send(response);
+    }
+}
+
+/// A connection streaming events
+struct Subscriber {
+    /// Event names to send (empty = all)
+    events: Vec<String>,
+    /// Uid of the connected process: it only gets events of its panes
+    peer_uid: Option<u32>,
+    tx: mpsc::SyncSender<Arc<str>>,
+}
+
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+/// Remote control socket
+pub struct RemoteServer {
+    listener: Listener,
+    requests: mpsc::Receiver<PendingRequest>,
+    shared: Shared,
+    /// Sockets of the users logged in on a pane, by uid (bcon running as root)
+    user_sockets: Mutex<HashMap<u32, Listener>>,
+}
+
+/// What the accept threads of all sockets share
+#[derive(Clone)]
+struct Shared {
+    tx: mpsc::Sender<PendingRequest>,
+    subscribers: Subscribers,
+    /// Users logged in on a pane (see `set_users`)
+    users: Arc<Mutex<Vec<u32>>>,
+    /// Connections being served
+    connections: Arc<AtomicUsize>,
+}
+
+/// A listening socket and its accept thread
+struct Listener {
+    path: String,
+    stop: Arc<AtomicBool>,
+}
+
+impl Listener {
+    /// Listen on `path` with mode 0600, owned by `owner` if given
+    fn start(path: &str, owner: Option<u32>, shared: Shared) -> Option<Self> {
+        // The path carries our pid: anything there is left over from a dead process
+        let _ = std::fs::remove_file(path);
+        let listener = match UnixListener::bind(path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Remote control: cannot listen on {}: {}", path, e);
+                return None;
+            }
+        };
+        if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)) {
+            log::warn!("Failed to set remote control socket mode: {}", e);
+        }
+        if let Some(uid) = owner {
+            if let Err(e) = std::os::unix::fs::chown(path, Some(uid), None) {
+                log::warn!("Remote control: cannot hand {} to uid {}: {}", path, uid, e);
+                let _ = std::fs::remove_file(path);
+                return None;
+            }
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let spawned = std::thread::Builder::new()
+            .name("remote-control".to_string())
+            .spawn(move || accept_loop(listener, shared, thread_stop));
+        if let Err(e) = spawned {
+            log::warn!("Remote control: {}", e);
+            let _ = std::fs::remove_file(path);
+            return None;
+        }
+
+        log::info!("Remote control listening on {}", path);
+        Some(Self {
+            path: path.to_string(),
+            stop,
+        })
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so it sees the stop flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Accept connections until `stop` is set, serving each on its own thread
+fn accept_loop(listener: UnixListener, shared: Shared, stop: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let peer = peer_uid(&stream);
+        let logged_in = |uid| shared.users.lock().is_ok_and(|u| u.contains(&uid));
+        if !peer_may_use(peer, None) && !peer.is_some_and(logged_in) {
+            log::warn!("Remote control: connection from uid {:?} refused", peer);
+            continue;
+        }
+        if shared.connections.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+            shared.connections.fetch_sub(1, Ordering::Relaxed);
+            log::warn!(
+                "Remote control: too many connections, uid {:?} refused",
+                peer
+            );
+            continue;
+        }
+        let tx = shared.tx.clone();
+        let subscribers = shared.subscribers.clone();
+        let connections = shared.connections.clone();
+        let spawned = std::thread::Builder::new()
+            .name("remote-client".to_string())
+            .spawn(move || {
+                serve(stream, tx, subscribers);
+                connections.fetch_sub(1, Ordering::Relaxed);
+            });
+        if spawned.is_err() {
+            shared.connections.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl RemoteServer {
+    /// Listen on `path` (mode 0600). When bcon runs as root, users logged
+    /// in on a pane get sockets of their own (see `set_users`).
+    pub fn start(path: &str) -> Option<Self> {
+        let (tx, rx) = mpsc::channel();
+        let shared = Shared {
+            tx,
+            subscribers: Subscribers::default(),
+            users: Arc::default(),
+            connections: Arc::default(),
+        };
+        let Some(listener) = Listener::start(path, None, shared.clone()) else {
+            log::warn!("Remote control disabled");
+            return None;
+        };
+        Some(Self {
+            listener,
+            requests: rx,
+            shared,
+            user_sockets: Mutex::default(),
+        })
+    }
+
+    /// Users logged in on a pane: the only ones besides bcon's own user
+    /// allowed to connect. Under root each gets a socket in its
+    /// `/run/user/<uid>`, owned by it with mode 0600; sockets of users no
+    /// longer logged in are removed.
+    pub fn set_users(&self, uids: Vec<u32>) {
+        if unsafe { libc::geteuid() } == 0 {
+            if let Ok(mut sockets) = self.user_sockets.lock() {
+                sockets.retain(|uid, _| uids.contains(uid));
+                for &uid in &uids {
+                    if sockets.contains_key(&uid) {
+                        continue;
+                    }
+                    let Some(path) = user_socket_path(uid) else {
+                        continue;
+                    };
+                    if let Some(listener) = Listener::start(&path, Some(uid), self.shared.clone()) {
+                        sockets.insert(uid, listener);
+                    }
+                }
+            }
+        }
+        if let Ok(mut users) = self.shared.users.lock() {
+            *users = uids;
+        }
+    }
+
+    /// Socket path (exported to child shells as BCON_SOCKET)
+    pub fn path(&self) -> &str {
+        &self.listener.path
+    }
+
+    /// Next request waiting for the main loop
+    pub fn try_recv(&self) -> Option<PendingRequest> {
+        self.requests.try_recv().ok()
+    }
+
+    /// Whether any connection is streaming events
+    pub fn has_subscribers(&self) -> bool {
+        self.shared.subscribers.lock().is_ok_and(|s| !s.is_empty())
+    }
+
+    /// Send events to the subscribers that asked for them and may use the
+    /// pane. `owner` gives the user logged in on a pane.
+    pub fn emit(&self, events: &[events::Event], owner: impl Fn(u16) -> Option<u32>) {
+        let Ok(mut subscribers) = self.shared.subscribers.lock() else {
+            return;
+        };
+        for event in events {
+            let Ok(mut json) = serde_json::to_string(event) else {
+                continue;
+            };
+            json.push('\n');
+            let line: Arc<str> = json.into();
+            let name = event.name();
+            let owner = owner(event.pane());
+            subscribers.retain(|s| {
+                if !s.events.is_empty() && !s.events.iter().any(|e| e == name) {
+                    return true;
+                }
+                if !peer_may_use(s.peer_uid, owner) {
+                    return true;
+                }
+                match s.tx.try_send(line.clone()) {
+                    Ok(()) => true,
+                    Err(mpsc::TrySendError::Full(_)) => {
+                        log::debug!("Remote control: subscriber lagging, event dropped");
+                        true
+                    }
+                    Err(mpsc::TrySendError::Disconnected(_)) => false,
+                }
+            });
+        }
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        // Ends the event streams
+        if let Ok(mut subscribers) = self.shared.subscribers.lock() {
+            subscribers.clear();
+        }
+        // The listeners stop their accept loops and remove their sockets
+        if let Ok(mut sockets) = self.user_sockets.lock() {
+            sockets.clear();
+        }
+    }
+}
+
+/// Answer the requests of one connection, one line each
+fn serve(stream: UnixStream, tx: mpsc::Sender<PendingRequest>, subscribers: Subscribers) {
+    let peer_uid = peer_uid(&stream);
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match (&mut reader).take(MAX_REQUEST).read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut subscribe = None;
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                if let Request::Subscribe { events } = &request {
+                    subscribe = Some(events.clone());
+                }
+                let (reply_tx, reply_rx) = mpsc::channel();
+                let pending = PendingRequest {
+                    request,
+                    peer_uid,
+                    reply: reply_tx,
+                };
+                if tx.send(pending).is_err() {
+                    return;
+                }
+                reply_rx
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| Response::error("no answer from bcon"))
+            }
+            Err(e) => Response::error(format!("invalid request: {}", e)),
+        };
+        let Ok(mut json) = serde_json::to_string(&response) else {
+            return;
+        };
+        json.push('\n');
+        if writer.write_all(json.as_bytes()).is_err() {
+            return;
+        }
+        if let (Some(events), true) = (subscribe, response.ok) {
+            stream_events(writer, events, peer_uid, &subscribers);
+            return;
+        }
+    }
+}
+
+/// Write events to a subscribed connection until it goes away
+fn stream_events(
+    mut writer: UnixStream,
+    events: Vec<String>,
+    peer_uid: Option<u32>,
+    subscribers: &Subscribers,
+) {
+    let (tx, rx) = mpsc::sync_channel(EVENT_QUEUE);
+    match subscribers.lock() {
+        Ok(mut subscribers) => subscribers.push(Subscriber {
+            events,
+            peer_uid,
+            tx,
+        }),
+        Err(_) => return,
+    }
+    // Ends when the server drops the sender or the client disconnects
+    for line in rx {
+        if writer.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, wait_for, wait_until};
+
+    #[test]
+    fn test_request_format() {
+        let request: Request =
+            serde_json::from_str(r#"{"cmd": "send_text", "pane": 3, "text": "ls\n"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::SendText {
+                pane: Some(3),
+                text: "ls\n".to_string()
+            }
+        );
+        let request: Request =
+            serde_json::from_str(r#"{"cmd": "launch", "command": "htop", "location": "tab"}"#)
+                .unwrap();
+        assert!(matches!(
+            request,
+            Request::Launch {
+                location: LaunchLocation::Tab,
+                pane: None,
+                ratio,
+                ..
+            } if ratio == 0.5
+        ));
+        assert!(serde_json::from_str::<Request>(r#"{"cmd": "resize"}"#).is_err());
+
+        let json = serde_json::to_string(&Response::ok(serde_json::json!({"pane": 2}))).unwrap();
+        assert_eq!(json, r#"{"ok":true,"data":{"pane":2}}"#);
+        let json = serde_json::to_string(&Response::error("no pane 9")).unwrap();
+        assert_eq!(json, r#"{"ok":false,"error":"no pane 9"}"#);
+    }
+
+    #[test]
+    fn test_peer_may_use() {
+        let own = unsafe { libc::geteuid() };
+        let other = own.wrapping_add(1);
+        assert!(peer_may_use(Some(own), None));
+        assert!(peer_may_use(Some(other), Some(other)));
+        assert!(!peer_may_use(Some(other), None));
+        assert!(!peer_may_use(Some(other), Some(own)));
+        assert!(!peer_may_use(None, Some(other)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("remote_test.sock");
+        let server = RemoteServer::start(&path).unwrap();
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(b"{\"cmd\": \"ls\"}\nnot json\n").unwrap();
+
+        let pending = wait_for(|| server.try_recv());
+        assert_eq!(pending.request, Request::Ls);
+        assert_eq!(pending.peer_uid, Some(unsafe { libc::geteuid() }));
+        pending.reply(Response::ok(serde_json::Value::Null));
+
+        let mut reader = BufReader::new(client);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"ok\":true}\n");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with("{\"ok\":false,\"error\":\"invalid request"));
+
+        drop(server);
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        use events::Event;
+
+        let path = temp_path("subscribe_test.sock");
+        let server = RemoteServer::start(&path).unwrap();
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client
+            .write_all(b"{\"cmd\": \"subscribe\", \"events\": [\"bell\"]}\n")
+            .unwrap();
+        let pending = wait_for(|| server.try_recv());
+        pending.reply(Response::ok(serde_json::Value::Null));
+
+        let mut reader = BufReader::new(client);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"ok\":true}\n");
+
+        wait_until(|| server.has_subscribers());
+        server.emit(
+            &[Event::PaneClosed { pane: 1 }, Event::Bell { pane: 2 }],
+            |_| None,
+        );
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"event\":\"bell\",\"pane\":2}\n");
+
+        drop(reader);
+        drop(server);
+    }
+}
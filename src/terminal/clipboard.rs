@@ -0,0 +1,322 @@
+//! Clipboard history
+//!
+//! Every clipboard write (copy, copy mode yank, OSC 52, URL and hint copies,
+//! changes from other instances) is recorded in a bounded ring shared by all
+//! panes, newest first, so an older entry can be pasted again from the picker.
+//! The ring can be persisted to a file under `$XDG_RUNTIME_DIR` (mode 0600) to
+//! survive a restart. Without `$XDG_RUNTIME_DIR` it stays in memory: there is
+//! no private directory to put it in.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a clipboard entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipSource {
+    /// Copy of the selection (Ctrl+Shift+C, copy_on_select)
+    Selection,
+    /// Copy mode yank
+    CopyMode,
+    /// Application write via OSC 52
+    Osc52,
+    /// Link copied instead of opened
+    Url,
+    /// Hints mode match
+    Hint,
+    /// Another bcon instance of the same user (clipboard broker)
+    Remote,
+}
+
+impl ClipSource {
+    /// Short label for the picker
+    pub fn label(self) -> &'static str {
+        match self {
+            ClipSource::Selection => "sel",
+            ClipSource::CopyMode => "copy",
+            ClipSource::Osc52 => "osc52",
+            ClipSource::Url => "url",
+            ClipSource::Hint => "hint",
+            ClipSource::Remote => "remote",
+        }
+    }
+}
+
+/// One clipboard history entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipEntry {
+    pub text: String,
+    pub source: ClipSource,
+    /// Unix time of the copy (seconds)
+    pub time: u64,
+}
+
+impl ClipEntry {
+    /// Seconds since the copy
+    pub fn age_secs(&self) -> u64 {
+        unix_now().saturating_sub(self.time)
+    }
+}
+
+/// On-disk format of a persisted history
+#[derive(Default, Serialize, Deserialize)]
+struct Persisted {
+    #[serde(default)]
+    entry: Vec<ClipEntry>,
+}
+
+/// Bounded clipboard history, newest first
+#[derive(Debug, Default)]
+pub struct ClipboardHistory {
+    entries: VecDeque<ClipEntry>,
+    /// Maximum number of entries (0 = history disabled)
+    max_entries: usize,
+    /// Skip copies made while the pane has echo disabled (password prompts)
+    skip_no_echo: bool,
+    /// Persistence file (None = memory only)
+    path: Option<String>,
+}
+
+/// History shared by every pane
+pub type SharedClipboardHistory = Arc<Mutex<ClipboardHistory>>;
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Default persistence file (None without `$XDG_RUNTIME_DIR`)
+pub fn default_history_path() -> Option<String> {
+    let dir = std::env::var("XDG_RUNTIME_DIR").ok()?;
+    let uid = unsafe { libc::geteuid() };
+    Some(format!("{}/bcon_clipboard_history_{}.toml", dir, uid))
+}
+
+/// Open a private file (clipboard history, primary selection) without
+/// following a symlink, and only if it is ours: another user must not be
+/// able to plant or read it
+fn open_private(path: &str, write: bool) -> std::io::Result<File> {
+    let file = std::fs::OpenOptions::new()
+        .read(!write)
+        .write(write)
+        .create(write)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)?;
+    if file.metadata()?.uid() != unsafe { libc::geteuid() } {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "owned by another user",
+        ));
+    }
+    Ok(file)
+}
+
+/// Read a private file (see `open_private`)
+pub fn read_private(path: &str) -> std::io::Result<String> {
+    let mut content = String::new();
+    open_private(path, false)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Replace the contents of a private file (see `open_private`), created
+/// with mode 0600
+pub fn write_private(path: &str, content: &str) -> std::io::Result<()> {
+    // Truncate only after the owner check
+    let mut file = open_private(path, true)?;
+    file.set_len(0)?;
+    file.write_all(content.as_bytes())
+}
+
+impl ClipboardHistory {
+    /// Create a history, loading entries from `path` when persistence is on
+    pub fn new(max_entries: usize, skip_no_echo: bool, path: Option<String>) -> Self {
+        let mut history = Self {
+            entries: VecDeque::new(),
+            max_entries,
+            skip_no_echo,
+            path,
+        };
+        history.load();
+        history
+    }
+
+    /// Wrap for sharing between panes
+    pub fn shared(self) -> SharedClipboardHistory {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Lock a shared history (a panic while holding the lock leaves valid data)
+    pub fn lock(shared: &SharedClipboardHistory) -> MutexGuard<'_, Self> {
+        shared.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Apply new settings (config reload), keeping the entries
+    pub fn configure(&mut self, max_entries: usize, skip_no_echo: bool, path: Option<String>) {
+        let reload = path != self.path;
+        self.max_entries = max_entries;
+        self.skip_no_echo = skip_no_echo;
+        self.path = path;
+        if reload {
+            self.load();
+        }
+        self.entries.truncate(self.max_entries);
+        self.save();
+    }
+
+    /// Record a copy. An identical earlier entry moves to the front.
+    /// `echo` is the pane's termios ECHO flag at the time of the copy.
+    pub fn push(&mut self, text: &str, source: ClipSource, echo: bool) {
+        if self.max_entries == 0 || text.is_empty() || (self.skip_no_echo && !echo) {
+            return;
+        }
+        self.entries.retain(|e| e.text != text);
+        self.entries.push_front(ClipEntry {
+            text: text.to_string(),
+            source,
+            time: unix_now(),
+        });
+        self.entries.truncate(self.max_entries);
+        self.save();
+    }
+
+    /// Copies made with echo off are kept out (config: history_skip_no_echo)
+    pub fn skip_no_echo(&self) -> bool {
+        self.skip_no_echo
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> &VecDeque<ClipEntry> {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove one entry
+    pub fn remove(&mut self, idx: usize) {
+        if self.entries.remove(idx).is_some() {
+            self.save();
+        }
+    }
+
+    /// Forget everything
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.save();
+    }
+
+    fn load(&mut self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let content = match read_private(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                log::warn!("Ignoring clipboard history {}: {}", path, e);
+                return;
+            }
+        };
+        match toml::from_str::<Persisted>(&content) {
+            Ok(persisted) => {
+                self.entries = persisted.entry.into();
+                self.entries.truncate(self.max_entries);
+            }
+            Err(e) => log::warn!("Ignoring clipboard history {}: {}", path, e),
+        }
+    }
+
+    fn save(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let persisted = Persisted {
+            entry: self.entries.iter().cloned().collect(),
+        };
+        let content = match toml::to_string(&persisted) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("Failed to serialize clipboard history: {}", e);
+                return;
+            }
+        };
+        // Clipboard contents are private: never readable by other users
+        if let Err(e) = write_private(path, &content) {
+            log::warn!("Failed to write clipboard history {}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_push_dedup_and_bound() {
+        let mut history = ClipboardHistory::new(3, true, None);
+        history.push("a", ClipSource::Selection, true);
+        history.push("b", ClipSource::Osc52, true);
+        history.push("a", ClipSource::CopyMode, true);
+        let texts: Vec<_> = history.entries().iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["a", "b"]);
+        assert_eq!(history.entries()[0].source, ClipSource::CopyMode);
+
+        history.push("c", ClipSource::Url, true);
+        history.push("d", ClipSource::Url, true);
+        let texts: Vec<_> = history.entries().iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["d", "c", "a"]);
+    }
+
+    #[test]
+    fn test_skip_no_echo() {
+        let mut history = ClipboardHistory::new(10, true, None);
+        history.push("hunter2", ClipSource::Osc52, false);
+        assert!(history.is_empty());
+
+        let mut history = ClipboardHistory::new(10, false, None);
+        history.push("hunter2", ClipSource::Osc52, false);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path = temp_path("clip_test");
+        let mut history = ClipboardHistory::new(10, true, Some(path.clone()));
+        history.push("line 1\nline \"2\"", ClipSource::Selection, true);
+        history.push("x", ClipSource::Hint, true);
+
+        let loaded = ClipboardHistory::new(10, true, Some(path.clone()));
+        assert_eq!(loaded.entries(), history.entries());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_persistence_refuses_symlink() {
+        let target = temp_path("clip_target");
+        let link = temp_path("clip_link");
+        std::fs::write(&target, "keep").unwrap();
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut history = ClipboardHistory::new(10, true, Some(link.clone()));
+        history.push("secret", ClipSource::Osc52, true);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "keep");
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_file(&target);
+    }
+}
@@ -0,0 +1,446 @@
+//! Clipboard broker
+//!
+//! Shares the clipboard between bcon instances of the same user (one per VT).
+//! The first instance binds a unix socket under `$XDG_RUNTIME_DIR` and relays
+//! every clipboard change to the other instances. Instances running as root
+//! use the logged-in user's runtime directory instead, so VTs of different
+//! users never share a broker. Every instance, the broker's
+//! own included, connects to it as a client. When the broker exits, the others
+//! reconnect and one of them takes over.
+//!
+//! Changes received from other instances are written to the local clipboard
+//! file, which stays the fallback when no broker is reachable.
+//!
+//! Wire format: each message is a 4-byte big-endian length, a flags byte (bit 0:
+//! copied while the pane had echo on) and the UTF-8 text. Client -> broker:
+//! "the clipboard is now X". Broker -> client: "another instance set the
+//! clipboard to X" (the current value is also sent on connect).
+
+use std::io::{Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use super::clipboard::{ClipSource, ClipboardHistory, SharedClipboardHistory};
+
+/// Largest clipboard text relayed (larger messages drop the connection)
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Delay between attempts to reach or become the broker
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest delay between attempts while another user's socket is in the way
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A client that does not read its messages within this time is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Longest the main thread blocks handing a change to the broker
+const PUBLISH_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Flags byte: the text was copied while the pane had echo on
+const FLAG_ECHO: u8 = 1;
+
+/// Broker socket of the user `uid`: one per user. Running as root, it lives
+/// in that user's runtime directory (None until the directory exists).
+pub fn default_socket_path(uid: u32) -> Option<String> {
+    if unsafe { libc::geteuid() } == 0 {
+        let dir = format!("/run/user/{}", uid);
+        return std::path::Path::new(&dir)
+            .is_dir()
+            .then(|| format!("{}/bcon-clipboard.sock", dir));
+    }
+    Some(match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) => format!("{}/bcon-clipboard.sock", dir),
+        Err(_) => format!("/tmp/bcon-clipboard-{}.sock", uid),
+    })
+}
+
+/// UID of the process on the other end of a unix socket (SO_PEERCRED)
+pub fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (ret == 0).then_some(cred.uid)
+}
+
+/// Only instances running as the same user may share the clipboard
+fn same_user(stream: &UnixStream) -> bool {
+    peer_uid(stream) == Some(unsafe { libc::geteuid() })
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write_message(stream: &mut UnixStream, text: &str, echo: bool) -> std::io::Result<()> {
+    let mut buf = Vec::with_capacity(5 + text.len());
+    buf.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    buf.push(if echo { FLAG_ECHO } else { 0 });
+    buf.extend_from_slice(text.as_bytes());
+    stream.write_all(&buf)
+}
+
+/// Read one message: the text and its echo flag
+fn read_message(stream: &mut UnixStream) -> std::io::Result<(String, bool)> {
+    let mut header = [0u8; 5];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let echo = header[4] & FLAG_ECHO != 0;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "clipboard message too large",
+        ));
+    }
+    let mut data = vec![0u8; len];
+    stream.read_exact(&mut data)?;
+    let text = String::from_utf8(data)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    Ok((text, echo))
+}
+
+/// State shared between the instance and its broker thread
+struct Shared {
+    socket_path: String,
+    /// Local clipboard file that receives remote changes
+    clipboard_file: Mutex<String>,
+    history: SharedClipboardHistory,
+    /// Connection to the broker (None while disconnected)
+    writer: Mutex<Option<UnixStream>>,
+    /// Last clipboard text seen by this instance and its echo flag (seeds a
+    /// new broker)
+    latest: Mutex<(String, bool)>,
+    /// Whether this instance is the broker (its accept loop must be woken
+    /// to stop)
+    serving: AtomicBool,
+    stop: AtomicBool,
+}
+
+/// Connection of this instance to the per-user clipboard broker
+pub struct ClipboardBroker {
+    shared: Arc<Shared>,
+}
+
+impl ClipboardBroker {
+    /// Connect to the broker (becoming it if none is running) in the background
+    pub fn start(
+        socket_path: &str,
+        clipboard_file: &str,
+        history: SharedClipboardHistory,
+    ) -> Option<Self> {
+        let shared = Arc::new(Shared {
+            socket_path: socket_path.to_string(),
+            clipboard_file: Mutex::new(clipboard_file.to_string()),
+            history,
+            writer: Mutex::new(None),
+            latest: Mutex::new((String::new(), true)),
+            serving: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+        });
+        let thread_shared = shared.clone();
+        match std::thread::Builder::new()
+            .name("bcon-clipboard".into())
+            .spawn(move || client_thread(thread_shared))
+        {
+            Ok(_) => Some(Self { shared }),
+            Err(e) => {
+                log::warn!("Failed to start clipboard broker thread: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Tell the other instances about a local clipboard change. `echo` is
+    /// the pane's termios ECHO flag at the time of the copy.
+    pub fn publish(&self, text: &str, echo: bool) {
+        *lock(&self.shared.latest) = (text.to_string(), echo);
+        let mut writer = lock(&self.shared.writer);
+        if let Some(stream) = writer.as_mut() {
+            if let Err(e) = write_message(stream, text, echo) {
+                log::debug!("Clipboard broker: publish failed: {}", e);
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+                *writer = None;
+            }
+        }
+    }
+
+    /// Follow a changed clipboard file path (config reload)
+    pub fn set_clipboard_file(&self, path: &str) {
+        *lock(&self.shared.clipboard_file) = path.to_string();
+    }
+
+    /// Whether this instance currently reaches a broker
+    pub fn is_connected(&self) -> bool {
+        lock(&self.shared.writer).is_some()
+    }
+}
+
+impl Drop for ClipboardBroker {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(stream) = lock(&self.shared.writer).take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        // Wake the accept loop so it sees the stop flag
+        if self.shared.serving.load(Ordering::Relaxed) {
+            let _ = UnixStream::connect(&self.shared.socket_path);
+        }
+    }
+}
+
+/// Keep a connection to the broker, starting one when none answers
+fn client_thread(shared: Arc<Shared>) {
+    let mut delay = RECONNECT_DELAY;
+    while !shared.stop.load(Ordering::Relaxed) {
+        match UnixStream::connect(&shared.socket_path) {
+            Ok(stream) if same_user(&stream) => {
+                log::info!("Clipboard broker: connected to {}", shared.socket_path);
+                delay = RECONNECT_DELAY;
+                run_client(&shared, stream);
+                continue;
+            }
+            Ok(_) => {
+                // It may go away (an exited instance of another user): back off
+                if delay == RECONNECT_DELAY {
+                    log::warn!(
+                        "Clipboard broker: {} is owned by another user, not sharing",
+                        shared.socket_path
+                    );
+                }
+                sleep_unless_stopped(&shared, delay);
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+                continue;
+            }
+            Err(_) => become_broker(&shared),
+        }
+        std::thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+/// Sleep for `delay`, returning early when the instance stops
+fn sleep_unless_stopped(shared: &Shared, delay: Duration) {
+    let mut slept = Duration::ZERO;
+    while slept < delay && !shared.stop.load(Ordering::Relaxed) {
+        std::thread::sleep(RECONNECT_DELAY);
+        slept += RECONNECT_DELAY;
+    }
+}
+
+/// Receive changes until the broker goes away
+fn run_client(shared: &Shared, mut stream: UnixStream) {
+    // publish() runs on the main thread: a stuck broker must not freeze it
+    let _ = stream.set_write_timeout(Some(PUBLISH_TIMEOUT));
+    match stream.try_clone() {
+        Ok(writer) => *lock(&shared.writer) = Some(writer),
+        Err(e) => {
+            log::warn!("Clipboard broker: {}", e);
+            return;
+        }
+    }
+    while let Ok((text, echo)) = read_message(&mut stream) {
+        if shared.stop.load(Ordering::Relaxed) {
+            break;
+        }
+        {
+            // A broker seeded by this instance echoes our own text back
+            let mut latest = lock(&shared.latest);
+            if latest.0 == text {
+                continue;
+            }
+            *latest = (text.clone(), echo);
+        }
+        let path = lock(&shared.clipboard_file).clone();
+        if let Err(e) = std::fs::write(&path, &text) {
+            log::warn!("Failed to write clipboard file: {}", e);
+        }
+        ClipboardHistory::lock(&shared.history).push(&text, ClipSource::Remote, echo);
+    }
+    *lock(&shared.writer) = None;
+    log::info!("Clipboard broker: disconnected");
+}
+
+/// Bind the broker socket (replacing a stale one) and serve it in a thread
+fn become_broker(shared: &Arc<Shared>) {
+    let path = &shared.socket_path;
+    let listener = match UnixListener::bind(path) {
+        Ok(listener) => listener,
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+            // Another instance may have just become the broker
+            if UnixStream::connect(path).is_ok() {
+                return;
+            }
+            // Nobody answers: the socket file belongs to an exited instance
+            let _ = std::fs::remove_file(path);
+            match UnixListener::bind(path) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log::debug!("Clipboard broker: bind {} failed: {}", path, e);
+                    return;
+                }
+            }
+        }
+        Err(e) => {
+            log::debug!("Clipboard broker: bind {} failed: {}", path, e);
+            return;
+        }
+    };
+    let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+    let current = lock(&shared.latest).clone();
+    shared.serving.store(true, Ordering::Relaxed);
+    let thread_shared = shared.clone();
+    if let Err(e) = std::thread::Builder::new()
+        .name("bcon-clipboard-broker".into())
+        .spawn(move || serve(listener, current, &thread_shared))
+    {
+        shared.serving.store(false, Ordering::Relaxed);
+        log::warn!("Failed to start clipboard broker: {}", e);
+        return;
+    }
+    log::info!("Clipboard broker: serving {}", path);
+}
+
+/// Broker state: the current clipboard (with its echo flag) and every
+/// connected instance
+struct Broker {
+    current: (String, bool),
+    clients: Vec<(u64, UnixStream)>,
+    next_id: u64,
+}
+
+/// Accept instances until this one stops, then hand over: the socket is
+/// removed and the instances reconnect to elect a new broker
+fn serve(listener: UnixListener, current: (String, bool), shared: &Shared) {
+    let broker = Arc::new(Mutex::new(Broker {
+        current,
+        clients: Vec::new(),
+        next_id: 0,
+    }));
+    let inode = std::fs::metadata(&shared.socket_path).map(|m| m.ino()).ok();
+    for stream in listener.incoming() {
+        if shared.stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        if !same_user(&stream) {
+            log::warn!("Clipboard broker: rejected a connection from another user");
+            continue;
+        }
+        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+
+        let id = {
+            let mut state = lock(&broker);
+            let (ref text, echo) = state.current;
+            if !text.is_empty() && write_message(&mut stream, text, echo).is_err() {
+                continue;
+            }
+            let id = state.next_id;
+            state.next_id += 1;
+            state.clients.push((id, stream));
+            id
+        };
+
+        let broker = broker.clone();
+        let _ = std::thread::Builder::new()
+            .name("bcon-clipboard-peer".into())
+            .spawn(move || relay(broker, id, reader));
+    }
+
+    // Unless another broker has replaced it already
+    if std::fs::metadata(&shared.socket_path).is_ok_and(|m| Some(m.ino()) == inode) {
+        let _ = std::fs::remove_file(&shared.socket_path);
+    }
+    for (_, stream) in lock(&broker).clients.drain(..) {
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+    }
+    log::info!("Clipboard broker: stopped serving {}", shared.socket_path);
+}
+
+/// Forward one instance's changes to all the others
+fn relay(broker: Arc<Mutex<Broker>>, id: u64, mut reader: UnixStream) {
+    while let Ok((text, echo)) = read_message(&mut reader) {
+        let mut state = lock(&broker);
+        state.clients.retain_mut(|(other, stream)| {
+            *other == id || write_message(stream, &text, echo).is_ok()
+        });
+        state.current = (text, echo);
+    }
+    lock(&broker).clients.retain(|(other, _)| *other != id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, wait_until};
+
+    #[test]
+    fn test_message_roundtrip() {
+        let (mut a, mut b) = UnixStream::pair().unwrap();
+        write_message(&mut a, "héllo\nworld", true).unwrap();
+        write_message(&mut a, "", false).unwrap();
+        assert_eq!(
+            read_message(&mut b).unwrap(),
+            ("héllo\nworld".to_string(), true)
+        );
+        assert_eq!(read_message(&mut b).unwrap(), (String::new(), false));
+        assert_eq!(peer_uid(&a), Some(unsafe { libc::geteuid() }));
+    }
+
+    #[test]
+    fn test_instances_share_clipboard() {
+        let socket = temp_path("broker_test.sock");
+        let file_a = temp_path("broker_test_a");
+        let file_b = temp_path("broker_test_b");
+        let history = ClipboardHistory::new(10, true, None).shared();
+
+        let a = ClipboardBroker::start(&socket, &file_a, history.clone()).unwrap();
+        wait_until(|| a.is_connected());
+        let b = ClipboardBroker::start(&socket, &file_b, history.clone()).unwrap();
+        wait_until(|| b.is_connected());
+
+        a.publish("from a", true);
+        wait_until(|| std::fs::read_to_string(&file_b).ok().as_deref() == Some("from a"));
+        assert!(!std::path::Path::new(&file_a).exists());
+        assert_eq!(
+            ClipboardHistory::lock(&history).entries()[0].source,
+            ClipSource::Remote
+        );
+
+        // Copied with echo off: shared, but kept out of b's history
+        a.publish("hunter2", false);
+        wait_until(|| std::fs::read_to_string(&file_b).ok().as_deref() == Some("hunter2"));
+        assert_eq!(ClipboardHistory::lock(&history).len(), 1);
+
+        // a is the broker: once it stops, b takes over
+        drop(a);
+        wait_until(|| !b.is_connected());
+        wait_until(|| b.is_connected());
+        let file_c = temp_path("broker_test_c");
+        let c = ClipboardBroker::start(&socket, &file_c, history.clone()).unwrap();
+        wait_until(|| c.is_connected());
+        b.publish("from b", true);
+        wait_until(|| std::fs::read_to_string(&file_c).ok().as_deref() == Some("from b"));
+
+        drop(b);
+        drop(c);
+        let _ = std::fs::remove_file(&socket);
+        let _ = std::fs::remove_file(&file_b);
+        let _ = std::fs::remove_file(&file_c);
+    }
+}
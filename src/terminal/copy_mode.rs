@@ -0,0 +1,740 @@
+//! Copy mode vim motions
+//!
+//! Counts, word and WORD motions, f/t/F/T with `;`/`,` repeat, H/M/L,
+//! paragraph motions, `%`, `^`, marks, n/N over search results and yanking
+//! into registers. Motions work on buffer rows (scrollback followed by the
+//! screen) and scroll the view to keep the cursor visible.
+
+use super::grid::Cell;
+use super::Terminal;
+
+/// Direction and stop point of f/t/F/T
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindKind {
+    /// f: onto the next occurrence
+    Forward,
+    /// t: just before the next occurrence
+    TillForward,
+    /// F: onto the previous occurrence
+    Backward,
+    /// T: just after the previous occurrence
+    TillBackward,
+}
+
+impl FindKind {
+    fn from_key(ch: char) -> Option<Self> {
+        match ch {
+            'f' => Some(FindKind::Forward),
+            't' => Some(FindKind::TillForward),
+            'F' => Some(FindKind::Backward),
+            'T' => Some(FindKind::TillBackward),
+            _ => None,
+        }
+    }
+
+    /// Direction used by `,`
+    fn reversed(self) -> Self {
+        match self {
+            FindKind::Forward => FindKind::Backward,
+            FindKind::TillForward => FindKind::TillBackward,
+            FindKind::Backward => FindKind::Forward,
+            FindKind::TillBackward => FindKind::TillForward,
+        }
+    }
+}
+
+/// Selection flavor, as started with v, V or Ctrl+V
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisualMode {
+    /// v: character-wise
+    #[default]
+    Char,
+    /// V: whole rows
+    Line,
+    /// Ctrl+V: rectangle
+    Block,
+}
+
+/// First key of a two-key command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingKey {
+    Find(FindKind),
+    /// m: set a mark
+    SetMark,
+    /// ': jump to the line of a mark
+    JumpMarkLine,
+    /// `: jump to the exact position of a mark
+    JumpMark,
+    /// ": select a register
+    Register,
+}
+
+impl PendingKey {
+    /// Key as typed, for the status bar
+    pub fn key(self) -> &'static str {
+        match self {
+            PendingKey::Find(FindKind::Forward) => "f",
+            PendingKey::Find(FindKind::TillForward) => "t",
+            PendingKey::Find(FindKind::Backward) => "F",
+            PendingKey::Find(FindKind::TillBackward) => "T",
+            PendingKey::SetMark => "m",
+            PendingKey::JumpMarkLine => "'",
+            PendingKey::JumpMark => "`",
+            PendingKey::Register => "\"",
+        }
+    }
+}
+
+/// What the caller has to do after a copy mode key
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyModeAction {
+    None,
+    /// Leave copy mode
+    Exit,
+    /// Open the search bar
+    Search,
+    /// Copy mode has exited with this text; store it in `register`
+    /// (None = clipboard)
+    Yank {
+        register: Option<char>,
+        text: String,
+    },
+    /// Copy mode has exited; paste this register (None = clipboard)
+    Paste(Option<char>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Punct,
+    Word,
+}
+
+/// Character class for word motions (`big` = WORD: anything but blanks)
+fn char_class(ch: char, big: bool) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::Blank
+    } else if big || ch.is_alphanumeric() || ch == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+/// First character of a cell (blank for empty cells)
+fn cell_char(cell: &Cell) -> char {
+    cell.grapheme.chars().next().unwrap_or(' ')
+}
+
+/// Column reached by f/t/F/T on a row of characters (one per column, `\0`
+/// for wide-character continuations). `repeat` skips an adjacent target so
+/// that `;` after `t` moves on.
+fn find_in_row(
+    row: &[char],
+    col: usize,
+    kind: FindKind,
+    target: char,
+    repeat: bool,
+) -> Option<usize> {
+    match kind {
+        FindKind::Forward | FindKind::TillForward => {
+            let till = kind == FindKind::TillForward;
+            let from = col + if till && repeat { 2 } else { 1 };
+            let found = (from..row.len()).find(|&c| row[c] == target)?;
+            Some(if till { found - 1 } else { found })
+        }
+        FindKind::Backward | FindKind::TillBackward => {
+            let till = kind == FindKind::TillBackward;
+            let skip = if till && repeat { 2 } else { 1 };
+            let to = col.checked_sub(skip)?;
+            let found = (0..=to).rev().find(|&c| row[c] == target)?;
+            Some(if till { found + 1 } else { found })
+        }
+    }
+}
+
+/// Bracket pair for `%`: (open, close, search forward)
+fn bracket_pair(ch: char) -> Option<(char, char, bool)> {
+    match ch {
+        '(' => Some(('(', ')', true)),
+        '[' => Some(('[', ']', true)),
+        '{' => Some(('{', '}', true)),
+        ')' => Some(('(', ')', false)),
+        ']' => Some(('[', ']', false)),
+        '}' => Some(('{', '}', false)),
+        _ => None,
+    }
+}
+
+impl Terminal {
+    /// Number of buffer rows (scrollback followed by the screen)
+    fn buffer_rows(&self) -> usize {
+        self.grid.scrollback_len() + self.grid.rows()
+    }
+
+    /// Cell at a buffer row
+    fn buffer_cell(&self, abs: usize, col: usize) -> &Cell {
+        let scrollback_len = self.grid.scrollback_len();
+        if abs < scrollback_len {
+            self.grid
+                .scrollback_row(abs)
+                .and_then(|row| row.get(col))
+                .unwrap_or(Cell::empty_ref())
+        } else if col < self.grid.cols() {
+            self.grid.cell(abs - scrollback_len, col)
+        } else {
+            Cell::empty_ref()
+        }
+    }
+
+    fn buffer_row_wrapped(&self, abs: usize) -> bool {
+        let scrollback_len = self.grid.scrollback_len();
+        if abs < scrollback_len {
+            self.grid.scrollback_row_wrapped(abs)
+        } else {
+            self.grid.row_wrapped(abs - scrollback_len)
+        }
+    }
+
+    fn buffer_row_blank(&self, abs: usize) -> bool {
+        (0..self.grid.cols()).all(|col| cell_char(self.buffer_cell(abs, col)).is_whitespace())
+    }
+
+    /// Buffer row shown at the top of the view
+    fn view_top(&self) -> usize {
+        let scrollback_len = self.grid.scrollback_len();
+        scrollback_len - self.scroll_offset.min(scrollback_len)
+    }
+
+    /// Copy mode cursor as a buffer position
+    fn copy_cursor(&self) -> Option<(usize, usize)> {
+        let cm = self.copy_mode.as_ref()?;
+        let abs = cm.cursor_row.saturating_sub(self.first_line()) as usize;
+        Some((abs.min(self.buffer_rows() - 1), cm.cursor_col))
+    }
+
+    /// First non-blank column of a buffer row (0 if the row is blank)
+    fn first_non_blank(&self, abs: usize) -> usize {
+        (0..self.grid.cols())
+            .find(|&col| !cell_char(self.buffer_cell(abs, col)).is_whitespace())
+            .unwrap_or(0)
+    }
+
+    /// Move the copy mode cursor to a buffer position, scrolling as little as needed
+    pub fn copy_mode_set_cursor(&mut self, abs: usize, col: usize) {
+        let scrollback_len = self.grid.scrollback_len();
+        let rows = self.grid.rows();
+        let cols = self.grid.cols();
+        let abs = abs.min(self.buffer_rows() - 1);
+        let top = self.view_top();
+        let new_top = if abs < top {
+            abs
+        } else if abs >= top + rows {
+            abs + 1 - rows
+        } else {
+            top
+        };
+        let new_offset = scrollback_len - new_top.min(scrollback_len);
+        if new_offset != self.scroll_offset {
+            self.scroll_offset = new_offset;
+            self.grid.mark_all_dirty();
+        }
+        let line = self.first_line() + abs as u64;
+        if let Some(ref mut cm) = self.copy_mode {
+            cm.cursor_row = line;
+            cm.cursor_col = col.min(cols - 1);
+            if cm.selecting {
+                self.selection = cm.get_selection(cols);
+            }
+        }
+    }
+
+    // ========== Word motions ==========
+    // Positions are (buffer row, col). Column `cols` is the line break after
+    // a row that does not soft-wrap; it counts as a blank.
+
+    fn class_at(&self, (abs, col): (usize, usize), big: bool) -> CharClass {
+        if col >= self.grid.cols() {
+            CharClass::Blank
+        } else {
+            char_class(cell_char(self.buffer_cell(abs, col)), big)
+        }
+    }
+
+    fn next_pos(&self, (abs, col): (usize, usize)) -> Option<(usize, usize)> {
+        let cols = self.grid.cols();
+        let mut col = col + 1;
+        while col < cols && self.buffer_cell(abs, col).width == 0 {
+            col += 1;
+        }
+        if col < cols {
+            return Some((abs, col));
+        }
+        if col == cols && !self.buffer_row_wrapped(abs) {
+            return Some((abs, cols));
+        }
+        (abs + 1 < self.buffer_rows()).then_some((abs + 1, 0))
+    }
+
+    fn prev_pos(&self, (abs, col): (usize, usize)) -> Option<(usize, usize)> {
+        let cols = self.grid.cols();
+        let last_cell = |abs: usize| {
+            let mut c = cols - 1;
+            while c > 0 && self.buffer_cell(abs, c).width == 0 {
+                c -= 1;
+            }
+            c
+        };
+        if col > 0 {
+            let mut c = col.min(cols) - 1;
+            while c > 0 && self.buffer_cell(abs, c).width == 0 {
+                c -= 1;
+            }
+            return Some((abs, c));
+        }
+        let prev = abs.checked_sub(1)?;
+        if self.buffer_row_wrapped(prev) {
+            Some((prev, last_cell(prev)))
+        } else {
+            Some((prev, cols))
+        }
+    }
+
+    /// w / W: start of the next word
+    fn word_forward(&self, pos: (usize, usize), big: bool) -> (usize, usize) {
+        let mut p = pos;
+        let start = self.class_at(p, big);
+        if start != CharClass::Blank {
+            loop {
+                match self.next_pos(p) {
+                    Some(n) => {
+                        p = n;
+                        if self.class_at(p, big) != start {
+                            break;
+                        }
+                    }
+                    None => return p,
+                }
+            }
+        }
+        while self.class_at(p, big) == CharClass::Blank {
+            match self.next_pos(p) {
+                Some(n) => p = n,
+                None => break,
+            }
+        }
+        p
+    }
+
+    /// e / E: end of the current or next word
+    fn word_end(&self, pos: (usize, usize), big: bool) -> (usize, usize) {
+        let Some(mut p) = self.next_pos(pos) else {
+            return pos;
+        };
+        while self.class_at(p, big) == CharClass::Blank {
+            match self.next_pos(p) {
+                Some(n) => p = n,
+                None => return pos,
+            }
+        }
+        let class = self.class_at(p, big);
+        while let Some(n) = self.next_pos(p) {
+            if self.class_at(n, big) != class {
+                break;
+            }
+            p = n;
+        }
+        p
+    }
+
+    /// b / B: start of the current or previous word
+    fn word_backward(&self, pos: (usize, usize), big: bool) -> (usize, usize) {
+        let Some(mut p) = self.prev_pos(pos) else {
+            return pos;
+        };
+        while self.class_at(p, big) == CharClass::Blank {
+            match self.prev_pos(p) {
+                Some(n) => p = n,
+                None => return pos,
+            }
+        }
+        let class = self.class_at(p, big);
+        while let Some(n) = self.prev_pos(p) {
+            if self.class_at(n, big) != class {
+                break;
+            }
+            p = n;
+        }
+        p
+    }
+
+    // ========== Other motions ==========
+
+    /// f/t/F/T on the cursor row
+    fn find_motion(
+        &self,
+        (abs, col): (usize, usize),
+        kind: FindKind,
+        target: char,
+        repeat: bool,
+    ) -> Option<usize> {
+        let row: Vec<char> = (0..self.grid.cols())
+            .map(|c| {
+                let cell = self.buffer_cell(abs, c);
+                if cell.width == 0 {
+                    '\0'
+                } else {
+                    cell_char(cell)
+                }
+            })
+            .collect();
+        find_in_row(&row, col, kind, target, repeat)
+    }
+
+    /// }: next blank row after the current paragraph
+    fn paragraph_forward(&self, abs: usize) -> usize {
+        let last = self.buffer_rows() - 1;
+        let mut r = abs + 1;
+        while r < last && self.buffer_row_blank(r) {
+            r += 1;
+        }
+        while r < last && !self.buffer_row_blank(r) {
+            r += 1;
+        }
+        r.min(last)
+    }
+
+    /// {: previous blank row before the current paragraph
+    fn paragraph_backward(&self, abs: usize) -> usize {
+        let mut r = abs.saturating_sub(1);
+        while r > 0 && self.buffer_row_blank(r) {
+            r -= 1;
+        }
+        while r > 0 && !self.buffer_row_blank(r) {
+            r -= 1;
+        }
+        r
+    }
+
+    /// %: bracket matching the first bracket at or after the cursor on its row
+    fn matching_bracket(&self, (abs, col): (usize, usize)) -> Option<(usize, usize)> {
+        let cols = self.grid.cols();
+        let (start_col, (open, close, forward)) = (col..cols).find_map(|c| {
+            bracket_pair(cell_char(self.buffer_cell(abs, c))).map(|pair| (c, pair))
+        })?;
+        let mut depth = 0usize;
+        let mut p = (abs, start_col);
+        loop {
+            if p.1 < cols {
+                let ch = cell_char(self.buffer_cell(p.0, p.1));
+                if ch == open || ch == close {
+                    let opening = (ch == open) == forward;
+                    if opening {
+                        depth += 1;
+                    } else {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(p);
+                        }
+                    }
+                }
+            }
+            p = if forward {
+                self.next_pos(p)?
+            } else {
+                self.prev_pos(p)?
+            };
+        }
+    }
+
+    /// n / N in copy mode: next/previous search match relative to the cursor
+    fn search_match_from(
+        &mut self,
+        (abs, col): (usize, usize),
+        forward: bool,
+    ) -> Option<(usize, usize)> {
+        let first_line = self.first_line() as usize;
+        let search = self.search.as_mut()?;
+        if search.matches.is_empty() {
+            return None;
+        }
+        let here = (first_line + abs, col);
+        let key = |m: &super::search::SearchMatch| (m.start_row, m.start_col);
+        let idx = if forward {
+            let i = search.matches.partition_point(|m| key(m) <= here);
+            if i == search.matches.len() {
+                0
+            } else {
+                i
+            }
+        } else {
+            let i = search.matches.partition_point(|m| key(m) < here);
+            if i == 0 {
+                search.matches.len() - 1
+            } else {
+                i - 1
+            }
+        };
+        search.current_match = idx;
+        let m = search.matches[idx];
+        Some((m.start_row.checked_sub(first_line)?, m.start_col))
+    }
+
+    // ========== Key handling ==========
+
+    /// Handle a typed character in copy mode
+    pub fn copy_mode_key(&mut self, ch: char) -> CopyModeAction {
+        let Some(cm) = self.copy_mode.as_mut() else {
+            return CopyModeAction::None;
+        };
+
+        if let Some(pending) = cm.pending.take() {
+            let count = std::mem::take(&mut cm.count).max(1);
+            self.copy_mode_pending_key(pending, ch, count);
+            return CopyModeAction::None;
+        }
+
+        // Counts (a leading 0 is the line-start motion)
+        if let Some(digit) = ch.to_digit(10) {
+            if digit != 0 || cm.count > 0 {
+                cm.count = (cm.count * 10 + digit as usize).min(99_999);
+                return CopyModeAction::None;
+            }
+        }
+        let given = std::mem::take(&mut cm.count);
+        let count = given.max(1);
+        let last_find = cm.last_find;
+
+        let Some(pos) = self.copy_cursor() else {
+            return CopyModeAction::None;
+        };
+        let mut target = None;
+        match ch {
+            'h' | 'j' | 'k' | 'l' => {
+                let (dr, dc) = match ch {
+                    'h' => (0, -1),
+                    'j' => (1, 0),
+                    'k' => (-1, 0),
+                    _ => (0, 1),
+                };
+                for _ in 0..count {
+                    self.copy_mode_move(dr, dc);
+                }
+            }
+            'w' | 'W' | 'e' | 'E' | 'b' | 'B' => {
+                let big = ch.is_ascii_uppercase();
+                let mut p = pos;
+                for _ in 0..count {
+                    p = match ch.to_ascii_lowercase() {
+                        'w' => self.word_forward(p, big),
+                        'e' => self.word_end(p, big),
+                        _ => self.word_backward(p, big),
+                    };
+                }
+                target = Some(p);
+            }
+            '0' => target = Some((pos.0, 0)),
+            '^' => target = Some((pos.0, self.first_non_blank(pos.0))),
+            '$' => self.copy_mode_goto_line_end(),
+            'g' => self.copy_mode_goto_top(),
+            'G' if given > 0 => {
+                let abs = (given - 1).min(self.buffer_rows() - 1);
+                target = Some((abs, self.first_non_blank(abs)));
+            }
+            'G' => self.copy_mode_goto_bottom(),
+            'H' | 'M' | 'L' => {
+                let top = self.view_top();
+                let last = self.grid.rows() - 1;
+                let row = match ch {
+                    'H' => (count - 1).min(last),
+                    'M' => last / 2,
+                    _ => last.saturating_sub(count - 1),
+                };
+                target = Some((top + row, self.first_non_blank(top + row)));
+            }
+            '}' | '{' => {
+                let mut abs = pos.0;
+                for _ in 0..count {
+                    abs = if ch == '}' {
+                        self.paragraph_forward(abs)
+                    } else {
+                        self.paragraph_backward(abs)
+                    };
+                }
+                target = Some((abs, 0));
+            }
+            '%' => target = self.matching_bracket(pos),
+            ';' | ',' => {
+                if let Some((kind, c)) = last_find {
+                    let kind = if ch == ',' { kind.reversed() } else { kind };
+                    let mut col = pos.1;
+                    for _ in 0..count {
+                        match self.find_motion((pos.0, col), kind, c, true) {
+                            Some(found) => col = found,
+                            None => break,
+                        }
+                    }
+                    target = Some((pos.0, col));
+                }
+            }
+            'n' | 'N' => {
+                let mut p = pos;
+                for _ in 0..count {
+                    match self.search_match_from(p, ch == 'n') {
+                        Some(found) => p = found,
+                        None => break,
+                    }
+                }
+                target = Some(p);
+            }
+            'f' | 't' | 'F' | 'T' => {
+                if let (Some(kind), Some(cm)) = (FindKind::from_key(ch), self.copy_mode.as_mut()) {
+                    cm.pending = Some(PendingKey::Find(kind));
+                    cm.count = given;
+                }
+            }
+            'm' | '\'' | '`' | '"' => {
+                let pending = match ch {
+                    'm' => PendingKey::SetMark,
+                    '\'' => PendingKey::JumpMarkLine,
+                    '`' => PendingKey::JumpMark,
+                    _ => PendingKey::Register,
+                };
+                if let Some(ref mut cm) = self.copy_mode {
+                    cm.pending = Some(pending);
+                }
+            }
+            'v' => self.copy_mode_toggle_selection(VisualMode::Char),
+            'V' => self.copy_mode_toggle_selection(VisualMode::Line),
+            'y' => {
+                let register = self.copy_mode.as_mut().and_then(|cm| cm.register.take());
+                let text = self.get_selection_text();
+                self.exit_copy_mode();
+                return CopyModeAction::Yank { register, text };
+            }
+            'p' => {
+                let register = self.copy_mode.as_mut().and_then(|cm| cm.register.take());
+                self.exit_copy_mode();
+                return CopyModeAction::Paste(register);
+            }
+            '/' => return CopyModeAction::Search,
+            'q' => return CopyModeAction::Exit,
+            _ => {}
+        }
+        if let Some((abs, col)) = target {
+            self.copy_mode_set_cursor(abs, col);
+        }
+        CopyModeAction::None
+    }
+
+    /// Second key of f/t/F/T, m, ', ` and "
+    fn copy_mode_pending_key(&mut self, pending: PendingKey, ch: char, count: usize) {
+        let Some(pos) = self.copy_cursor() else {
+            return;
+        };
+        let first_line = self.first_line() as usize;
+        match pending {
+            PendingKey::Find(kind) => {
+                if let Some(ref mut cm) = self.copy_mode {
+                    cm.last_find = Some((kind, ch));
+                }
+                let mut col = pos.1;
+                for i in 0..count {
+                    match self.find_motion((pos.0, col), kind, ch, i > 0) {
+                        Some(found) => col = found,
+                        None => break,
+                    }
+                }
+                self.copy_mode_set_cursor(pos.0, col);
+            }
+            PendingKey::SetMark if ch.is_ascii_alphabetic() => {
+                self.copy_marks.insert(ch, (first_line + pos.0, pos.1));
+            }
+            PendingKey::JumpMarkLine | PendingKey::JumpMark => {
+                let Some(&(line, col)) = self.copy_marks.get(&ch) else {
+                    return;
+                };
+                // Mark scrolled out of the scrollback
+                let Some(abs) = line.checked_sub(first_line) else {
+                    return;
+                };
+                if abs >= self.buffer_rows() {
+                    return;
+                }
+                let col = if pending == PendingKey::JumpMark {
+                    col
+                } else {
+                    self.first_non_blank(abs)
+                };
+                self.copy_mode_set_cursor(abs, col);
+            }
+            PendingKey::Register if ch.is_ascii_alphabetic() || ch == '+' || ch == '"' => {
+                if let Some(ref mut cm) = self.copy_mode {
+                    // "+ and "" are the clipboard
+                    cm.register = ch.is_ascii_alphabetic().then_some(ch);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_in_row() {
+        let row: Vec<char> = "a,b,c,d".chars().collect();
+        assert_eq!(find_in_row(&row, 0, FindKind::Forward, ',', false), Some(1));
+        assert_eq!(
+            find_in_row(&row, 0, FindKind::TillForward, 'c', false),
+            Some(3)
+        );
+        // ; after t moves past the adjacent target
+        assert_eq!(
+            find_in_row(&row, 2, FindKind::TillForward, ',', true),
+            Some(4)
+        );
+        assert_eq!(
+            find_in_row(&row, 6, FindKind::Backward, 'b', false),
+            Some(2)
+        );
+        assert_eq!(
+            find_in_row(&row, 6, FindKind::TillBackward, ',', false),
+            Some(6)
+        );
+        assert_eq!(find_in_row(&row, 0, FindKind::Backward, 'a', false), None);
+        assert_eq!(find_in_row(&row, 0, FindKind::Forward, 'z', false), None);
+    }
+
+    #[test]
+    fn test_char_class() {
+        assert_eq!(char_class('a', false), CharClass::Word);
+        assert_eq!(char_class('_', false), CharClass::Word);
+        assert_eq!(char_class('.', false), CharClass::Punct);
+        assert_eq!(char_class('.', true), CharClass::Word);
+        assert_eq!(char_class(' ', true), CharClass::Blank);
+    }
+
+    #[test]
+    fn test_line_wise_selection() {
+        let mut cm = super::super::CopyModeState::new(5, 7);
+        cm.toggle_selection(VisualMode::Line);
+        cm.cursor_row = 2;
+        let sel = cm.get_selection(80).unwrap();
+        assert_eq!(sel.normalized(), (2, 0, 5, 80));
+        // v switches to character-wise without losing the anchor
+        cm.toggle_selection(VisualMode::Char);
+        let sel = cm.get_selection(80).unwrap();
+        assert_eq!((sel.anchor_row, sel.anchor_col), (5, 7));
+        // Ctrl+V makes it a rectangle between anchor and cursor
+        cm.cursor_col = 3;
+        cm.toggle_selection(VisualMode::Block);
+        let sel = cm.get_selection(80).unwrap();
+        assert_eq!(sel.normalized(), (2, 3, 5, 8));
+        assert_eq!(sel.cols_for_row(4, 80), Some((3, 8)));
+        cm.toggle_selection(VisualMode::Block);
+        assert!(cm.get_selection(80).is_none());
+    }
+}
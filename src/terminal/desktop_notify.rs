@@ -0,0 +1,563 @@
+//! Desktop notifications (org.freedesktop.Notifications)
+//!
+//! OSC 9 / OSC 99 notifications can also be shown by a notification daemon
+//! on the session bus (mako, dunst, a desktop session on another VT). D-Bus
+//! runs in its own thread (tokio runtime) like the IME client, and the bus
+//! is only connected when the first notification arrives, so a session bus
+//! that appears after login is picked up. When bcon runs as root the thread
+//! drops to the logged-in user first, since a session bus only lets its own
+//! user in.
+//!
+//! A notification with an OSC 99 `i=` id replaces the earlier one with the
+//! same id from the same pane, and `p=close` from the application closes it.
+//! When the daemon reports the notification clicked or closed, the main loop
+//! gets a [`DesktopEvent`] carrying the OSC 99 report for the application
+//! (`a=report`, `c=1`).
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+
+use super::pty::UserAccount;
+use super::Notification;
+
+/// Notifications remembered for replacement and signals
+const MAX_TRACKED: usize = 256;
+
+/// Icon images are scaled down to this size before they are sent
+const MAX_ICON_PIXELS: u32 = 256;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, zbus::zvariant::Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    fn close_notification(&self, id: u32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: &str) -> zbus::Result<()>;
+}
+
+/// Something happened to a notification on the desktop
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopEvent {
+    /// Pane that sent the notification
+    pub pane: u16,
+    /// Focus the pane (the notification was clicked and wants focus)
+    pub focus: bool,
+    /// OSC 99 report to write to the pane's application
+    pub report: Option<String>,
+}
+
+/// A notification shown by the daemon
+struct Shown {
+    pane: u16,
+    id: Option<String>,
+    focus: bool,
+    activation_report: Option<String>,
+    close_report: Option<String>,
+}
+
+/// Request from the main thread
+enum Request {
+    Show(u16, Notification),
+    /// The application closed its notification (pane, OSC 99 id)
+    Close(u16, String),
+}
+
+/// Notifications shown so far (daemon id -> notification)
+#[derive(Default)]
+struct Tracker {
+    shown: HashMap<u32, Shown>,
+}
+
+impl Tracker {
+    /// Daemon id to replace for an OSC 99 id from `pane`
+    fn replaces(&self, pane: u16, id: Option<&str>) -> u32 {
+        let Some(id) = id else {
+            return 0;
+        };
+        self.shown
+            .iter()
+            .find(|(_, s)| s.pane == pane && s.id.as_deref() == Some(id))
+            .map_or(0, |(daemon_id, _)| *daemon_id)
+    }
+
+    fn insert(&mut self, daemon_id: u32, pane: u16, n: &Notification) {
+        if self.shown.len() >= MAX_TRACKED {
+            if let Some(oldest) = self.shown.keys().min().copied() {
+                self.shown.remove(&oldest);
+            }
+        }
+        self.shown.insert(
+            daemon_id,
+            Shown {
+                pane,
+                id: n.id.clone(),
+                focus: n.focus_on_activate,
+                activation_report: n.activation_report(),
+                close_report: n.close_report(),
+            },
+        );
+    }
+
+    /// ActionInvoked: the default action is a click on the notification
+    fn activated(&self, daemon_id: u32) -> Option<DesktopEvent> {
+        let shown = self.shown.get(&daemon_id)?;
+        Some(DesktopEvent {
+            pane: shown.pane,
+            focus: shown.focus,
+            report: shown.activation_report.clone(),
+        })
+    }
+
+    /// NotificationClosed (expired, dismissed or closed by a call)
+    fn closed(&mut self, daemon_id: u32) -> Option<DesktopEvent> {
+        let shown = self.shown.remove(&daemon_id)?;
+        shown.close_report.map(|report| DesktopEvent {
+            pane: shown.pane,
+            focus: false,
+            report: Some(report),
+        })
+    }
+}
+
+/// Desktop notification client
+///
+/// Held by the main thread; sends notifications and polls desktop events.
+pub struct DesktopNotifier {
+    tx: tokio::sync::mpsc::Sender<Request>,
+    events: mpsc::Receiver<DesktopEvent>,
+    /// D-Bus thread (ends when the notifier is dropped)
+    _thread: std::thread::JoinHandle<()>,
+}
+
+impl DesktopNotifier {
+    /// Start the D-Bus thread. `address` overrides the session bus; with
+    /// `user` the thread runs as that user (bcon running as root).
+    pub fn start(address: Option<String>, user: Option<UserAccount>) -> Result<Self> {
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        let (event_tx, events) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("bcon-notify".into())
+            .spawn(move || {
+                if let Some(user) = user {
+                    if let Err(e) = user.drop_thread_privileges() {
+                        warn!("Desktop notifications: cannot run as {}: {}", user.name, e);
+                        return;
+                    }
+                }
+                let rt = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        warn!(
+                            "Desktop notifications: failed to create tokio runtime: {}",
+                            e
+                        );
+                        return;
+                    }
+                };
+                rt.block_on(notify_main(address, rx, event_tx));
+            })
+            .map_err(|e| anyhow!("Failed to start notification thread: {}", e))?;
+        Ok(Self {
+            tx,
+            events,
+            _thread: thread,
+        })
+    }
+
+    /// Show a notification from `pane` (non-blocking, dropped if the thread is busy)
+    pub fn send(&self, pane: u16, notification: &Notification) {
+        if self
+            .tx
+            .try_send(Request::Show(pane, notification.clone()))
+            .is_err()
+        {
+            debug!("Desktop notifications: queue full, notification dropped");
+        }
+    }
+
+    /// Close the notification with OSC 99 id `id` from `pane` (OSC 99 p=close)
+    pub fn close(&self, pane: u16, id: &str) {
+        if self
+            .tx
+            .try_send(Request::Close(pane, id.to_string()))
+            .is_err()
+        {
+            debug!("Desktop notifications: queue full, close dropped");
+        }
+    }
+
+    /// Get all pending desktop events
+    pub fn poll_events(&self) -> Vec<DesktopEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Connection to the notification daemon with its signal streams
+struct Bus {
+    proxy: NotificationsProxy<'static>,
+    activated: ActionInvokedStream<'static>,
+    closed: NotificationClosedStream<'static>,
+}
+
+async fn connect(address: Option<&str>) -> Result<Bus> {
+    let connection = match address {
+        Some(address) => zbus::connection::Builder::address(address)?.build().await?,
+        None => zbus::Connection::session().await?,
+    };
+    let proxy = NotificationsProxy::new(&connection).await?;
+    let activated = proxy.receive_action_invoked().await?;
+    let closed = proxy.receive_notification_closed().await?;
+    Ok(Bus {
+        proxy,
+        activated,
+        closed,
+    })
+}
+
+/// Send one notification, returning the daemon's id
+async fn show(bus: &Bus, replaces: u32, n: &Notification) -> zbus::Result<u32> {
+    let mut hints = HashMap::from([("urgency", zbus::zvariant::Value::U8(n.urgency.min(2)))]);
+    if let Some(image) = n.icon_data.as_deref().and_then(|data| image_data(data)) {
+        hints.insert("image-data", image);
+    }
+    let app_icon = n.icon_names.first().map_or("", String::as_str);
+    bus.proxy
+        .notify(
+            "bcon",
+            replaces,
+            app_icon,
+            &n.title,
+            &n.body,
+            &["default", "Open"],
+            hints,
+            n.expire_ms,
+        )
+        .await
+}
+
+/// OSC 99 icon data (PNG / JPEG) as an `image-data` hint (iiibiiay)
+fn image_data(data: &[u8]) -> Option<zbus::zvariant::Value<'static>> {
+    let image = match image::load_from_memory(data) {
+        Ok(image) => image,
+        Err(e) => {
+            debug!("Desktop notifications: icon not decoded: {}", e);
+            return None;
+        }
+    };
+    let image = if image.width() > MAX_ICON_PIXELS || image.height() > MAX_ICON_PIXELS {
+        image.thumbnail(MAX_ICON_PIXELS, MAX_ICON_PIXELS)
+    } else {
+        image
+    };
+    let rgba = image.to_rgba8();
+    let (width, height) = (rgba.width() as i32, rgba.height() as i32);
+    Some(zbus::zvariant::Value::new((
+        width,
+        height,
+        width * 4,
+        true,
+        8i32,
+        4i32,
+        rgba.into_raw(),
+    )))
+}
+
+/// Handle one request from the main thread (false if the bus is lost)
+async fn handle(bus: &Bus, tracker: &mut Tracker, request: Request) -> bool {
+    match request {
+        Request::Show(pane, n) => {
+            match show(bus, tracker.replaces(pane, n.id.as_deref()), &n).await {
+                Ok(daemon_id) => tracker.insert(daemon_id, pane, &n),
+                Err(e) => {
+                    warn!("Desktop notifications: Notify failed: {}", e);
+                    return false;
+                }
+            }
+        }
+        Request::Close(pane, id) => {
+            // NotificationClosed follows and sends the close report
+            let daemon_id = tracker.replaces(pane, Some(&id));
+            if daemon_id != 0 {
+                if let Err(e) = bus.proxy.close_notification(daemon_id).await {
+                    warn!("Desktop notifications: CloseNotification failed: {}", e);
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Notification thread async main
+async fn notify_main(
+    address: Option<String>,
+    mut rx: tokio::sync::mpsc::Receiver<Request>,
+    event_tx: mpsc::Sender<DesktopEvent>,
+) {
+    let mut tracker = Tracker::default();
+    let mut bus: Option<Bus> = None;
+    loop {
+        let Some(connected) = bus.as_mut() else {
+            // Not connected: connect when the next notification arrives
+            let Some(request) = rx.recv().await else {
+                return;
+            };
+            if matches!(request, Request::Close(..)) {
+                // Nothing was shown on this connection
+                continue;
+            }
+            match connect(address.as_deref()).await {
+                Ok(connected) => {
+                    info!("Desktop notifications: connected to the notification daemon");
+                    handle(&connected, &mut tracker, request).await;
+                    bus = Some(connected);
+                }
+                Err(e) => warn!("Desktop notifications: no notification daemon: {}", e),
+            }
+            continue;
+        };
+
+        let mut lost = false;
+        tokio::select! {
+            request = rx.recv() => {
+                let Some(request) = request else {
+                    return;
+                };
+                lost = !handle(connected, &mut tracker, request).await;
+            }
+            signal = connected.activated.next() => match signal {
+                Some(signal) => match signal.args() {
+                    Ok(args) if args.action_key() == &"default" => {
+                        if let Some(event) = tracker.activated(*args.id()) {
+                            let _ = event_tx.send(event);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("ActionInvoked parse error: {}", e),
+                },
+                None => lost = true,
+            },
+            signal = connected.closed.next() => match signal {
+                Some(signal) => match signal.args() {
+                    Ok(args) => {
+                        if let Some(event) = tracker.closed(*args.id()) {
+                            let _ = event_tx.send(event);
+                        }
+                    }
+                    Err(e) => warn!("NotificationClosed parse error: {}", e),
+                },
+                None => lost = true,
+            },
+        }
+        if lost {
+            // Reconnect with the next notification
+            bus = None;
+        }
+    }
+}
+
+// Required to use next() on zbus SignalStream
+use futures_util::StreamExt;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, wait_for};
+    use std::io::BufRead;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// (replaces_id, app_icon, summary, urgency, expire_timeout) of each Notify call
+    type Calls = Arc<Mutex<Vec<(u32, String, String, u8, i32)>>>;
+
+    struct MockDaemon {
+        calls: Calls,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl MockDaemon {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            replaces_id: u32,
+            app_icon: &str,
+            summary: &str,
+            _body: &str,
+            _actions: Vec<String>,
+            hints: HashMap<String, zbus::zvariant::OwnedValue>,
+            expire_timeout: i32,
+        ) -> u32 {
+            let urgency = hints
+                .get("urgency")
+                .and_then(|v| u8::try_from(v).ok())
+                .unwrap_or(9);
+            let mut calls = self.calls.lock().unwrap();
+            calls.push((
+                replaces_id,
+                app_icon.into(),
+                summary.into(),
+                urgency,
+                expire_timeout,
+            ));
+            if replaces_id != 0 {
+                replaces_id
+            } else {
+                calls.len() as u32 + 100
+            }
+        }
+
+        async fn close_notification(
+            &self,
+            #[zbus(signal_context)] ctxt: zbus::SignalContext<'_>,
+            id: u32,
+        ) {
+            let _ = Self::notification_closed(&ctxt, id, 3).await;
+        }
+
+        #[zbus(signal)]
+        async fn notification_closed(
+            ctxt: &zbus::SignalContext<'_>,
+            id: u32,
+            reason: u32,
+        ) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn action_invoked(
+            ctxt: &zbus::SignalContext<'_>,
+            id: u32,
+            action_key: &str,
+        ) -> zbus::Result<()>;
+    }
+
+    fn notification(id: &str, title: &str) -> Notification {
+        let mut n = Notification::new(Some(id.to_string()), 2);
+        n.title = title.to_string();
+        n.body = "body".to_string();
+        n.report_activation = true;
+        n.report_close = true;
+        n.icon_names = vec!["utilities-terminal".to_string()];
+        n.expire_ms = 3000;
+        n
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon (cargo test -- --ignored)"]
+    fn test_mock_daemon() {
+        // Private bus
+        let socket = temp_path("notify");
+        let mut daemon = std::process::Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .arg(format!("--address=unix:path={}", socket))
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("dbus-daemon");
+        let mut address = String::new();
+        std::io::BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+        let address = address.trim().to_string();
+
+        // Mock notification daemon, emitting clicks on request
+        let calls = Calls::default();
+        let (click_tx, mut click_rx) = tokio::sync::mpsc::channel::<u32>(4);
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let mock_calls = calls.clone();
+        let mock_address = address.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            rt.block_on(async move {
+                let connection = zbus::connection::Builder::address(mock_address.as_str())
+                    .unwrap()
+                    .name("org.freedesktop.Notifications")
+                    .unwrap()
+                    .serve_at(
+                        "/org/freedesktop/Notifications",
+                        MockDaemon { calls: mock_calls },
+                    )
+                    .unwrap()
+                    .build()
+                    .await
+                    .unwrap();
+                ready_tx.send(()).unwrap();
+                let iface = connection
+                    .object_server()
+                    .interface::<_, MockDaemon>("/org/freedesktop/Notifications")
+                    .await
+                    .unwrap();
+                while let Some(id) = click_rx.recv().await {
+                    MockDaemon::action_invoked(iface.signal_context(), id, "default")
+                        .await
+                        .unwrap();
+                }
+            });
+        });
+        ready_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let notifier = DesktopNotifier::start(Some(address), None).unwrap();
+        notifier.send(3, &notification("build", "Building"));
+        wait_for(|| (calls.lock().unwrap().len() == 1).then_some(()));
+        // Same OSC 99 id from the same pane replaces it
+        notifier.send(3, &notification("build", "Done"));
+        wait_for(|| (calls.lock().unwrap().len() == 2).then_some(()));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                (0, "utilities-terminal".into(), "Building".into(), 2, 3000),
+                (101, "utilities-terminal".into(), "Done".into(), 2, 3000),
+            ]
+        );
+
+        click_tx.blocking_send(101).unwrap();
+        let events = wait_for(|| Some(notifier.poll_events()).filter(|e| !e.is_empty()));
+        assert_eq!(
+            events,
+            vec![DesktopEvent {
+                pane: 3,
+                focus: true,
+                report: Some("\x1b]99;i=build;\x1b\\".to_string()),
+            }]
+        );
+        // OSC 99 p=close from the application
+        notifier.close(3, "build");
+        let events = wait_for(|| Some(notifier.poll_events()).filter(|e| !e.is_empty()));
+        assert_eq!(
+            events[0].report.as_deref(),
+            Some("\x1b]99;i=build:p=close;\x1b\\")
+        );
+
+        drop(notifier);
+        let _ = daemon.kill();
+        let _ = daemon.wait();
+        let _ = std::fs::remove_file(&socket);
+    }
+}
@@ -0,0 +1,401 @@
+//! Scrollback export
+//!
+//! Renders the whole scrollback plus the screen as plain text (soft wraps
+//! joined), ANSI (SGR colors and OSC 8 links preserved) or self-contained
+//! HTML using the current palette. Text and ANSI are also available for the
+//! screen alone (remote control `get_text`).
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use super::grid::{Cell, CellAttrs, Color, Grid, Hyperlink};
+
+/// Rows oldest first (scrollback, if asked for, then screen), with their
+/// soft-wrap flag. Trailing blank screen rows are dropped.
+fn rows(grid: &Grid, scrollback: bool) -> Vec<(&[Cell], bool)> {
+    let first = if scrollback { 0 } else { grid.scrollback_len() };
+    let mut rows: Vec<(&[Cell], bool)> = (first..grid.scrollback_len())
+        .filter_map(|i| Some((grid.scrollback_row(i)?, grid.scrollback_row_wrapped(i))))
+        .chain((0..grid.rows()).map(|r| (grid.row_cells(r), grid.row_wrapped(r))))
+        .collect();
+    while rows
+        .last()
+        .is_some_and(|(cells, _)| cells.iter().all(is_blank))
+    {
+        rows.pop();
+    }
+    rows
+}
+
+/// A cell that a line can end with and lose nothing
+fn is_blank(cell: &Cell) -> bool {
+    (cell.grapheme.is_empty() || cell.grapheme == " ")
+        && cell.bg == Color::Default
+        && cell.hyperlink.is_none()
+        && !cell.attrs.contains(CellAttrs::INVERSE)
+}
+
+/// Cells worth writing: a hard line end drops its blank tail
+fn visible(cells: &[Cell], wrapped: bool) -> &[Cell] {
+    if wrapped {
+        return cells;
+    }
+    let end = cells
+        .iter()
+        .rposition(|c| !is_blank(c))
+        .map_or(0, |i| i + 1);
+    &cells[..end]
+}
+
+/// Text of a cell (continuation halves of wide characters are empty)
+fn cell_text(cell: &Cell) -> &str {
+    if cell.width == 0 {
+        ""
+    } else if cell.grapheme.is_empty() {
+        " "
+    } else {
+        cell.grapheme.as_str()
+    }
+}
+
+/// Plain text, soft-wrapped rows joined into their logical line
+pub fn to_text(grid: &Grid) -> String {
+    render_text(rows(grid, true))
+}
+
+/// Plain text of the screen only
+pub fn screen_to_text(grid: &Grid) -> String {
+    render_text(rows(grid, false))
+}
+
+fn render_text(rows: Vec<(&[Cell], bool)>) -> String {
+    let mut out = String::new();
+    for (cells, wrapped) in rows {
+        let line: String = visible(cells, wrapped).iter().map(cell_text).collect();
+        if wrapped {
+            out.push_str(&line);
+        } else {
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// SGR color parameters (`fg` selects 3x / 9x / 38 vs 4x / 10x / 48)
+fn sgr_color(out: &mut String, color: &Color, fg: bool) {
+    let base = if fg { 30 } else { 40 };
+    match *color {
+        Color::Default => {}
+        Color::Indexed(n) if n < 8 => {
+            let _ = write!(out, ";{}", base + n as u32);
+        }
+        Color::Indexed(n) if n < 16 => {
+            let _ = write!(out, ";{}", base + 60 + (n - 8) as u32);
+        }
+        Color::Indexed(n) => {
+            let _ = write!(out, ";{};5;{}", base + 8, n);
+        }
+        Color::Rgb(r, g, b) => {
+            let _ = write!(out, ";{};2;{};{};{}", base + 8, r, g, b);
+        }
+    }
+}
+
+/// Full SGR sequence for a cell's style (always starts from a reset)
+fn sgr(cell: &Cell) -> String {
+    let mut out = String::from("\x1b[0");
+    for (flag, code) in [
+        (CellAttrs::BOLD, 1),
+        (CellAttrs::DIM, 2),
+        (CellAttrs::ITALIC, 3),
+        (CellAttrs::UNDERLINE, 4),
+        (CellAttrs::BLINK, 5),
+        (CellAttrs::INVERSE, 7),
+        (CellAttrs::HIDDEN, 8),
+        (CellAttrs::STRIKE, 9),
+        (CellAttrs::OVERLINE, 53),
+    ] {
+        if cell.attrs.contains(flag) {
+            let _ = write!(out, ";{}", code);
+        }
+    }
+    sgr_color(&mut out, &cell.fg, true);
+    sgr_color(&mut out, &cell.bg, false);
+    out.push('m');
+    out
+}
+
+fn same_link(a: &Option<Arc<Hyperlink>>, b: &Option<Arc<Hyperlink>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Text with SGR attributes and OSC 8 links, replayable with `cat`
+pub fn to_ansi(grid: &Grid) -> String {
+    render_ansi(rows(grid, true))
+}
+
+/// ANSI text of the screen only
+pub fn screen_to_ansi(grid: &Grid) -> String {
+    render_ansi(rows(grid, false))
+}
+
+fn render_ansi(rows: Vec<(&[Cell], bool)>) -> String {
+    let mut out = String::new();
+    let mut style = String::from("\x1b[0m");
+    let mut link: Option<Arc<Hyperlink>> = None;
+    for (cells, wrapped) in rows {
+        for cell in visible(cells, wrapped).iter().filter(|c| c.width != 0) {
+            if !same_link(&link, &cell.hyperlink) {
+                if link.is_some() {
+                    out.push_str("\x1b]8;;\x1b\\");
+                }
+                if let Some(ref l) = cell.hyperlink {
+                    let params = l.id.as_deref().map(|id| format!("id={}", id));
+                    let _ = write!(out, "\x1b]8;{};{}\x1b\\", params.unwrap_or_default(), l.url);
+                }
+                link = cell.hyperlink.clone();
+            }
+            let cell_style = sgr(cell);
+            if cell_style != style {
+                out.push_str(&cell_style);
+                style = cell_style;
+            }
+            out.push_str(cell_text(cell));
+        }
+        if !wrapped {
+            // Lines stand alone: no style or link bleeds into the next one
+            if link.take().is_some() {
+                out.push_str("\x1b]8;;\x1b\\");
+            }
+            if style != "\x1b[0m" {
+                out.push_str("\x1b[0m");
+                style = String::from("\x1b[0m");
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn hex(rgb: (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2)
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+/// Schemes exported as links: anything else (javascript:, data:, ...) could
+/// run in the browser that opens the page, so it stays plain text
+fn html_linkable(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    ["http", "https", "file", "mailto"]
+        .iter()
+        .any(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Resolve a non-default cell color with the grid's palette (OSC 4 and config colors)
+fn resolve(grid: &Grid, color: &Color, fg: bool) -> Option<(u8, u8, u8)> {
+    if *color == Color::Default {
+        return None;
+    }
+    let c = grid.color_to_rgba(color, fg);
+    Some((
+        (c[0] * 255.0).round() as u8,
+        (c[1] * 255.0).round() as u8,
+        (c[2] * 255.0).round() as u8,
+    ))
+}
+
+/// Inline CSS for a cell (empty for default style)
+fn css(grid: &Grid, cell: &Cell, fg_default: (u8, u8, u8), bg_default: (u8, u8, u8)) -> String {
+    let mut fg = resolve(grid, &cell.fg, true);
+    let mut bg = resolve(grid, &cell.bg, false);
+    if cell.attrs.contains(CellAttrs::INVERSE) {
+        let new_fg = bg.unwrap_or(bg_default);
+        bg = Some(fg.unwrap_or(fg_default));
+        fg = Some(new_fg);
+    }
+    let mut style = String::new();
+    if cell.attrs.contains(CellAttrs::HIDDEN) {
+        style.push_str("color:transparent;");
+    } else if let Some(fg) = fg {
+        let _ = write!(style, "color:{};", hex(fg));
+    }
+    if let Some(bg) = bg {
+        let _ = write!(style, "background:{};", hex(bg));
+    }
+    if cell.attrs.contains(CellAttrs::BOLD) {
+        style.push_str("font-weight:bold;");
+    }
+    if cell.attrs.contains(CellAttrs::DIM) {
+        style.push_str("opacity:.6;");
+    }
+    if cell.attrs.contains(CellAttrs::ITALIC) {
+        style.push_str("font-style:italic;");
+    }
+    let decorations: Vec<&str> = [
+        (CellAttrs::UNDERLINE, "underline"),
+        (CellAttrs::STRIKE, "line-through"),
+        (CellAttrs::OVERLINE, "overline"),
+    ]
+    .iter()
+    .filter(|(flag, _)| cell.attrs.contains(*flag))
+    .map(|(_, name)| *name)
+    .collect();
+    if !decorations.is_empty() {
+        let _ = write!(style, "text-decoration:{};", decorations.join(" "));
+    }
+    style
+}
+
+/// Self-contained HTML page with the current colors and clickable links
+pub fn to_html(grid: &Grid, fg_default: (u8, u8, u8), bg_default: (u8, u8, u8)) -> String {
+    let fg_default = grid.osc_fg_color().unwrap_or(fg_default);
+    let bg_default = grid.osc_bg_color().unwrap_or(bg_default);
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>bcon scrollback</title>\n\
+         <style>\nbody {{ margin: 0; background: {bg}; color: {fg}; }}\n\
+         pre {{ margin: 0; padding: 8px; font-family: monospace; white-space: pre-wrap; }}\n\
+         a {{ color: inherit; }}\n</style>\n</head>\n<body>\n<pre>",
+        bg = hex(bg_default),
+        fg = hex(fg_default),
+    );
+
+    // Open run: (style, link)
+    let mut open: Option<(String, Option<Arc<Hyperlink>>)> = None;
+    let close = |out: &mut String, open: &mut Option<(String, Option<Arc<Hyperlink>>)>| {
+        if let Some((style, link)) = open.take() {
+            if !style.is_empty() {
+                out.push_str("</span>");
+            }
+            if link.is_some() {
+                out.push_str("</a>");
+            }
+        }
+    };
+    for (cells, wrapped) in rows(grid, true) {
+        for cell in visible(cells, wrapped).iter().filter(|c| c.width != 0) {
+            let style = css(grid, cell, fg_default, bg_default);
+            let link = cell
+                .hyperlink
+                .clone()
+                .filter(|link| html_linkable(&link.url));
+            let same = open
+                .as_ref()
+                .is_some_and(|(s, l)| *s == style && same_link(l, &link));
+            if !same {
+                close(&mut out, &mut open);
+                if let Some(ref link) = link {
+                    out.push_str("<a href=\"");
+                    escape_html(&link.url, &mut out);
+                    out.push_str("\">");
+                }
+                if !style.is_empty() {
+                    let _ = write!(out, "<span style=\"{}\">", style);
+                }
+                open = Some((style, link));
+            }
+            escape_html(cell_text(cell), &mut out);
+        }
+        if !wrapped {
+            close(&mut out, &mut open);
+            out.push('\n');
+        }
+    }
+    close(&mut out, &mut open);
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_with(text: &str) -> Grid {
+        let mut grid = Grid::with_scrollback(10, 3, 100);
+        for ch in text.chars() {
+            match ch {
+                '\n' => {
+                    grid.carriage_return();
+                    grid.linefeed();
+                }
+                _ => grid.put_char(ch),
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_text_joins_wraps() {
+        // "0123456789abc" wraps at 10 columns; 4 lines push one into scrollback
+        let grid = grid_with("first\n0123456789abc\nlast\n");
+        assert_eq!(to_text(&grid), "first\n0123456789abc\nlast\n");
+        // The screen starts on the continuation row
+        assert_eq!(screen_to_text(&grid), "abc\nlast\n");
+    }
+
+    #[test]
+    fn test_ansi_and_html_styles() {
+        let mut grid = grid_with("a");
+        grid.set_fg(Color::Indexed(1));
+        grid.set_attr(CellAttrs::BOLD);
+        grid.put_char('<');
+        grid.reset_attrs();
+        grid.current_hyperlink = Some(Arc::new(Hyperlink {
+            id: None,
+            url: "https://x.test/?a&b".to_string(),
+        }));
+        grid.put_char('L');
+        grid.current_hyperlink = None;
+
+        assert_eq!(
+            to_ansi(&grid),
+            "a\x1b[0;1;31m<\x1b]8;;https://x.test/?a&b\x1b\\\x1b[0mL\x1b]8;;\x1b\\\n"
+        );
+
+        let html = to_html(&grid, (255, 255, 255), (0, 0, 0));
+        assert!(html.contains(
+            "<pre>a<span style=\"color:#cd0000;font-weight:bold;\">&lt;</span>\
+             <a href=\"https://x.test/?a&amp;b\">L</a>\n</pre>"
+        ));
+        assert!(html.contains("background: #000000; color: #ffffff;"));
+    }
+
+    #[test]
+    fn test_html_unsafe_link_is_text() {
+        let mut grid = grid_with("");
+        grid.current_hyperlink = Some(Arc::new(Hyperlink {
+            id: None,
+            url: "JavaScript:alert(1)".to_string(),
+        }));
+        grid.put_char('J');
+        grid.current_hyperlink = Some(Arc::new(Hyperlink {
+            id: None,
+            url: "MAILTO:a@x.test".to_string(),
+        }));
+        grid.put_char('M');
+        grid.current_hyperlink = None;
+
+        let html = to_html(&grid, (255, 255, 255), (0, 0, 0));
+        assert!(html.contains("<pre>J<a href=\"MAILTO:a@x.test\">M</a>\n</pre>"));
+        assert!(!html.contains("alert"));
+    }
+}
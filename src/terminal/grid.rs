@@ -44,6 +44,8 @@ use unicode_width::UnicodeWidthChar;
 
 use crate::font::emoji::is_emoji_presentation;
 
+use super::search::ScrollbackIndex;
+
 /// Convert char to SmolStr efficiently
 /// For ASCII (1 byte), uses inline storage directly
 #[inline]
@@ -74,6 +76,17 @@ pub struct Hyperlink {
     pub url: String,
 }
 
+impl Hyperlink {
+    /// Whether two cells belong to the same link: same `id=` and URL, or
+    /// the same OSC 8 span when no id was given
+    pub fn same_link(a: &Arc<Hyperlink>, b: &Arc<Hyperlink>) -> bool {
+        match (&a.id, &b.id) {
+            (Some(x), Some(y)) => x == y && a.url == b.url,
+            _ => Arc::ptr_eq(a, b),
+        }
+    }
+}
+
 /// Maximum scrollback lines
 const MAX_SCROLLBACK: usize = 10000;
 
@@ -493,6 +506,10 @@ pub struct ShellState {
     pub command_row: Option<usize>,
     /// Last command exit code
     pub last_exit_code: Option<i32>,
+    /// Number of finished commands (OSC 133;D)
+    pub commands_finished: u64,
+    /// When the running command started (OSC 133;C)
+    pub command_started: Option<std::time::Instant>,
 }
 
 /// Keyboard protocol state
@@ -570,6 +587,8 @@ pub struct Grid {
     scrollback: VecDeque<Vec<Cell>>,
     /// Scrollback wrapped flags (true = line continues to the next)
     scrollback_wrapped: VecDeque<bool>,
+    /// Scrollback text for search (kept in step with `scrollback`)
+    search_index: ScrollbackIndex,
     /// Maximum scrollback lines
     pub max_scrollback: usize,
     /// Saved cursor position
@@ -586,6 +605,8 @@ pub struct Grid {
     alternate_screen: Option<AlternateScreen>,
     /// Bell notification flag (reset after drawing)
     pub bell_triggered: bool,
+    /// Number of bells received
+    pub bell_count: u64,
     /// Current hyperlink (OSC 8)
     pub current_hyperlink: Option<Arc<Hyperlink>>,
     /// Image placement list
@@ -702,6 +723,7 @@ impl Grid {
             pen: Pen::default(),
             scrollback: VecDeque::new(),
             scrollback_wrapped: VecDeque::new(),
+            search_index: ScrollbackIndex::default(),
             max_scrollback,
             saved_cursor: None,
             last_char: ' ',
@@ -710,6 +732,7 @@ impl Grid {
             in_zwj_sequence: false,
             alternate_screen: None,
             bell_triggered: false,
+            bell_count: 0,
             current_hyperlink: None,
             image_placements: Vec::new(),
             scrollback_total: 0,
@@ -774,7 +797,8 @@ impl Grid {
         self.rows
     }
 
-    /// Cumulative scroll count for absolute image positioning
+    /// Cumulative scroll count for absolute positioning (images, selection).
+    /// Screen row `r` is absolute line `scrollback_total + r`.
     pub fn scrollback_total(&self) -> u64 {
         self.scrollback_total
     }
@@ -1594,10 +1618,11 @@ impl Grid {
                     .unwrap_or_else(|| Vec::with_capacity(self.cols));
                 row_cells.clear();
                 row_cells.extend_from_slice(&self.cells[start..start + self.cols]);
-                self.scrollback.push_back(row_cells);
                 // Save wrapped flag for this row
-                self.scrollback_wrapped
-                    .push_back(self.wrapped_lines.get(i).copied().unwrap_or(false));
+                let wrapped = self.wrapped_lines.get(i).copied().unwrap_or(false);
+                self.search_index.push(&row_cells, wrapped);
+                self.scrollback.push_back(row_cells);
+                self.scrollback_wrapped.push_back(wrapped);
             }
             // Return evicted rows to pool for reuse
             while self.scrollback.len() > self.max_scrollback {
@@ -1608,6 +1633,7 @@ impl Grid {
                     }
                 }
                 self.scrollback_wrapped.pop_front();
+                self.search_index.pop_front();
             }
         }
 
@@ -1683,6 +1709,52 @@ impl Grid {
         self.scrollback.get(idx).map(|v| v.as_slice())
     }
 
+    /// Searchable scrollback text, built on first use
+    pub fn search_index(&self) -> &ScrollbackIndex {
+        if !self.search_index.is_built() {
+            self.search_index.build(
+                (self.scrollback_total - self.scrollback.len() as u64) as usize,
+                self.scrollback
+                    .iter()
+                    .zip(self.scrollback_wrapped.iter())
+                    .map(|(row, &wrapped)| (row.as_slice(), wrapped)),
+            );
+        }
+        &self.search_index
+    }
+
+    /// RIS: start over with an empty grid of the same size. The search index
+    /// is kept and emptied, so running searches see a new generation, and
+    /// line numbering continues past the old screen. The bell and command
+    /// counters keep counting, so event watchers see no spurious change.
+    pub fn full_reset(&mut self) {
+        let index = self.search_index.clone();
+        let scrollback_total = self.scrollback_total + self.rows as u64;
+        let (bell_count, commands_finished) = (self.bell_count, self.shell.commands_finished);
+        *self = Grid::with_scrollback(self.cols(), self.rows(), self.max_scrollback);
+        self.scrollback_total = scrollback_total;
+        self.bell_count = bell_count;
+        self.shell.commands_finished = commands_finished;
+        index.rebuild(scrollback_total as usize, std::iter::empty());
+        self.search_index = index;
+    }
+
+    /// Whether a scrollback line soft-wraps into the next line
+    pub fn scrollback_row_wrapped(&self, idx: usize) -> bool {
+        self.scrollback_wrapped.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Whether a screen row soft-wraps into the next row
+    pub fn row_wrapped(&self, row: usize) -> bool {
+        self.wrapped_lines.get(row).copied().unwrap_or(false)
+    }
+
+    /// Cells of a screen row
+    pub fn row_cells(&self, row: usize) -> &[Cell] {
+        let start = row * self.cols;
+        self.cells.get(start..start + self.cols).unwrap_or(&[])
+    }
+
     // ========== Control characters ==========
 
     /// Line feed (LF)
@@ -2778,6 +2850,16 @@ impl Grid {
             self.scrollback.pop_front();
             self.scrollback_wrapped.pop_front();
         }
+        // Absolute lines start at scrollback_total - scrollback_len; narrower
+        // reflow can add scrollback rows without any scroll
+        self.scrollback_total = self.scrollback_total.max(self.scrollback.len() as u64);
+        self.search_index.rebuild(
+            (self.scrollback_total - self.scrollback.len() as u64) as usize,
+            self.scrollback
+                .iter()
+                .zip(self.scrollback_wrapped.iter())
+                .map(|(row, &wrapped)| (row.as_slice(), wrapped)),
+        );
 
         // Screen
         self.cells = vec![Cell::default(); new_cols * new_rows];
@@ -3115,6 +3197,62 @@ mod tests {
         assert_eq!(g.image_placements.len(), 1);
     }
 
+    #[test]
+    fn full_reset_keeps_search_index() {
+        let mut g = Grid::with_scrollback(10, 2, 100);
+        for _ in 0..5 {
+            g.put_char('x');
+            g.carriage_return();
+            g.linefeed();
+        }
+        let index = g.search_index().clone();
+        let (generation, first_line) = (index.generation(), index.first_line());
+        g.bell_count = 3;
+        g.full_reset();
+        assert_eq!(g.bell_count, 3);
+        assert_eq!(g.scrollback_len(), 0);
+        // Same index, new generation, numbering moved past the old rows
+        assert_eq!(g.search_index().generation(), generation + 1);
+        assert_eq!(index.generation(), generation + 1);
+        assert!(index.first_line() > first_line);
+        assert_eq!(index.first_line() as u64, g.scrollback_total());
+    }
+
+    #[test]
+    fn search_index_built_on_first_use() {
+        let mut g = Grid::with_scrollback(10, 2, 100);
+        for _ in 0..5 {
+            g.put_char('x');
+            g.carriage_return();
+            g.linefeed();
+        }
+        // Scrolled rows are not copied until a search needs them
+        assert!(!g.search_index.is_built());
+        let index = g.search_index().clone();
+        assert!(index.is_built());
+        let first_line = g.scrollback_total() - g.scrollback_len() as u64;
+        assert_eq!(index.first_line() as u64, first_line);
+    }
+
+    #[test]
+    fn reflow_keeps_search_index_numbering() {
+        let mut g = Grid::with_scrollback(10, 2, 100);
+        for _ in 0..5 {
+            for _ in 0..8 {
+                g.put_char('x');
+            }
+            g.carriage_return();
+            g.linefeed();
+        }
+        // Narrower: every line wraps, adding scrollback rows without a scroll
+        g.resize(4, 2);
+        let first_line = g.scrollback_total() - g.scrollback_len() as u64;
+        assert_eq!(g.search_index().first_line() as u64, first_line);
+        g.resize(10, 2);
+        let first_line = g.scrollback_total() - g.scrollback_len() as u64;
+        assert_eq!(g.search_index().first_line() as u64, first_line);
+    }
+
     #[test]
     fn overlay_dedup_same_position() {
         let mut g = make_grid();
@@ -0,0 +1,331 @@
+//! Hints (quick-select) mode
+//!
+//! Scans the visible screen for URLs, file paths, git hashes, IP addresses,
+//! UUIDs and user-defined patterns, and labels each match with a short
+//! letter sequence. Typing a label selects the match.
+
+use regex::Regex;
+
+use super::Terminal;
+
+/// Built-in patterns, in priority order (earlier wins when matches start
+/// at the same column)
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    ("url", r#"(?:https?|ftp|file)://[^\s<>"'`]+"#),
+    (
+        "uuid",
+        r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+    ),
+    ("ipv4", r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d+)?\b"),
+    ("ipv6", r"\b(?:[0-9a-fA-F]{1,4}:){3,7}[0-9a-fA-F]{1,4}\b"),
+    (
+        "path",
+        r"(?:~|\.{1,2}|[\w.+@-]+)?(?:/[\w.+@-]+)+(?::\d+(?::\d+)?)?|[\w.+-]+\.\w+:\d+(?::\d+)?",
+    ),
+    ("git_hash", r"\b[0-9a-f]{7,40}\b"),
+];
+
+/// Compiled hint patterns
+#[derive(Debug, Clone, Default)]
+pub struct HintPatterns {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl HintPatterns {
+    /// Compile user patterns (highest priority) followed by the built-ins.
+    /// Invalid user patterns are logged and skipped.
+    pub fn new(user: &[(String, String)], builtin: bool) -> Self {
+        let mut patterns = Vec::new();
+        for (name, re) in user {
+            match Regex::new(re) {
+                Ok(re) => patterns.push((name.clone(), re)),
+                Err(e) => log::warn!("Invalid hint pattern '{}': {}", name, e),
+            }
+        }
+        if builtin {
+            for (name, re) in BUILTIN_PATTERNS {
+                patterns.push((name.to_string(), Regex::new(re).expect("builtin hint pattern")));
+            }
+        }
+        Self { patterns }
+    }
+}
+
+/// A labelled match on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct HintMatch {
+    /// Display row
+    pub row: usize,
+    /// First column (inclusive)
+    pub start_col: usize,
+    /// Last column (exclusive)
+    pub end_col: usize,
+    /// Matched text (link target for OSC 8 hyperlinks)
+    pub text: String,
+    /// Pattern name ("url", "path", "hyperlink", ...)
+    pub kind: String,
+    /// Label to type
+    pub label: String,
+}
+
+/// Result of typing a key in hints mode
+#[derive(Debug, Clone, PartialEq)]
+pub enum HintInput {
+    /// Label completed
+    Selected(HintMatch),
+    /// Prefix of one or more labels, keep going
+    Pending,
+    /// No label starts with the typed keys
+    NoMatch,
+}
+
+/// Hints mode state
+pub struct HintState {
+    pub matches: Vec<HintMatch>,
+    /// Keys typed so far
+    pub typed: String,
+}
+
+impl HintState {
+    /// Add a typed key and check for a completed label
+    pub fn push_key(&mut self, ch: char) -> HintInput {
+        self.typed.push(ch.to_ascii_lowercase());
+        if let Some(m) = self.matches.iter().find(|m| m.label == self.typed) {
+            return HintInput::Selected(m.clone());
+        }
+        if self.matches.iter().any(|m| m.label.starts_with(&self.typed)) {
+            HintInput::Pending
+        } else {
+            self.typed.pop();
+            HintInput::NoMatch
+        }
+    }
+
+    /// Remove the last typed key
+    pub fn pop_key(&mut self) {
+        self.typed.pop();
+    }
+}
+
+/// Generate `n` prefix-free labels from `alphabet`.
+/// All labels have the same length, the shortest that fits. Labels are
+/// lowercase, like the keys `push_key` compares them with.
+pub fn make_labels(n: usize, alphabet: &str) -> Vec<String> {
+    let mut chars: Vec<char> = Vec::new();
+    for ch in alphabet.chars().map(|c| c.to_ascii_lowercase()) {
+        if !chars.contains(&ch) {
+            chars.push(ch);
+        }
+    }
+    if chars.len() < 2 || n == 0 {
+        return Vec::new();
+    }
+    let mut len = 1;
+    let mut capacity = chars.len();
+    while capacity < n {
+        len += 1;
+        capacity *= chars.len();
+    }
+    (0..n)
+        .map(|mut i| {
+            let mut label = vec![chars[0]; len];
+            for slot in label.iter_mut().rev() {
+                *slot = chars[i % chars.len()];
+                i /= chars.len();
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Trim punctuation that usually ends a sentence rather than a URL/path
+pub(super) fn trim_match(text: &str) -> &str {
+    let mut s = text;
+    loop {
+        let Some(last) = s.chars().last() else {
+            return s;
+        };
+        let unbalanced = |open: char, close: char| {
+            last == close && s.matches(open).count() < s.matches(close).count()
+        };
+        if ".,:;!?'\"".contains(last) || unbalanced('(', ')') || unbalanced('[', ']') {
+            s = &s[..s.len() - last.len_utf8()];
+        } else {
+            return s;
+        }
+    }
+}
+
+impl Terminal {
+    /// Enter hints mode: scan the visible screen and label matches.
+    /// Returns false (and stays out of hints mode) if nothing matched.
+    pub fn start_hints(&mut self, patterns: &HintPatterns, alphabet: &str) -> bool {
+        let cols = self.grid.cols();
+        let rows = self.grid.rows();
+        let mut matches: Vec<HintMatch> = Vec::new();
+
+        for row in 0..rows {
+            // Candidates: (start_col, end_col, priority, kind, text)
+            let mut candidates: Vec<(usize, usize, usize, String, String)> = Vec::new();
+
+            // OSC 8 hyperlinks first
+            let mut col = 0;
+            while col < cols {
+                let link = self.display_cell(row, col).hyperlink.clone();
+                match link {
+                    Some(link) => {
+                        let start = col;
+                        while col < cols
+                            && self
+                                .display_cell(row, col)
+                                .hyperlink
+                                .as_ref()
+                                .is_some_and(|l| l.url == link.url)
+                        {
+                            col += 1;
+                        }
+                        candidates.push((start, col, 0, "hyperlink".to_string(), link.url.clone()));
+                    }
+                    None => col += 1,
+                }
+            }
+
+            // Line text with byte offset -> column mapping
+            let mut line = String::new();
+            let mut byte_col: Vec<usize> = Vec::with_capacity(cols + 1);
+            for col in 0..cols {
+                let cell = self.display_cell(row, col);
+                if cell.width == 0 {
+                    continue;
+                }
+                let g = if cell.grapheme.is_empty() { " " } else { cell.grapheme.as_str() };
+                for _ in 0..g.len() {
+                    byte_col.push(col);
+                }
+                line.push_str(g);
+            }
+            byte_col.push(cols);
+
+            for (priority, (kind, re)) in patterns.patterns.iter().enumerate() {
+                for m in re.find_iter(&line) {
+                    let text = trim_match(m.as_str());
+                    if text.is_empty() {
+                        continue;
+                    }
+                    // Plain numbers are not git hashes
+                    if kind == "git_hash" && !text.bytes().any(|b| b.is_ascii_alphabetic()) {
+                        continue;
+                    }
+                    let start = byte_col[m.start()];
+                    let end = byte_col[m.start() + text.len()];
+                    candidates.push((start, end, priority + 1, kind.clone(), text.to_string()));
+                }
+            }
+
+            // Non-overlapping, leftmost first, highest priority on ties
+            candidates.sort_by_key(|c| (c.0, c.2));
+            let mut last_end = 0;
+            for (start, end, _, kind, text) in candidates {
+                if start < last_end || start >= end {
+                    continue;
+                }
+                last_end = end;
+                matches.push(HintMatch {
+                    row,
+                    start_col: start,
+                    end_col: end,
+                    text,
+                    kind,
+                    label: String::new(),
+                });
+            }
+        }
+
+        if matches.is_empty() {
+            return false;
+        }
+
+        // Matches nearest the bottom (most recent output) get the first labels
+        let labels = make_labels(matches.len(), alphabet);
+        if labels.is_empty() {
+            return false;
+        }
+        for (m, label) in matches.iter_mut().rev().zip(labels) {
+            m.label = label;
+        }
+
+        self.hints = Some(HintState {
+            matches,
+            typed: String::new(),
+        });
+        self.grid.mark_all_dirty();
+        true
+    }
+
+    /// Leave hints mode
+    pub fn end_hints(&mut self) {
+        if self.hints.take().is_some() {
+            self.grid.mark_all_dirty();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_labels() {
+        assert_eq!(make_labels(3, "asdf"), vec!["a", "s", "d"]);
+        let labels = make_labels(5, "ab");
+        assert_eq!(labels, vec!["aaa", "aab", "aba", "abb", "baa"]);
+        assert!(make_labels(3, "a").is_empty());
+        // Uppercase alphabets give labels that typed keys can match
+        assert_eq!(make_labels(3, "ASaD"), vec!["a", "s", "d"]);
+    }
+
+    #[test]
+    fn test_trim_match() {
+        assert_eq!(trim_match("https://example.com/a."), "https://example.com/a");
+        assert_eq!(trim_match("https://e.com/wiki/Foo_(bar)"), "https://e.com/wiki/Foo_(bar)");
+        assert_eq!(trim_match("https://e.com/x)"), "https://e.com/x");
+        assert_eq!(trim_match("src/main.rs:10:5:"), "src/main.rs:10:5");
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        let patterns = HintPatterns::new(&[], true);
+        let find = |kind: &str, text: &str| {
+            let (_, re) = patterns.patterns.iter().find(|(k, _)| k == kind).unwrap();
+            re.find(text).map(|m| m.as_str().to_string())
+        };
+        assert_eq!(
+            find("path", "error at src/main.rs:120:9 here").as_deref(),
+            Some("src/main.rs:120:9")
+        );
+        assert_eq!(find("path", "see ~/notes.txt").as_deref(), Some("~/notes.txt"));
+        assert_eq!(find("path", "lib.rs:42").as_deref(), Some("lib.rs:42"));
+        assert_eq!(find("ipv4", "ping 192.168.0.1").as_deref(), Some("192.168.0.1"));
+        assert_eq!(find("git_hash", "commit 4224703abc").as_deref(), Some("4224703abc"));
+        assert!(find("uuid", "id 123e4567-e89b-12d3-a456-426614174000").is_some());
+    }
+
+    #[test]
+    fn test_push_key() {
+        let m = |label: &str| HintMatch {
+            row: 0,
+            start_col: 0,
+            end_col: 1,
+            text: label.to_string(),
+            kind: "url".to_string(),
+            label: label.to_string(),
+        };
+        let mut state = HintState {
+            matches: vec![m("aa"), m("as")],
+            typed: String::new(),
+        };
+        assert_eq!(state.push_key('x'), HintInput::NoMatch);
+        assert_eq!(state.push_key('a'), HintInput::Pending);
+        assert_eq!(state.push_key('S'), HintInput::Selected(m("as")));
+    }
+}
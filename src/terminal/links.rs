@@ -0,0 +1,199 @@
+//! Link targets and handler command lines
+//!
+//! Turns an OSC 8 URI, a detected URL or a hinted path (`src/main.rs:10:5`)
+//! into a `LinkTarget`, and expands handler command templates with
+//! shell-quoted placeholders. Link text comes from terminal output, so every
+//! substituted value is quoted before it reaches `sh -c`.
+
+/// A link to open
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkTarget {
+    /// Full URL (plain paths become `file://` URLs)
+    pub url: String,
+    /// URL scheme in lowercase ("https", "file", ...)
+    pub scheme: String,
+    /// Local path (file links only)
+    pub path: Option<String>,
+    /// Line number from a `:line[:col]` suffix
+    pub line: Option<u32>,
+    /// Column from a `:line:col` suffix
+    pub col: Option<u32>,
+}
+
+impl LinkTarget {
+    /// Parse a URL or a plain path. Relative paths are resolved against `cwd`,
+    /// `~/` against the user's `home`.
+    pub fn parse(text: &str, cwd: Option<&str>, home: Option<&str>) -> Self {
+        if let Some((scheme, rest)) = text.split_once("://") {
+            let scheme = scheme.to_ascii_lowercase();
+            if scheme != "file" {
+                return Self {
+                    url: text.to_string(),
+                    scheme,
+                    path: None,
+                    line: None,
+                    col: None,
+                };
+            }
+            // file://host/path: drop the host part
+            let path = rest.find('/').map(|pos| &rest[pos..]).unwrap_or("/");
+            let (path, line, col) = split_line_col(&percent_decode(path));
+            return Self {
+                url: text.to_string(),
+                scheme,
+                path: Some(path),
+                line,
+                col,
+            };
+        }
+
+        let (path, line, col) = split_line_col(text);
+        let path = if let Some(rest) = path.strip_prefix("~/") {
+            match home {
+                Some(home) => format!("{}/{}", home.trim_end_matches('/'), rest),
+                None => path,
+            }
+        } else if path.starts_with('/') {
+            path
+        } else {
+            match cwd {
+                Some(dir) => format!("{}/{}", dir.trim_end_matches('/'), path),
+                None => path,
+            }
+        };
+        Self {
+            url: format!("file://{}", path),
+            scheme: "file".to_string(),
+            path: Some(path),
+            line,
+            col,
+        }
+    }
+}
+
+/// Split a trailing `:line` or `:line:col` off a path
+fn split_line_col(text: &str) -> (String, Option<u32>, Option<u32>) {
+    let mut parts = text.rsplitn(3, ':');
+    let last = parts.next().unwrap_or("");
+    let mid = parts.next();
+    let first = parts.next();
+    match (first, mid.map(str::parse::<u32>), last.parse::<u32>()) {
+        (Some(path), Some(Ok(line)), Ok(col)) => (path.to_string(), Some(line), Some(col)),
+        (_, _, Ok(line)) if mid.is_some() => {
+            let path = &text[..text.len() - last.len() - 1];
+            (path.to_string(), Some(line), None)
+        }
+        _ => (text.to_string(), None, None),
+    }
+}
+
+/// Decode %XX escapes (invalid sequences are kept as-is)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(Ok(b)) = s.get(i + 1..i + 3).map(|h| u8::from_str_radix(h, 16)) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Quote a string for `sh -c`
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Expand `{url}`, `{path}`, `{line}` and `{col}` in a handler command.
+/// Without any placeholder the quoted URL is appended.
+pub fn expand_command(template: &str, target: &LinkTarget) -> String {
+    let has_placeholder = ["{url}", "{path}", "{line}", "{col}"]
+        .iter()
+        .any(|p| template.contains(p));
+    if !has_placeholder {
+        return format!("{} {}", template, shell_quote(&target.url));
+    }
+    let path = target.path.as_deref().unwrap_or(&target.url);
+    // One pass over the template: inserted text is never scanned again
+    let mut command = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = if let Some(after) = rest.strip_prefix("{url}") {
+            rest = after;
+            shell_quote(&target.url)
+        } else if let Some(after) = rest.strip_prefix("{path}") {
+            rest = after;
+            shell_quote(path)
+        } else if let Some(after) = rest.strip_prefix("{line}") {
+            rest = after;
+            target.line.unwrap_or(1).to_string()
+        } else if let Some(after) = rest.strip_prefix("{col}") {
+            rest = after;
+            target.col.unwrap_or(1).to_string()
+        } else {
+            rest = &rest[1..];
+            "{".to_string()
+        };
+        command.push_str(&value);
+    }
+    command.push_str(rest);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        let t = LinkTarget::parse("https://example.com/a?b=c", None, None);
+        assert_eq!(t.scheme, "https");
+        assert_eq!(t.path, None);
+
+        let t = LinkTarget::parse("file://host/home/me/My%20File.rs:12:3", None, None);
+        assert_eq!(t.scheme, "file");
+        assert_eq!(t.path.as_deref(), Some("/home/me/My File.rs"));
+        assert_eq!((t.line, t.col), (Some(12), Some(3)));
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let t = LinkTarget::parse("src/main.rs:10", Some("/work/bcon/"), None);
+        assert_eq!(t.path.as_deref(), Some("/work/bcon/src/main.rs"));
+        assert_eq!((t.line, t.col), (Some(10), None));
+        assert_eq!(t.url, "file:///work/bcon/src/main.rs");
+
+        let t = LinkTarget::parse("/etc/hosts", Some("/tmp"), None);
+        assert_eq!(t.path.as_deref(), Some("/etc/hosts"));
+        assert_eq!(t.line, None);
+
+        let t = LinkTarget::parse("~/notes.txt", None, Some("/home/me"));
+        assert_eq!(t.path.as_deref(), Some("/home/me/notes.txt"));
+    }
+
+    #[test]
+    fn test_expand_command() {
+        let t = LinkTarget::parse("/tmp/it's.txt:7", None, None);
+        assert_eq!(
+            expand_command("${EDITOR:-vi} +{line} {path}", &t),
+            r"${EDITOR:-vi} +7 '/tmp/it'\''s.txt'"
+        );
+        let t = LinkTarget::parse("https://e.com/$(reboot)", None, None);
+        assert_eq!(expand_command("xdg-open", &t), "xdg-open 'https://e.com/$(reboot)'");
+        // Placeholders inside the link are not expanded again
+        let t = LinkTarget::parse("https://e/{path};echo PWNED;#", None, None);
+        assert_eq!(
+            expand_command("w3m {url} {line}", &t),
+            "w3m 'https://e/{path};echo PWNED;#' 1"
+        );
+    }
+}
@@ -5,17 +5,31 @@
 
 #![allow(dead_code)]
 
+pub mod clipboard;
+pub mod clipboard_broker;
 pub mod grid;
+pub mod copy_mode;
+pub mod desktop_notify;
+pub mod export;
+pub mod hints;
+pub mod links;
 pub mod kitty;
 pub mod parser;
+pub mod paste;
 pub mod pty;
+pub mod search;
 pub mod sixel;
+pub mod smart_selection;
 
 use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use log::{info, trace};
 
+use clipboard::{ClipSource, ClipboardHistory, SharedClipboardHistory};
+use clipboard_broker::ClipboardBroker;
 use grid::{Cell, Grid};
 use kitty::KittyDecoder;
 use parser::Performer;
@@ -28,6 +42,17 @@ const READ_BUF_SIZE: usize = 262144; // 256KB — one sixel frame fits in ~2 rea
 /// Maximum notification history entries
 const MAX_NOTIFICATIONS: usize = 100;
 
+/// Maximum finished command history entries
+const MAX_FINISHED_COMMANDS: usize = 100;
+
+/// Command finished (OSC 133;D)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedCommand {
+    pub exit_code: Option<i32>,
+    /// How long it ran (OSC 133;C to D)
+    pub duration: Option<std::time::Duration>,
+}
+
 /// Notification from OSC 9 / OSC 99
 #[derive(Debug, Clone)]
 pub struct Notification {
@@ -39,10 +64,89 @@ pub struct Notification {
     pub body: String,
     /// Urgency: 0=low, 1=normal, 2=critical
     pub urgency: u8,
+    /// Focus the pane when activated (OSC 99 a=focus, default on)
+    pub focus_on_activate: bool,
+    /// Tell the application when activated (OSC 99 a=report)
+    pub report_activation: bool,
+    /// Tell the application when closed (OSC 99 c=1)
+    pub report_close: bool,
+    /// When to show it (OSC 99 o=)
+    pub occasion: NotifyOccasion,
+    /// Milliseconds until it closes by itself (OSC 99 w=; -1 = default, 0 = never)
+    pub expire_ms: i32,
+    /// Icon names (OSC 99 n=), e.g. "dialog-information"
+    pub icon_names: Vec<String>,
+    /// Icon image data, PNG or JPEG (OSC 99 p=icon, or cached by g=)
+    pub icon_data: Option<std::sync::Arc<Vec<u8>>>,
+    /// Icon cache identifier (OSC 99 g=)
+    pub icon_cache_id: Option<String>,
     /// When the notification was received
     pub timestamp: std::time::Instant,
 }
 
+impl Notification {
+    pub fn new(id: Option<String>, urgency: u8) -> Self {
+        Self {
+            id,
+            title: String::new(),
+            body: String::new(),
+            urgency,
+            focus_on_activate: true,
+            report_activation: false,
+            report_close: false,
+            occasion: NotifyOccasion::Always,
+            expire_ms: -1,
+            icon_names: Vec::new(),
+            icon_data: None,
+            icon_cache_id: None,
+            timestamp: std::time::Instant::now(),
+        }
+    }
+
+    /// Whether to show it, given whether its pane has the keyboard focus and
+    /// whether its pane is on screen (both false while bcon's VT is inactive)
+    pub fn should_show(&self, focused: bool, visible: bool) -> bool {
+        match self.occasion {
+            NotifyOccasion::Always => true,
+            NotifyOccasion::Unfocused => !focused,
+            NotifyOccasion::Invisible => !visible,
+        }
+    }
+
+    /// OSC 99 report that the notification was activated (a=report)
+    pub fn activation_report(&self) -> Option<String> {
+        let id = self.id.as_ref().filter(|_| self.report_activation)?;
+        Some(osc99_activation_report(id))
+    }
+
+    /// OSC 99 report that the notification was closed (c=1)
+    pub fn close_report(&self) -> Option<String> {
+        let id = self.id.as_ref().filter(|_| self.report_close)?;
+        Some(osc99_close_report(id))
+    }
+}
+
+/// OSC 99 `o=`: when a notification is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotifyOccasion {
+    #[default]
+    Always,
+    /// Only when its pane does not have the keyboard focus
+    Unfocused,
+    /// Only when its pane is not on screen
+    Invisible,
+}
+
+/// `OSC 99 ; i=<id> ; ST`: notification `id` was activated
+pub fn osc99_activation_report(id: &str) -> String {
+    format!("\x1b]99;i={};\x1b\\", id)
+}
+
+/// `OSC 99 ; i=<id>:p=close ; ST`: notification `id` was closed
+pub fn osc99_close_report(id: &str) -> String {
+    format!("\x1b]99;i={}:p=close;\x1b\\", id)
+}
+
 /// Progress state from OSC 9;4
 #[derive(Debug, Clone)]
 pub struct NotificationProgress {
@@ -68,6 +172,58 @@ fn default_clipboard_path() -> String {
     }
 }
 
+/// Primary selection file next to the clipboard file
+fn primary_path_for(clipboard_path: &str) -> String {
+    format!("{}_primary", clipboard_path)
+}
+
+/// Refresh a buffer from its shared file (enables cross-pane sharing)
+fn read_shared(path: &str, buffer: &mut String) {
+    if let Ok(text) = std::fs::read_to_string(path) {
+        if !text.is_empty() {
+            *buffer = text;
+        }
+    }
+}
+
+/// copy_command runs waiting for a worker, shared by all panes. Clipboard
+/// changes arriving while a run is in progress replace each other, so a burst
+/// of OSC 52 writes costs one thread and at most two runs.
+#[derive(Default)]
+pub struct CopyCommandQueue {
+    /// Next run: (uid, command, input, timeout)
+    next: Option<(u32, String, Vec<u8>, Duration)>,
+    /// A worker thread is draining the queue
+    running: bool,
+}
+
+/// copy_command queue shared by all panes
+pub type SharedCopyCommandQueue = Arc<std::sync::Mutex<CopyCommandQueue>>;
+
+fn lock_copy_queue(
+    queue: &SharedCopyCommandQueue,
+) -> std::sync::MutexGuard<'_, CopyCommandQueue> {
+    queue.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Run queued copy_commands until the queue is empty
+fn drain_copy_queue(queue: SharedCopyCommandQueue) {
+    loop {
+        let next = {
+            let mut q = lock_copy_queue(&queue);
+            let next = q.next.take();
+            q.running = next.is_some();
+            next
+        };
+        let Some((uid, command, input, timeout)) = next else {
+            return;
+        };
+        if let Err(e) = pty::pipe_to_user_command(uid, &command, &input, timeout) {
+            log::warn!("copy_command failed: {}", e);
+        }
+    }
+}
+
 /// Check if buffer contains ESC _ (APC start sequence)
 /// Manual loop is faster than windows(2).any() for small patterns
 #[inline]
@@ -97,88 +253,269 @@ fn has_esc_p(buf: &[u8]) -> bool {
 
 /// Copy mode state
 pub struct CopyModeState {
-    /// Copy mode cursor row (display coordinates)
-    pub cursor_row: usize,
+    /// Copy mode cursor row (absolute line, see `Terminal::display_line`)
+    pub cursor_row: u64,
     /// Copy mode cursor column
     pub cursor_col: usize,
     /// Selection active flag
     pub selecting: bool,
-    /// Selection start position (when selecting=true)
-    pub anchor_row: usize,
+    /// Selection flavor (v, V or Ctrl+V)
+    pub mode: copy_mode::VisualMode,
+    /// Selection start position (when selecting=true, absolute line)
+    pub anchor_row: u64,
     pub anchor_col: usize,
+    /// Count typed before a motion (0 = none)
+    pub count: usize,
+    /// First key of a two-key command (f/t/F/T, m, ', `, ")
+    pub pending: Option<copy_mode::PendingKey>,
+    /// Register selected with `"x` for the next yank
+    pub register: Option<char>,
+    /// Last f/t/F/T search, repeated by ; and ,
+    pub last_find: Option<(copy_mode::FindKind, char)>,
 }
 
 impl CopyModeState {
-    pub fn new(cursor_row: usize, cursor_col: usize) -> Self {
+    pub fn new(cursor_row: u64, cursor_col: usize) -> Self {
         Self {
             cursor_row,
             cursor_col,
             selecting: false,
+            mode: copy_mode::VisualMode::Char,
             anchor_row: 0,
             anchor_col: 0,
+            count: 0,
+            pending: None,
+            register: None,
+            last_find: None,
         }
     }
 
-    /// Start/toggle selection
-    pub fn toggle_selection(&mut self) {
-        if self.selecting {
+    /// Start/toggle selection in `mode`.
+    /// Switching between v, V and Ctrl+V keeps the selection.
+    pub fn toggle_selection(&mut self, mode: copy_mode::VisualMode) {
+        if self.selecting && self.mode == mode {
             self.selecting = false;
         } else {
+            if !self.selecting {
+                self.anchor_row = self.cursor_row;
+                self.anchor_col = self.cursor_col;
+            }
             self.selecting = true;
-            self.anchor_row = self.cursor_row;
-            self.anchor_col = self.cursor_col;
+            self.mode = mode;
         }
     }
 
     /// Return current selection range as Selection
-    pub fn get_selection(&self) -> Option<Selection> {
-        if self.selecting {
-            Some(Selection {
+    pub fn get_selection(&self, cols: usize) -> Option<Selection> {
+        if !self.selecting {
+            return None;
+        }
+        if self.mode == copy_mode::VisualMode::Line {
+            // Whole rows, whichever direction the cursor moved
+            let last_col = cols.saturating_sub(1);
+            let (anchor_col, end_col) = if self.anchor_row <= self.cursor_row {
+                (0, last_col)
+            } else {
+                (last_col, 0)
+            };
+            return Some(Selection {
                 anchor_row: self.anchor_row,
-                anchor_col: self.anchor_col,
+                anchor_col,
                 end_row: self.cursor_row,
-                end_col: self.cursor_col,
-            })
-        } else {
-            None
+                end_col,
+                block: false,
+            });
+        }
+        Some(Selection {
+            anchor_row: self.anchor_row,
+            anchor_col: self.anchor_col,
+            end_row: self.cursor_row,
+            end_col: self.cursor_col,
+            block: self.mode == copy_mode::VisualMode::Block,
+        })
+    }
+
+    /// Count and pending keys for the status bar (e.g. `"a 5`)
+    pub fn pending_display(&self) -> String {
+        let mut out = String::new();
+        if let Some(reg) = self.register {
+            out.push('"');
+            out.push(reg);
+        }
+        if self.count > 0 {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(&self.count.to_string());
+        }
+        if let Some(pending) = self.pending {
+            out.push_str(pending.key());
         }
+        out
     }
 }
 
 /// Search state
+///
+/// `matches` holds the scrollback matches streamed in by the worker followed
+/// by the matches on screen (from `screen_start`). Match rows are line
+/// numbers from the grid's `ScrollbackIndex`, so they stay valid while the
+/// scrollback grows and old rows are evicted.
 pub struct SearchState {
     /// Search query
     pub query: String,
-    /// Match position list: (row, start_col, end_col)
-    /// row is absolute row including scrollback (0 = start of scrollback)
-    pub matches: Vec<(usize, usize, usize)>,
+    /// Matches in buffer order; a match may span soft-wrapped rows
+    pub matches: Vec<search::SearchMatch>,
     /// Current match index
     pub current_match: usize,
-    /// Matches grouped by row for fast lookup (row -> [(start_col, end_col, match_index)])
+    /// Regex / case options
+    pub options: search::SearchOptions,
+    /// Pattern compile error from the last search
+    pub error: Option<String>,
+    /// Whether the current query has been searched (distinguishes "0/0" from not run yet)
+    pub executed: bool,
+    /// Enter was pressed: n / N move between matches instead of editing the query
+    pub confirmed: bool,
+    /// Scrollback scan still in progress
+    pub scanning: bool,
+    /// Compiled query
+    matcher: Option<regex::Regex>,
+    /// Scrollback worker (dropping it cancels the scan)
+    job: Option<search::ScanJob>,
+    /// Index generation the worker is scanning
+    generation: u64,
+    /// Index of the first screen match in `matches`
+    screen_start: usize,
+    /// Matches dropped from the front after their rows were evicted.
+    /// `row_matches` stores `index + removed` so it survives the shift.
+    removed: usize,
+    /// New output arrived since the screen was last searched
+    screen_stale: bool,
+    /// Pick the match nearest the bottom and scroll to it once known
+    jump_pending: bool,
+    /// Matches grouped by row for fast lookup (row -> [(start_col, end_col, match_ordinal)])
     row_matches: HashMap<usize, Vec<(usize, usize, usize)>>,
 }
 
 impl SearchState {
-    pub fn new() -> Self {
+    pub fn new(options: search::SearchOptions) -> Self {
         Self {
             query: String::new(),
             matches: Vec::new(),
             current_match: 0,
+            options,
+            error: None,
+            executed: false,
+            confirmed: false,
+            scanning: false,
+            matcher: None,
+            job: None,
+            generation: 0,
+            screen_start: 0,
+            removed: 0,
+            screen_stale: false,
+            jump_pending: false,
             row_matches: HashMap::new(),
         }
     }
 
-    /// Build row_matches index from matches
-    fn build_row_index(&mut self) {
+    /// Forget results (and stop the worker) after the query or options changed
+    pub fn clear_results(&mut self) {
+        self.matches.clear();
         self.row_matches.clear();
-        for (idx, &(row, start, end)) in self.matches.iter().enumerate() {
-            self.row_matches
-                .entry(row)
-                .or_insert_with(|| Vec::with_capacity(4))
-                .push((start, end, idx));
+        self.current_match = 0;
+        self.error = None;
+        self.executed = false;
+        self.scanning = false;
+        self.matcher = None;
+        self.job = None;
+        self.screen_start = 0;
+        self.removed = 0;
+        self.screen_stale = false;
+        self.jump_pending = false;
+    }
+
+    /// Don't move the view when results arrive (the user has picked a match)
+    pub fn cancel_jump(&mut self) {
+        self.jump_pending = false;
+    }
+
+    /// Add row_matches entries for `matches[range]`.
+    /// Multi-row matches get one segment per row they cover.
+    fn index_range(&mut self, range: std::ops::Range<usize>, cols: usize) {
+        for idx in range {
+            let m = self.matches[idx];
+            for row in m.start_row..=m.end_row {
+                let start = if row == m.start_row { m.start_col } else { 0 };
+                let end = if row == m.end_row { m.end_col } else { cols };
+                self.row_matches
+                    .entry(row)
+                    .or_insert_with(|| Vec::with_capacity(4))
+                    .push((start, end, idx + self.removed));
+            }
         }
     }
 
+    /// Remove row_matches entries for `matches[range]`
+    fn unindex_range(&mut self, range: std::ops::Range<usize>) {
+        for idx in range {
+            let m = self.matches[idx];
+            let ordinal = idx + self.removed;
+            for row in m.start_row..=m.end_row {
+                if let Some(entries) = self.row_matches.get_mut(&row) {
+                    entries.retain(|e| e.2 != ordinal);
+                    if entries.is_empty() {
+                        self.row_matches.remove(&row);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Insert scrollback matches from the worker (they precede the screen matches)
+    fn insert_scrollback_matches(&mut self, found: Vec<search::SearchMatch>, cols: usize) {
+        let n = found.len();
+        let had_current = self.current_match < self.matches.len();
+        let len = self.matches.len();
+        // Screen matches move, so their ordinals change
+        self.unindex_range(self.screen_start..len);
+        let at = self.screen_start;
+        self.matches.splice(at..at, found);
+        if had_current && self.current_match >= at {
+            self.current_match += n;
+        }
+        self.screen_start += n;
+        self.index_range(at..self.matches.len(), cols);
+    }
+
+    /// Replace the screen matches
+    fn set_screen_matches(&mut self, found: Vec<search::SearchMatch>, cols: usize) {
+        let len = self.matches.len();
+        self.unindex_range(self.screen_start..len);
+        self.matches.truncate(self.screen_start);
+        self.matches.extend(found);
+        self.index_range(self.screen_start..self.matches.len(), cols);
+        if self.current_match >= self.matches.len() {
+            self.current_match = self.matches.len().saturating_sub(1);
+        }
+    }
+
+    /// Drop scrollback matches that start on evicted rows
+    fn prune(&mut self, first_line: usize) {
+        let k = self.matches[..self.screen_start]
+            .iter()
+            .take_while(|m| m.start_row < first_line)
+            .count();
+        if k == 0 {
+            return;
+        }
+        self.unindex_range(0..k);
+        self.matches.drain(..k);
+        self.removed += k;
+        self.screen_start -= k;
+        self.current_match = self.current_match.saturating_sub(k);
+    }
+
     /// Move to next match
     pub fn next_match(&mut self) {
         if !self.matches.is_empty() {
@@ -198,21 +535,35 @@ impl SearchState {
     }
 }
 
-/// Text selection range (display coordinates)
+/// Text selection range
+///
+/// Rows are absolute lines (`scrollback_total` + screen row, like image
+/// placements), so the selection stays on its text while output scrolls.
 pub struct Selection {
     /// Selection start point (anchor)
-    pub anchor_row: usize,
+    pub anchor_row: u64,
     pub anchor_col: usize,
     /// Selection end point (current position)
-    pub end_row: usize,
+    pub end_row: u64,
     pub end_col: usize,
+    /// Rectangular selection: the same columns on every row
+    pub block: bool,
 }
 
 impl Selection {
     /// Return normalized range (guarantees start <= end)
     /// Both anchor_col and end_col are stored as inclusive cell positions.
     /// This method returns (sr, sc, er, ec) where sc is inclusive and ec is EXCLUSIVE.
-    pub fn normalized(&self) -> (usize, usize, usize, usize) {
+    /// For block selections sc..ec is the column range of every row.
+    pub fn normalized(&self) -> (u64, usize, u64, usize) {
+        if self.block {
+            return (
+                self.anchor_row.min(self.end_row),
+                self.anchor_col.min(self.end_col),
+                self.anchor_row.max(self.end_row),
+                self.anchor_col.max(self.end_col) + 1,
+            );
+        }
         if (self.anchor_row, self.anchor_col) <= (self.end_row, self.end_col) {
             (
                 self.anchor_row,
@@ -234,13 +585,13 @@ impl Selection {
     /// This is more efficient than calling contains() for every column.
     /// Returns (start_col, end_col) where end_col is exclusive.
     #[inline]
-    pub fn cols_for_row(&self, row: usize, max_cols: usize) -> Option<(usize, usize)> {
+    pub fn cols_for_row(&self, row: u64, max_cols: usize) -> Option<(usize, usize)> {
         let (sr, sc, er, ec) = self.normalized();
         if row < sr || row > er {
             return None;
         }
-        let start = if row == sr { sc } else { 0 };
-        let end = if row == er {
+        let start = if row == sr || self.block { sc } else { 0 };
+        let end = if row == er || self.block {
             ec.min(max_cols)
         } else {
             max_cols
@@ -252,12 +603,12 @@ impl Selection {
     }
 
     /// Check if specified cell is within selection range
-    pub fn contains(&self, row: usize, col: usize) -> bool {
+    pub fn contains(&self, row: u64, col: usize) -> bool {
         let (sr, sc, er, ec) = self.normalized();
         if row < sr || row > er {
             return false;
         }
-        if row == sr && row == er {
+        if self.block || (row == sr && row == er) {
             return col >= sc && col < ec;
         }
         if row == sr {
@@ -578,6 +929,8 @@ pub struct Terminal {
     pub selection: Option<Selection>,
     /// Internal clipboard
     pub clipboard: String,
+    /// Primary selection (last selected text, pasted with middle click)
+    pub primary: String,
     /// Image registry (Sixel, Kitty, etc.)
     pub images: ImageRegistry,
     /// DCS sequence handler (during Sixel parsing)
@@ -596,8 +949,16 @@ pub struct Terminal {
     pub search: Option<SearchState>,
     /// Copy mode state (None = normal mode)
     pub copy_mode: Option<CopyModeState>,
+    /// Hints mode state (None = hints mode OFF)
+    pub hints: Option<hints::HintState>,
+    /// Copy mode marks (m / ' / `): letter -> (line number, col)
+    copy_marks: HashMap<char, (usize, usize)>,
     /// Clipboard file path
     clipboard_path: String,
+    /// Primary selection file path (clipboard path + "_primary")
+    primary_path: String,
+    /// OSC 52 clipboard writes not yet recorded in the history
+    osc52_writes: Vec<String>,
     /// Current directory (OSC 7)
     pub current_directory: Option<String>,
     /// PTY response buffer (reused across parser calls)
@@ -610,12 +971,39 @@ pub struct Terminal {
     pub notification_seq: u64,
     /// Whether notifications are enabled (config: notifications.enabled)
     pub notifications_enabled: bool,
+    /// Finished commands (oldest first, max MAX_FINISHED_COMMANDS), counted
+    /// by grid.shell.commands_finished
+    pub finished_commands: VecDeque<FinishedCommand>,
     /// Current progress bar state (OSC 9;4)
     pub active_progress: Option<NotificationProgress>,
     /// Pending OSC 99 notifications (incomplete, keyed by id)
     pub pending_notifications: HashMap<String, Notification>,
+    /// OSC 99 ids the application asked to close (p=close), drained by the main loop
+    pub notification_closes: Vec<String>,
+    /// OSC 99 icon data cached by `g=` identifier
+    pub notification_icons: HashMap<String, std::sync::Arc<Vec<u8>>>,
     /// Allow Kitty graphics remote file/shm transfers (from config)
     pub allow_kitty_remote: bool,
+    /// Also copy selections to the clipboard (config: selection.copy_on_select)
+    pub copy_on_select: bool,
+    /// Clipboard history shared by all panes
+    pub clipboard_history: SharedClipboardHistory,
+    /// Connection to the per-user clipboard broker (config: clipboard.share)
+    pub clipboard_broker: Option<Arc<ClipboardBroker>>,
+    /// Command fed every clipboard change on stdin (config: clipboard.copy_command)
+    pub copy_command: String,
+    /// Pending copy_command runs (shared by all panes)
+    pub copy_command_queue: SharedCopyCommandQueue,
+    /// Command whose stdout is pasted (config: clipboard.paste_command)
+    pub paste_command: String,
+    /// Running paste_command: its output, once it finishes
+    paste_command_job: Option<std::sync::mpsc::Receiver<Option<String>>>,
+    /// Time limit for copy_command / paste_command
+    pub clipboard_command_timeout: Duration,
+    /// Paste sanitizing and confirmation settings
+    pub paste_safety: paste::PasteSafety,
+    /// Paste waiting for confirmation (None = no prompt)
+    pub pending_paste: Option<paste::PendingPaste>,
 }
 
 impl Terminal {
@@ -654,6 +1042,7 @@ impl Terminal {
             scroll_offset: 0,
             selection: None,
             clipboard: String::new(),
+            primary: String::new(),
             images: ImageRegistry::new(),
             dcs_handler: None,
             kitty_decoder: None,
@@ -663,21 +1052,39 @@ impl Terminal {
             cell_height: 0,
             search: None,
             copy_mode: None,
+            hints: None,
+            copy_marks: HashMap::new(),
             clipboard_path: default_clipboard_path(),
+            primary_path: primary_path_for(&default_clipboard_path()),
+            osc52_writes: Vec::new(),
             current_directory: None,
             pty_response: Vec::with_capacity(256),
             dirty_image_ids: Vec::new(),
             notifications: VecDeque::new(),
             notification_seq: 0,
             notifications_enabled: true,
+            finished_commands: VecDeque::new(),
             active_progress: None,
             pending_notifications: HashMap::new(),
+            notification_closes: Vec::new(),
+            notification_icons: HashMap::new(),
             allow_kitty_remote: true,
+            copy_on_select: false,
+            clipboard_history: ClipboardHistory::default().shared(),
+            clipboard_broker: None,
+            copy_command: String::new(),
+            copy_command_queue: SharedCopyCommandQueue::default(),
+            paste_command: String::new(),
+            paste_command_job: None,
+            clipboard_command_timeout: Duration::from_secs(2),
+            paste_safety: paste::PasteSafety::default(),
+            pending_paste: None,
         })
     }
 
     /// Initialize terminal spawning the user's shell directly (for split panes / new tabs).
     /// Drops privileges to the specified UID instead of running /bin/login.
+    /// With `command`, runs it through the user's shell instead of an interactive shell.
     pub fn with_scrollback_as_user(
         cols: usize,
         rows: usize,
@@ -685,10 +1092,18 @@ impl Terminal {
         term_env: &str,
         extra_env: &[(&str, &str)],
         uid: u32,
+        command: Option<&str>,
     ) -> Result<Self> {
         let grid = Grid::with_scrollback(cols, rows, max_scrollback);
         let vt_parser = vte::Parser::new();
-        let pty = Pty::spawn_as_user(cols as u16, rows as u16, term_env, extra_env, uid)?;
+        let pty = Pty::spawn_command_as_user(
+            cols as u16,
+            rows as u16,
+            term_env,
+            extra_env,
+            uid,
+            command,
+        )?;
 
         Ok(Self {
             grid,
@@ -698,6 +1113,7 @@ impl Terminal {
             scroll_offset: 0,
             selection: None,
             clipboard: String::new(),
+            primary: String::new(),
             images: ImageRegistry::new(),
             dcs_handler: None,
             kitty_decoder: None,
@@ -707,16 +1123,33 @@ impl Terminal {
             cell_height: 0,
             search: None,
             copy_mode: None,
+            hints: None,
+            copy_marks: HashMap::new(),
             clipboard_path: default_clipboard_path(),
+            primary_path: primary_path_for(&default_clipboard_path()),
+            osc52_writes: Vec::new(),
             current_directory: None,
             pty_response: Vec::with_capacity(256),
             dirty_image_ids: Vec::new(),
             notifications: VecDeque::new(),
             notification_seq: 0,
             notifications_enabled: true,
+            finished_commands: VecDeque::new(),
             active_progress: None,
             pending_notifications: HashMap::new(),
+            notification_closes: Vec::new(),
+            notification_icons: HashMap::new(),
             allow_kitty_remote: true,
+            copy_on_select: false,
+            clipboard_history: ClipboardHistory::default().shared(),
+            clipboard_broker: None,
+            copy_command: String::new(),
+            copy_command_queue: SharedCopyCommandQueue::default(),
+            paste_command: String::new(),
+            paste_command_job: None,
+            clipboard_command_timeout: Duration::from_secs(2),
+            paste_safety: paste::PasteSafety::default(),
+            pending_paste: None,
         })
     }
 
@@ -738,6 +1171,7 @@ impl Terminal {
     /// Set clipboard file path
     pub fn set_clipboard_path(&mut self, path: &str) {
         self.clipboard_path = path.to_string();
+        self.primary_path = primary_path_for(path);
     }
 
     /// Get the home directory of the logged-in user (child process owner)
@@ -840,6 +1274,9 @@ impl Terminal {
 
         trace!("PTY read: {} bytes", n);
 
+        let total_before = self.grid.scrollback_total();
+        let selected_before = self.selected_text_from(total_before);
+
         // Slow path is needed when we're inside (or about to enter) an APC
         // or DCS sequence that bcon handles directly (bypassing vte).
         let needs_slow = matches!(
@@ -861,9 +1298,68 @@ impl Terminal {
             self.process_pty_output_fast(n);
         }
 
+        // Screen matches are refreshed on the next poll_search()
+        if let Some(ref mut search) = self.search {
+            search.screen_stale = true;
+        }
+
+        self.follow_output(total_before, selected_before);
+
+        if !self.osc52_writes.is_empty() {
+            let echo = self.pty.echo_enabled();
+            for text in std::mem::take(&mut self.osc52_writes) {
+                self.clipboard_changed(&text, ClipSource::Osc52, echo);
+            }
+        }
+
         Ok(n)
     }
 
+    /// Keep the view and the selection on their lines after output:
+    /// a scrolled-back view stays put, and the selection is dropped only
+    /// once its lines are evicted or its text on screen is overwritten.
+    fn follow_output(&mut self, total_before: u64, selected_before: Option<String>) {
+        let scrolled = self.grid.scrollback_total() - total_before;
+        if self.scroll_offset > 0 && scrolled > 0 {
+            let max = self.grid.scrollback_len();
+            self.scroll_offset = (self.scroll_offset + scrolled as usize).min(max);
+            self.grid.mark_all_dirty();
+        }
+
+        let first_line = self.first_line();
+        let evicted = self
+            .selection
+            .as_ref()
+            .is_some_and(|sel| sel.normalized().0 < first_line);
+        if evicted || selected_before != self.selected_text_from(total_before) {
+            self.selection = None;
+            self.grid.mark_all_dirty();
+            if let Some(ref mut cm) = self.copy_mode {
+                cm.selecting = false;
+            }
+        }
+        if let Some(ref mut cm) = self.copy_mode {
+            cm.cursor_row = cm.cursor_row.max(first_line);
+        }
+    }
+
+    /// Selected text on lines at or after `from_line` (None without a selection)
+    fn selected_text_from(&self, from_line: u64) -> Option<String> {
+        let sel = self.selection.as_ref()?;
+        let (sr, _, er, _) = sel.normalized();
+        let cols = self.grid.cols();
+        let mut text = String::new();
+        for line in sr.max(from_line)..=er {
+            if let Some((start, end)) = sel.cols_for_row(line, cols) {
+                for col in start..end {
+                    text.push_str(&self.line_cell(line, col).grapheme);
+                }
+            }
+            text.push('\n');
+        }
+        Some(text)
+    }
+
     /// Fast path: process all bytes with single Performer (no APC)
     fn process_pty_output_fast(&mut self, n: usize) {
         self.pty_response.clear();
@@ -871,6 +1367,7 @@ impl Terminal {
         let mut performer = Performer::new(
             &mut self.grid,
             &mut self.clipboard,
+            &mut self.primary,
             &mut self.dcs_handler,
             &mut self.images,
             &mut self.dirty_image_ids,
@@ -878,12 +1375,17 @@ impl Terminal {
             self.cell_height,
             &mut self.current_directory,
             &self.clipboard_path,
+            &self.primary_path,
+            &mut self.osc52_writes,
             &mut self.pty_response,
             &mut self.notifications,
             &mut self.notification_seq,
             &mut self.active_progress,
             &mut self.pending_notifications,
+            &mut self.notification_closes,
+            &mut self.notification_icons,
             &self.notifications_enabled,
+            &mut self.finished_commands,
         );
 
         for i in 0..n {
@@ -1044,6 +1546,7 @@ impl Terminal {
         let mut performer = Performer::new(
             &mut self.grid,
             &mut self.clipboard,
+            &mut self.primary,
             &mut self.dcs_handler,
             &mut self.images,
             &mut self.dirty_image_ids,
@@ -1051,12 +1554,17 @@ impl Terminal {
             self.cell_height,
             &mut self.current_directory,
             &self.clipboard_path,
+            &self.primary_path,
+            &mut self.osc52_writes,
             &mut self.pty_response,
             &mut self.notifications,
             &mut self.notification_seq,
             &mut self.active_progress,
             &mut self.pending_notifications,
+            &mut self.notification_closes,
+            &mut self.notification_icons,
             &self.notifications_enabled,
+            &mut self.finished_commands,
         );
         self.vt_parser.advance(&mut performer, byte);
 
@@ -1885,15 +2393,20 @@ impl Terminal {
             Some(s) => s,
             None => return String::new(),
         };
-        let (sr, sc, er, ec) = sel.normalized();
+        let (sr, _, er, _) = sel.normalized();
         let mut result = String::new();
         let cols = self.grid.cols();
 
         for row in sr..=er {
-            let col_start = if row == sr { sc } else { 0 };
-            let col_end = if row == er { ec.min(cols) } else { cols };
+            let (mut col_start, col_end) = sel.cols_for_row(row, cols).unwrap_or_default();
+            // A block edge cutting a wide character takes the whole character
+            if sel.block {
+                while col_start > 0 && self.line_cell(row, col_start).width == 0 {
+                    col_start -= 1;
+                }
+            }
             for col in col_start..col_end {
-                let cell = self.display_cell(row, col);
+                let cell = self.line_cell(row, col);
                 if cell.width == 0 {
                     continue; // Wide character continuation cell
                 }
@@ -1901,9 +2414,14 @@ impl Terminal {
                     result.push_str(&cell.grapheme);
                 }
             }
-            // Trim trailing whitespace, add newline
-            if row < er {
-                let trimmed = result.trim_end().len();
+            // Trim trailing whitespace (blank block rows stay as empty lines), add newline
+            // (soft-wrapped rows continue the same line)
+            if row < er && (sel.block || !self.line_wrapped(row)) {
+                let trimmed = if sel.block {
+                    result.trim_end_matches(' ').len()
+                } else {
+                    result.trim_end().len()
+                };
                 result.truncate(trimmed);
                 result.push('\n');
             }
@@ -1913,128 +2431,248 @@ impl Terminal {
         result
     }
 
-    /// Set clipboard (internal buffer + write to file)
-    pub fn set_clipboard(&mut self, text: &str) {
+    /// Set clipboard (internal buffer + write to file) and record it in the history
+    pub fn set_clipboard(&mut self, text: &str, source: ClipSource) {
+        let echo = self.pty.echo_enabled();
+        self.clipboard_changed(text, source, echo);
         self.clipboard = text.to_string();
         if let Err(e) = std::fs::write(&self.clipboard_path, text) {
             log::warn!("Failed to write clipboard file: {}", e);
         }
     }
 
-    /// Copy selection to clipboard
-    pub fn copy_selection(&mut self) {
-        let text = self.get_selection_text();
-        if !text.is_empty() {
-            self.set_clipboard(&text);
-            info!("Clipboard: {} characters copied", text.len());
+    /// Tell the history, the broker and copy_command about a new clipboard
+    fn clipboard_changed(&self, text: &str, source: ClipSource, echo: bool) {
+        let skip_no_echo = {
+            let mut history = ClipboardHistory::lock(&self.clipboard_history);
+            history.push(text, source, echo);
+            history.skip_no_echo() && !echo
+        };
+        if let Some(ref broker) = self.clipboard_broker {
+            broker.publish(text, echo);
+        }
+        // Like the history, external clipboards never see password input
+        if !skip_no_echo {
+            self.run_copy_command(text);
         }
     }
 
-    /// Double click: word selection
-    pub fn select_word(&mut self, row: usize, col: usize) {
-        let cols = self.grid.cols();
-
-        // Get cell at click position
-        let mut click_col = col;
-        let cell = self.grid.cell(row, col);
-
-        // If clicked on continuation cell (width=0), find the original cell
-        if cell.width == 0 {
-            while click_col > 0 {
-                click_col -= 1;
-                let prev = self.grid.cell(row, click_col);
-                if prev.width != 0 {
-                    break;
-                }
-            }
+    /// Pipe the clipboard into copy_command as the logged-in user (in the
+    /// background; nothing runs before login)
+    fn run_copy_command(&self, text: &str) {
+        if self.copy_command.is_empty() {
+            return;
         }
-
-        let base_cell = self.grid.cell(row, click_col);
-
-        // Don't select if whitespace/empty cell
-        if base_cell.grapheme.is_empty() || base_cell.grapheme == " " {
-            self.selection = None;
+        let Some(uid) = self.logged_in_uid() else {
+            return;
+        };
+        let mut queue = lock_copy_queue(&self.copy_command_queue);
+        queue.next = Some((
+            uid,
+            self.copy_command.clone(),
+            text.as_bytes().to_vec(),
+            self.clipboard_command_timeout,
+        ));
+        if queue.running {
             return;
         }
-
-        // Word boundary detection: treat everything except whitespace as word (simple version)
-        fn is_word_boundary(grapheme: &str) -> bool {
-            grapheme.is_empty() || grapheme == " " || grapheme == "\t"
+        let worker_queue = self.copy_command_queue.clone();
+        match std::thread::Builder::new()
+            .name("bcon-copy-command".into())
+            .spawn(move || drain_copy_queue(worker_queue))
+        {
+            Ok(_) => queue.running = true,
+            Err(e) => log::warn!("Failed to start copy_command thread: {}", e),
         }
+    }
 
-        let mut start_col = click_col;
-        let mut end_col = click_col;
-
-        // Search left
-        while start_col > 0 {
-            let prev = self.grid.cell(row, start_col - 1);
-            if prev.width == 0 {
-                start_col -= 1;
-                continue;
+    /// Read the clipboard from paste_command as the logged-in user in the
+    /// background; `poll_paste_command` pastes the output. Returns false if
+    /// there is no paste_command to run.
+    fn start_paste_command(&mut self) -> bool {
+        if self.paste_command.is_empty() {
+            return false;
+        }
+        let Some(uid) = self.logged_in_uid() else {
+            return false;
+        };
+        if self.paste_command_job.is_some() {
+            // Already reading the clipboard; one paste is enough
+            return true;
+        }
+        let command = self.paste_command.clone();
+        let timeout = self.clipboard_command_timeout;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("bcon-paste-command".into())
+            .spawn(move || {
+                let output = match pty::read_user_command(uid, &command, timeout) {
+                    Ok(output) => Some(String::from_utf8_lossy(&output).into_owned()),
+                    Err(e) => {
+                        log::warn!("paste_command failed: {}", e);
+                        None
+                    }
+                };
+                let _ = tx.send(output);
+            });
+        match spawned {
+            Ok(_) => {
+                self.paste_command_job = Some(rx);
+                true
             }
-            if is_word_boundary(&prev.grapheme) {
-                break;
+            Err(e) => {
+                log::warn!("Failed to start paste_command thread: {}", e);
+                false
             }
-            start_col -= 1;
         }
+    }
 
-        // Search right
-        while end_col < cols - 1 {
-            let next = self.grid.cell(row, end_col + 1);
-            if next.width == 0 {
-                end_col += 1;
-                continue;
+    /// Paste the output of a finished paste_command (the shared clipboard
+    /// file if it failed or printed nothing). Returns true if it finished.
+    pub fn poll_paste_command(&mut self) -> bool {
+        let Some(ref rx) = self.paste_command_job else {
+            return false;
+        };
+        let output = match rx.try_recv() {
+            Ok(output) => output,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => None,
+        };
+        self.paste_command_job = None;
+        match output {
+            // An external clipboard wins; keep it for the other panes
+            Some(text) if !text.is_empty() => {
+                if let Err(e) = std::fs::write(&self.clipboard_path, &text) {
+                    log::warn!("Failed to write clipboard file: {}", e);
+                }
+                self.clipboard = text;
             }
-            if is_word_boundary(&next.grapheme) {
-                break;
+            _ => read_shared(&self.clipboard_path, &mut self.clipboard),
+        }
+        if !self.clipboard.is_empty() {
+            let text = self.clipboard.clone();
+            if let Err(e) = self.paste_text(&text) {
+                log::warn!("Paste failed: {}", e);
             }
-            end_col += 1;
         }
+        true
+    }
 
-        // Both anchor_col and end_col are inclusive cell positions
-        self.selection = Some(Selection {
-            anchor_row: row,
-            anchor_col: start_col,
-            end_row: row,
-            end_col: end_col,
-        });
-        self.copy_selection();
+    /// Copy selection to clipboard
+    pub fn copy_selection(&mut self) {
+        let text = self.get_selection_text();
+        if !text.is_empty() {
+            self.set_clipboard(&text, ClipSource::Selection);
+            info!("Clipboard: {} characters copied", text.len());
+        }
     }
 
-    /// Triple click: line selection
-    pub fn select_line(&mut self, row: usize) {
-        let cols = self.grid.cols();
-        self.selection = Some(Selection {
-            anchor_row: row,
-            anchor_col: 0,
-            end_row: row,
-            end_col: cols - 1,
-        });
-        self.copy_selection();
+    /// Set primary selection (internal buffer + write to file)
+    pub fn set_primary(&mut self, text: &str) {
+        self.primary = text.to_string();
+        if let Err(e) = clipboard::write_private(&self.primary_path, text) {
+            log::warn!("Failed to write primary selection file: {}", e);
+        }
+    }
+
+    /// A selection was made: store it as the primary selection
+    /// (and in the clipboard with copy_on_select)
+    pub fn selection_to_primary(&mut self) {
+        let text = self.get_selection_text();
+        if !text.is_empty() {
+            self.set_primary(&text);
+            if self.copy_on_select {
+                self.set_clipboard(&text, ClipSource::Selection);
+            }
+        }
     }
 
     /// Send clipboard contents to PTY (paste)
     /// Reads from clipboard file to share clipboard across split panes.
     /// If bracketed_paste is enabled, wrap with \e[200~ and \e[201~
+    /// With paste_command, the paste happens once the command finishes.
     pub fn paste_clipboard(&mut self) -> Result<()> {
-        // Read from shared clipboard file (enables cross-pane clipboard sharing)
-        if let Ok(text) = std::fs::read_to_string(&self.clipboard_path) {
-            if !text.is_empty() {
-                self.clipboard = text;
-            }
+        if self.start_paste_command() {
+            return Ok(());
         }
+        read_shared(&self.clipboard_path, &mut self.clipboard);
         if !self.clipboard.is_empty() {
-            if self.grid.modes.bracketed_paste {
-                self.pty.write_all(b"\x1b[200~")?;
-                self.pty.write_all(self.clipboard.as_bytes())?;
-                self.pty.write_all(b"\x1b[201~")?;
-            } else {
-                self.pty.write_all(self.clipboard.as_bytes())?;
+            let text = self.clipboard.clone();
+            self.paste_text(&text)?;
+        }
+        Ok(())
+    }
+
+    /// Send the primary selection to PTY (middle click / Shift+Insert)
+    pub fn paste_primary(&mut self) -> Result<()> {
+        if let Ok(text) = clipboard::read_private(&self.primary_path) {
+            if !text.is_empty() {
+                self.primary = text;
             }
         }
+        if !self.primary.is_empty() {
+            let text = self.primary.clone();
+            self.paste_text(&text)?;
+        }
+        Ok(())
+    }
+
+    /// Send text to PTY as a paste (bracketed if enabled).
+    /// ESC / C1 are stripped; risky pastes wait in `pending_paste` for
+    /// `confirm_paste` / `cancel_paste`.
+    pub fn paste_text(&mut self, text: &str) -> Result<()> {
+        let text = self.paste_safety.prepare(text);
+        let bracketed = self.grid.modes.bracketed_paste;
+        if let Some(warning) = self.paste_safety.check(&text, bracketed) {
+            info!("Paste held for confirmation: {}", warning.summary());
+            self.pending_paste = Some(paste::PendingPaste {
+                text: text.into_owned(),
+                warning,
+            });
+            return Ok(());
+        }
+        self.write_paste(&text)
+    }
+
+    /// Paste the text held for confirmation
+    pub fn confirm_paste(&mut self) -> Result<()> {
+        match self.pending_paste.take() {
+            Some(pending) => self.write_paste(&pending.text),
+            None => Ok(()),
+        }
+    }
+
+    /// Drop the text held for confirmation
+    pub fn cancel_paste(&mut self) {
+        if self.pending_paste.take().is_some() {
+            info!("Paste cancelled");
+        }
+    }
+
+    fn write_paste(&self, text: &str) -> Result<()> {
+        if self.grid.modes.bracketed_paste {
+            self.pty.write_all(b"\x1b[200~")?;
+            self.pty.write_all(text.as_bytes())?;
+            self.pty.write_all(b"\x1b[201~")?;
+        } else {
+            self.pty.write_all(text.as_bytes())?;
+        }
         Ok(())
     }
 
+    /// Paste a clipboard history entry and make it the current clipboard
+    pub fn paste_history_entry(&mut self, idx: usize) -> Result<()> {
+        let entry = ClipboardHistory::lock(&self.clipboard_history)
+            .entries()
+            .get(idx)
+            .cloned();
+        let Some(entry) = entry else {
+            return Ok(());
+        };
+        self.set_clipboard(&entry.text, entry.source);
+        self.paste_text(&entry.text)
+    }
+
     /// Check if mouse mode is enabled
     pub fn mouse_mode_enabled(&self) -> bool {
         self.grid.modes.mouse_mode != grid::MouseMode::None
@@ -2183,6 +2821,53 @@ impl Terminal {
         self.send_mouse_event(cb, col, row, pixel_coords, true)
     }
 
+    /// Absolute line shown at a display row
+    pub fn display_line(&self, display_row: usize) -> u64 {
+        self.grid.scrollback_total() + display_row as u64 - self.scroll_offset as u64
+    }
+
+    /// Display row of an absolute line (None when it is out of view)
+    pub fn line_display_row(&self, line: u64) -> Option<usize> {
+        let top = self.display_line(0);
+        (line >= top && line < top + self.grid.rows() as u64).then(|| (line - top) as usize)
+    }
+
+    /// Oldest absolute line still in the buffer (first scrollback row)
+    pub fn first_line(&self) -> u64 {
+        self.grid.scrollback_total() - self.grid.scrollback_len() as u64
+    }
+
+    /// Whether an absolute line continues on the next one (soft wrap)
+    pub fn line_wrapped(&self, line: u64) -> bool {
+        let screen_top = self.grid.scrollback_total();
+        if line >= screen_top {
+            let row = (line - screen_top) as usize;
+            row < self.grid.rows() && self.grid.row_wrapped(row)
+        } else if line >= self.first_line() {
+            self.grid
+                .scrollback_row_wrapped((line - self.first_line()) as usize)
+        } else {
+            false
+        }
+    }
+
+    /// Cell at an absolute line (empty once the line has been evicted)
+    pub fn line_cell(&self, line: u64, col: usize) -> &Cell {
+        let screen_top = self.grid.scrollback_total();
+        if line >= screen_top {
+            let row = (line - screen_top) as usize;
+            if row < self.grid.rows() && col < self.grid.cols() {
+                return self.grid.cell(row, col);
+            }
+        } else if line >= self.first_line() {
+            let idx = (line - self.first_line()) as usize;
+            if let Some(cell) = self.grid.scrollback_row(idx).and_then(|row| row.get(col)) {
+                return cell;
+            }
+        }
+        Cell::empty_ref()
+    }
+
     /// Get cell for display row (considering scroll_offset)
     ///
     /// display_row: row on screen (0 = top of screen)
@@ -2284,14 +2969,14 @@ impl Terminal {
     /// so copy URL to clipboard and notify.
     pub fn copy_url_to_clipboard(&mut self, url: &str) {
         info!("URL copied to clipboard: {}", url);
-        self.set_clipboard(url);
+        self.set_clipboard(url, ClipSource::Url);
     }
 
     // ========== Search functionality ==========
 
     /// Start search mode
-    pub fn start_search(&mut self) {
-        self.search = Some(SearchState::new());
+    pub fn start_search(&mut self, options: search::SearchOptions) {
+        self.search = Some(SearchState::new(options));
     }
 
     /// End search mode
@@ -2299,124 +2984,171 @@ impl Terminal {
         self.search = None;
     }
 
-    /// Execute search (search entire scrollback + grid)
+    /// Execute search: search the screen now and start a worker on the
+    /// scrollback. Cheap enough to run on every keystroke.
     pub fn execute_search(&mut self) {
-        let query = match &self.search {
-            Some(s) if !s.query.is_empty() => s.query.clone(),
-            _ => return,
+        let (query, options) = match &mut self.search {
+            Some(s) if !s.query.is_empty() => (s.query.clone(), s.options),
+            Some(s) => {
+                s.clear_results();
+                return;
+            }
+            None => return,
         };
 
-        let mut matches = Vec::new();
-        let cols = self.grid.cols();
-        let grid_rows = self.grid.rows();
+        let re = search::build_matcher(&query, options);
+        let index = self.grid.search_index().clone();
+        let generation = index.generation();
+        let Some(ref mut s) = self.search else {
+            return;
+        };
+        s.clear_results();
+        s.executed = true;
+        let re = match re {
+            Ok(re) => re,
+            Err(e) => {
+                s.error = Some(e);
+                return;
+            }
+        };
+        s.job = search::ScanJob::spawn(index, re.clone());
+        s.scanning = s.job.is_some();
+        s.matcher = Some(re);
+        s.generation = generation;
+        s.jump_pending = true;
+
+        self.search_screen();
+        self.finish_search_jump();
+    }
+
+    /// Search the screen rows (and the scrollback rows of a line that wraps
+    /// onto the screen), replacing the screen matches
+    fn search_screen(&mut self) {
+        let Some(re) = self.search.as_ref().and_then(|s| s.matcher.clone()) else {
+            return;
+        };
+        let first_line = self.first_line() as usize;
         let scrollback_len = self.grid.scrollback_len();
+        let mut line = search::LogicalLine::default();
+        let mut row = search::IndexedRow::default();
+        let mut found = Vec::new();
+
+        // Terminated scrollback lines belong to the worker
+        let mut start = scrollback_len;
+        while start > 0 && self.grid.scrollback_row_wrapped(start - 1) {
+            start -= 1;
+        }
+        for sb_row in start..scrollback_len {
+            if let Some(cells) = self.grid.scrollback_row(sb_row) {
+                row.fill(cells, true);
+                line.push_row(first_line + sb_row, &row);
+            }
+        }
+        for screen_row in 0..self.grid.rows() {
+            let wrapped = self.grid.row_wrapped(screen_row);
+            row.fill(self.grid.row_cells(screen_row), wrapped);
+            line.push_row(first_line + scrollback_len + screen_row, &row);
+            if !wrapped {
+                line.find(&re, &mut found);
+                line.clear();
+            }
+        }
+        if !line.is_empty() {
+            line.find(&re, &mut found);
+        }
 
-        // Build byte→char index once per line for O(1) byte-to-column lookup.
-        // This avoids repeated O(n) chars().count() calls per match.
-        let mut byte_to_char: Vec<usize> = Vec::new();
-
-        /// Build byte-to-char-index mapping for a string.
-        /// byte_to_char[byte_offset] = char_index at that byte offset.
-        fn build_byte_to_char(s: &str, buf: &mut Vec<usize>) {
-            buf.clear();
-            buf.reserve(s.len() + 1);
-            let mut char_idx = 0;
-            for (byte_idx, _) in s.char_indices() {
-                while buf.len() <= byte_idx {
-                    buf.push(char_idx);
+        let cols = self.grid.cols();
+        if let Some(ref mut s) = self.search {
+            s.set_screen_matches(found, cols);
+            s.screen_stale = false;
+        }
+    }
+
+    /// Once the nearest match is known, make it current and scroll to it
+    fn finish_search_jump(&mut self) {
+        let ready = match &mut self.search {
+            Some(s) if s.jump_pending => {
+                if s.screen_start < s.matches.len() {
+                    // Last match on screen
+                    s.current_match = s.matches.len() - 1;
+                    true
+                } else if !s.scanning && !s.matches.is_empty() {
+                    // Nothing on screen: newest scrollback match
+                    s.current_match = s.matches.len() - 1;
+                    true
+                } else {
+                    false
                 }
-                char_idx += 1;
             }
-            // Fill remaining bytes (for slicing up to s.len())
-            while buf.len() <= s.len() {
-                buf.push(char_idx);
+            _ => false,
+        };
+        if ready {
+            if let Some(ref mut s) = self.search {
+                s.jump_pending = false;
             }
+            self.scroll_to_current_match();
         }
+    }
 
-        // Search scrollback
-        for sb_row in 0..scrollback_len {
-            if let Some(row_cells) = self.grid.scrollback_row(sb_row) {
-                let line_text: String = row_cells
-                    .iter()
-                    .filter(|c| c.width != 0)
-                    .map(|c| {
-                        if c.grapheme.is_empty() {
-                            " "
-                        } else {
-                            c.grapheme.as_str()
-                        }
-                    })
-                    .collect();
-
-                build_byte_to_char(&line_text, &mut byte_to_char);
-
-                let mut search_pos = 0;
-                while let Some(pos) = line_text[search_pos..].find(&query) {
-                    let start_byte = search_pos + pos;
-                    let end_byte = start_byte + query.len();
-
-                    let start_col = byte_to_char[start_byte];
-                    let end_col = byte_to_char[end_byte];
+    /// Pull in streamed scrollback matches, drop evicted ones and re-search
+    /// the screen after new output. Returns true if the results changed.
+    pub fn poll_search(&mut self) -> bool {
+        let index = self.grid.search_index().clone();
+        let first_line = self.first_line() as usize;
+        let cols = self.grid.cols();
+        let Some(ref mut s) = self.search else {
+            return false;
+        };
+        if s.matcher.is_none() {
+            return false;
+        }
 
-                    matches.push((sb_row, start_col, end_col));
-                    search_pos = start_byte + 1;
-                }
-            }
+        // Scrollback rebuilt (resize reflow): start over
+        if index.generation() != s.generation {
+            self.execute_search();
+            return true;
         }
 
-        // Search grid
-        for row in 0..grid_rows {
-            let mut line_text = String::new();
-            for col in 0..cols {
-                let cell = self.grid.cell(row, col);
-                if cell.width == 0 {
-                    continue;
+        let mut changed = false;
+        let events = s.job.as_ref().map(|job| job.poll()).unwrap_or_default();
+        for event in events {
+            match event {
+                search::ScanEvent::Matches(found) => {
+                    s.insert_scrollback_matches(found, cols);
+                    changed = true;
                 }
-                if !cell.grapheme.is_empty() {
-                    line_text.push_str(&cell.grapheme);
-                } else {
-                    line_text.push(' ');
+                search::ScanEvent::CaughtUp => {
+                    s.scanning = false;
+                    changed = true;
+                }
+                search::ScanEvent::Invalidated => {
+                    self.execute_search();
+                    return true;
                 }
             }
+        }
 
-            build_byte_to_char(&line_text, &mut byte_to_char);
-
-            let mut search_pos = 0;
-            while let Some(pos) = line_text[search_pos..].find(&query) {
-                let start_byte = search_pos + pos;
-                let end_byte = start_byte + query.len();
-
-                let start_col = byte_to_char[start_byte];
-                let end_col = byte_to_char[end_byte];
+        let before = s.matches.len() + s.removed;
+        s.prune(first_line);
+        changed |= s.matches.len() + s.removed != before;
 
-                // Grid row is offset from scrollback_len
-                matches.push((scrollback_len + row, start_col, end_col));
-                search_pos = start_byte + 1;
-            }
+        if s.screen_stale {
+            self.search_screen();
+            changed = true;
         }
-
-        if let Some(ref mut s) = self.search {
-            s.matches = matches;
-            s.current_match = 0;
-            s.build_row_index();
+        if changed {
+            self.finish_search_jump();
         }
-
-        info!(
-            "Search '{}': {} matches",
-            query,
-            self.search.as_ref().map(|s| s.matches.len()).unwrap_or(0)
-        );
+        changed
     }
 
     /// Scroll to current match position
     pub fn scroll_to_current_match(&mut self) {
-        let (match_row, scrollback_len, grid_rows) = match &self.search {
-            Some(s) if !s.matches.is_empty() => {
-                let (row, _, _) = s.matches[s.current_match];
-                (row, self.grid.scrollback_len(), self.grid.rows())
-            }
-            _ => return,
+        let Some((match_row, _)) = self.current_search_match_pos() else {
+            return;
         };
+        let scrollback_len = self.grid.scrollback_len();
+        let grid_rows = self.grid.rows();
 
         // Calculate scroll_offset to display match_row
         if match_row < scrollback_len {
@@ -2435,8 +3167,17 @@ impl Terminal {
         }
     }
 
+    /// Start of the current match as (row, col), row counted from the
+    /// start of scrollback
+    pub fn current_search_match_pos(&self) -> Option<(usize, usize)> {
+        let s = self.search.as_ref()?;
+        let m = s.matches.get(s.current_match)?;
+        let first_line = self.first_line() as usize;
+        Some((m.start_row.checked_sub(first_line)?, m.start_col))
+    }
+
     /// Check if row has search matches (for highlighting)
-    /// Returns: list of match ranges (start_col, end_col, is_current)
+    /// Returns: list of match ranges (start_col, end_col, match_ordinal)
     pub fn get_search_matches_for_display_row(
         &self,
         display_row: usize,
@@ -2462,18 +3203,19 @@ impl Terminal {
                 scrollback_len + display_row - scrollback_rows_shown
             }
         };
+        let line = self.first_line() as usize + abs_row;
 
         // O(1) lookup instead of O(n) linear scan
         search
             .row_matches
-            .get(&abs_row)
+            .get(&line)
             .map(|v| v.as_slice())
             .unwrap_or(&EMPTY)
     }
 
-    /// Get current match index (for is_current check)
+    /// Get current match ordinal (for is_current check against row matches)
     pub fn current_search_match(&self) -> Option<usize> {
-        self.search.as_ref().map(|s| s.current_match)
+        self.search.as_ref().map(|s| s.current_match + s.removed)
     }
 
     // ========== Copy mode ==========
@@ -2483,7 +3225,7 @@ impl Terminal {
         // Start copy mode from current cursor position
         let cursor_row = self.grid.cursor_row;
         let cursor_col = self.grid.cursor_col;
-        self.copy_mode = Some(CopyModeState::new(cursor_row, cursor_col));
+        self.copy_mode = Some(CopyModeState::new(self.display_line(cursor_row), cursor_col));
         info!("Copy mode started: row={}, col={}", cursor_row, cursor_col);
     }
 
@@ -2491,71 +3233,36 @@ impl Terminal {
     pub fn exit_copy_mode(&mut self) {
         self.copy_mode = None;
         self.selection = None;
+        // Search results were kept for n / N; they end with copy mode
+        self.search = None;
         info!("Copy mode ended");
     }
 
-    /// Copy mode: move cursor
+    /// Copy mode: move cursor (scrolls when moving past the top or bottom)
     pub fn copy_mode_move(&mut self, delta_row: isize, delta_col: isize) {
-        if let Some(ref mut cm) = self.copy_mode {
-            let rows = self.grid.rows();
-            let cols = self.grid.cols();
-            let max_scroll = self.grid.scrollback_len();
-
-            // Row movement
-            let new_row = (cm.cursor_row as isize + delta_row).max(0) as usize;
-
-            // Scroll back if going past top
-            if delta_row < 0 && cm.cursor_row == 0 && self.scroll_offset < max_scroll {
-                self.scroll_offset += 1;
-                self.grid.mark_all_dirty();
-            }
-            // Scroll forward if going past bottom
-            else if delta_row > 0 && cm.cursor_row >= rows - 1 && self.scroll_offset > 0 {
-                self.scroll_offset -= 1;
-                self.grid.mark_all_dirty();
-            } else {
-                cm.cursor_row = new_row.min(rows - 1);
-            }
-
-            // Column movement
-            let new_col = (cm.cursor_col as isize + delta_col).max(0) as usize;
-            cm.cursor_col = new_col.min(cols - 1);
-
-            // Update selection if selecting
-            if cm.selecting {
-                self.selection = cm.get_selection();
-            }
-        }
+        let Some(ref cm) = self.copy_mode else {
+            return;
+        };
+        let cols = self.grid.cols();
+        let first = self.first_line() as i64;
+        let last = first + (self.grid.scrollback_len() + self.grid.rows()) as i64 - 1;
+        let line = (cm.cursor_row as i64 + delta_row as i64).clamp(first, last);
+        let col = (cm.cursor_col as isize + delta_col).clamp(0, cols as isize - 1);
+        self.copy_mode_set_cursor((line - first) as usize, col as usize);
     }
 
     /// Copy mode: go to beginning
     pub fn copy_mode_goto_top(&mut self) {
-        if let Some(ref mut cm) = self.copy_mode {
-            let new_offset = self.grid.scrollback_len();
-            if self.scroll_offset != new_offset {
-                self.scroll_offset = new_offset;
-                self.grid.mark_all_dirty();
-            }
-            cm.cursor_row = 0;
-            cm.cursor_col = 0;
-            if cm.selecting {
-                self.selection = cm.get_selection();
-            }
+        if self.copy_mode.is_some() {
+            self.copy_mode_set_cursor(0, 0);
         }
     }
 
     /// Copy mode: go to end
     pub fn copy_mode_goto_bottom(&mut self) {
-        if let Some(ref mut cm) = self.copy_mode {
-            if self.scroll_offset != 0 {
-                self.scroll_offset = 0;
-                self.grid.mark_all_dirty();
-            }
-            cm.cursor_row = self.grid.rows() - 1;
-            cm.cursor_col = 0;
-            if cm.selecting {
-                self.selection = cm.get_selection();
-            }
+        if self.copy_mode.is_some() {
+            let last = self.grid.scrollback_len() + self.grid.rows() - 1;
+            self.copy_mode_set_cursor(last, 0);
         }
     }
 
@@ -2576,11 +3283,11 @@ impl Terminal {
     }
 
     /// Copy mode: toggle selection
-    pub fn copy_mode_toggle_selection(&mut self) {
+    pub fn copy_mode_toggle_selection(&mut self, mode: copy_mode::VisualMode) {
         if let Some(ref mut cm) = self.copy_mode {
-            cm.toggle_selection();
+            cm.toggle_selection(mode);
             if cm.selecting {
-                self.selection = cm.get_selection();
+                self.selection = cm.get_selection(self.grid.cols());
             } else {
                 self.selection = None;
             }
@@ -2592,7 +3299,7 @@ impl Terminal {
         if let Some(ref mut cm) = self.copy_mode {
             cm.cursor_col = 0;
             if cm.selecting {
-                self.selection = cm.get_selection();
+                self.selection = cm.get_selection(self.grid.cols());
             }
         }
     }
@@ -2602,111 +3309,9 @@ impl Terminal {
         if let Some(ref mut cm) = self.copy_mode {
             cm.cursor_col = self.grid.cols() - 1;
             if cm.selecting {
-                self.selection = cm.get_selection();
-            }
-        }
-    }
-
-    /// Copy mode: move forward by word
-    pub fn copy_mode_word_forward(&mut self) {
-        // Get necessary values first to avoid borrow issues
-        let (row, start_col, cols) = match self.copy_mode {
-            Some(ref cm) => (cm.cursor_row, cm.cursor_col, self.grid.cols()),
-            None => return,
-        };
-
-        let mut col = start_col;
-
-        // If currently in a word, skip to end of word
-        while col < cols - 1 {
-            let cell = self.display_cell(row, col);
-            if cell
-                .grapheme
-                .chars()
-                .next()
-                .map(|c| c.is_whitespace())
-                .unwrap_or(true)
-            {
-                break;
+                self.selection = cm.get_selection(self.grid.cols());
             }
-            col += 1;
-        }
-        // Skip whitespace
-        while col < cols - 1 {
-            let cell = self.display_cell(row, col);
-            if !cell
-                .grapheme
-                .chars()
-                .next()
-                .map(|c| c.is_whitespace())
-                .unwrap_or(true)
-            {
-                break;
-            }
-            col += 1;
         }
-
-        // Apply result
-        if let Some(ref mut cm) = self.copy_mode {
-            cm.cursor_col = col;
-            if cm.selecting {
-                self.selection = cm.get_selection();
-            }
-        }
-    }
-
-    /// Copy mode: move backward by word
-    pub fn copy_mode_word_backward(&mut self) {
-        // Get necessary values first to avoid borrow issues
-        let (row, start_col) = match self.copy_mode {
-            Some(ref cm) => (cm.cursor_row, cm.cursor_col),
-            None => return,
-        };
-
-        let mut col = start_col;
-
-        // Skip whitespace
-        while col > 0 {
-            let cell = self.display_cell(row, col - 1);
-            if !cell
-                .grapheme
-                .chars()
-                .next()
-                .map(|c| c.is_whitespace())
-                .unwrap_or(true)
-            {
-                break;
-            }
-            col -= 1;
-        }
-        // Go to start of word
-        while col > 0 {
-            let cell = self.display_cell(row, col - 1);
-            if cell
-                .grapheme
-                .chars()
-                .next()
-                .map(|c| c.is_whitespace())
-                .unwrap_or(true)
-            {
-                break;
-            }
-            col -= 1;
-        }
-
-        // Apply result
-        if let Some(ref mut cm) = self.copy_mode {
-            cm.cursor_col = col;
-            if cm.selecting {
-                self.selection = cm.get_selection();
-            }
-        }
-    }
-
-    /// Copy mode: yank selection and exit
-    pub fn copy_mode_yank(&mut self) {
-        self.copy_selection();
-        self.exit_copy_mode();
     }
 
     // ========== Focus events ==========
@@ -2740,6 +3345,7 @@ impl Terminal {
         let mut performer = Performer::new(
             &mut self.grid,
             &mut self.clipboard,
+            &mut self.primary,
             &mut self.dcs_handler,
             &mut self.images,
             &mut self.dirty_image_ids,
@@ -2747,12 +3353,17 @@ impl Terminal {
             self.cell_height,
             &mut self.current_directory,
             &self.clipboard_path,
+            &self.primary_path,
+            &mut self.osc52_writes,
             &mut self.pty_response,
             &mut self.notifications,
             &mut self.notification_seq,
             &mut self.active_progress,
             &mut self.pending_notifications,
+            &mut self.notification_closes,
+            &mut self.notification_icons,
             &self.notifications_enabled,
+            &mut self.finished_commands,
         );
 
         for &byte in data {
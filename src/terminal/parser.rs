@@ -20,6 +20,19 @@ use vte::{Params, Perform};
 /// Maximum XTGETTCAP buffer size (64KB)
 const MAX_XTGETTCAP_BUFFER: usize = 64 * 1024;
 
+/// Maximum incomplete OSC 99 notifications (and queued close requests)
+const MAX_PENDING_NOTIFICATIONS: usize = 32;
+
+/// Maximum OSC 99 icon image size (decoded)
+const MAX_NOTIFICATION_ICON_SIZE: usize = 1024 * 1024;
+
+/// Maximum OSC 99 icons cached by `g=`
+const MAX_CACHED_NOTIFICATION_ICONS: usize = 16;
+
+/// OSC 99 `p=?` response payload: the supported keys and values
+const OSC99_CAPABILITIES: &str =
+    "a=focus,report:c=1:o=always,unfocused,invisible:p=title,body,?,close,icon:u=0,1,2:w=1";
+
 // ============================================================================
 // Helper functions
 // ============================================================================
@@ -53,7 +66,8 @@ const fn cursor_style_from_decscusr(param: u16) -> Option<(CursorStyle, bool)> {
 use super::grid::{CellAttrs, Color, CursorStyle, Grid, Hyperlink, UnderlineStyle};
 use super::sixel::SixelDecoder;
 use super::{
-    AnimationState, DcsHandler, ImageRegistry, Notification, NotificationProgress, TerminalImage,
+    AnimationState, DcsHandler, FinishedCommand, ImageRegistry, Notification,
+    NotificationProgress, NotifyOccasion, TerminalImage,
 };
 
 use std::collections::{HashMap, VecDeque};
@@ -116,6 +130,8 @@ fn parse_osc_color(data: &[u8]) -> Option<(u8, u8, u8)> {
 pub struct Performer<'a> {
     pub grid: &'a mut Grid,
     pub clipboard: &'a mut String,
+    /// Primary selection (OSC 52 `p`)
+    pub primary: &'a mut String,
     /// PTY response buffer (borrowed, not owned)
     pub pty_response: &'a mut Vec<u8>,
     pub dcs_handler: &'a mut Option<DcsHandler>,
@@ -131,6 +147,10 @@ pub struct Performer<'a> {
     pub current_dir: &'a mut Option<String>,
     /// Clipboard file path (OSC 52)
     clipboard_path: &'a str,
+    /// Primary selection file path (OSC 52 `p`)
+    primary_path: &'a str,
+    /// Texts written to the clipboard by OSC 52 (recorded in the history)
+    pub clipboard_writes: &'a mut Vec<String>,
     /// Notification history
     pub notifications: &'a mut VecDeque<Notification>,
     /// Monotonically increasing counter for toast detection
@@ -139,14 +159,21 @@ pub struct Performer<'a> {
     pub active_progress: &'a mut Option<NotificationProgress>,
     /// Pending (incomplete) OSC 99 notifications
     pub pending_notifications: &'a mut HashMap<String, Notification>,
+    /// OSC 99 ids the application asked to close
+    pub notification_closes: &'a mut Vec<String>,
+    /// OSC 99 icon data cached by `g=`
+    pub notification_icons: &'a mut HashMap<String, Arc<Vec<u8>>>,
     /// Whether notifications are enabled
     pub notifications_enabled: &'a bool,
+    /// Finished command history (OSC 133;D)
+    pub finished_commands: &'a mut VecDeque<FinishedCommand>,
 }
 
 impl<'a> Performer<'a> {
     pub fn new(
         grid: &'a mut Grid,
         clipboard: &'a mut String,
+        primary: &'a mut String,
         dcs_handler: &'a mut Option<DcsHandler>,
         images: &'a mut ImageRegistry,
         dirty_image_ids: &'a mut Vec<u32>,
@@ -154,16 +181,22 @@ impl<'a> Performer<'a> {
         cell_height: u32,
         current_dir: &'a mut Option<String>,
         clipboard_path: &'a str,
+        primary_path: &'a str,
+        clipboard_writes: &'a mut Vec<String>,
         pty_response: &'a mut Vec<u8>,
         notifications: &'a mut VecDeque<Notification>,
         notification_seq: &'a mut u64,
         active_progress: &'a mut Option<NotificationProgress>,
         pending_notifications: &'a mut HashMap<String, Notification>,
+        notification_closes: &'a mut Vec<String>,
+        notification_icons: &'a mut HashMap<String, Arc<Vec<u8>>>,
         notifications_enabled: &'a bool,
+        finished_commands: &'a mut VecDeque<FinishedCommand>,
     ) -> Self {
         Self {
             grid,
             clipboard,
+            primary,
             pty_response,
             dcs_handler,
             images,
@@ -172,11 +205,16 @@ impl<'a> Performer<'a> {
             cell_height,
             current_dir,
             clipboard_path,
+            primary_path,
+            clipboard_writes,
             notifications,
             notification_seq,
             active_progress,
             pending_notifications,
+            notification_closes,
+            notification_icons,
             notifications_enabled,
+            finished_commands,
         }
     }
 }
@@ -199,7 +237,11 @@ impl<'a> Perform for Performer<'a> {
             0x0D => self.grid.carriage_return(),     // CR
             0x0E => self.grid.shift_out(),           // SO - activate G1 charset
             0x0F => self.grid.shift_in(),            // SI - activate G0 charset
-            0x07 => self.grid.bell_triggered = true, // BEL
+            0x07 => {
+                // BEL
+                self.grid.bell_triggered = true;
+                self.grid.bell_count += 1;
+            }
             _ => {
                 trace!("Unhandled control character: 0x{:02x}", byte);
             }
@@ -594,10 +636,7 @@ impl<'a> Perform for Performer<'a> {
             }
             (b'c', []) => {
                 // RIS - Full Reset
-                let cols = self.grid.cols();
-                let rows = self.grid.rows();
-                let max_scrollback = self.grid.max_scrollback;
-                *self.grid = Grid::with_scrollback(cols, rows, max_scrollback);
+                self.grid.full_reset();
             }
             (b'7', []) => {
                 // DECSC - Save Cursor
@@ -1127,6 +1166,8 @@ impl<'a> Performer<'a> {
 
     /// OSC 52 (clipboard operation) handler
     /// Format: ESC ] 52 ; <selection> ; <base64-data> ST
+    /// Selection `p` is the primary selection; `c`, `s`, cut buffers and an
+    /// empty selection all use the clipboard.
     fn handle_osc_52(&mut self, params: &[&[u8]]) {
         // Max payload size (10MB base64 = ~7.5MB decoded)
         const MAX_OSC52_PAYLOAD: usize = 10 * 1024 * 1024;
@@ -1140,6 +1181,7 @@ impl<'a> Performer<'a> {
             return;
         }
 
+        let targets = params[1];
         let data = params[2];
 
         // Security: reject oversized payloads before any processing/logging
@@ -1151,24 +1193,39 @@ impl<'a> Performer<'a> {
         trace!("OSC 52: data len={}", data.len());
 
         if data == b"?" {
-            // Query: read from shared clipboard file for cross-pane sharing
-            if let Ok(text) = std::fs::read_to_string(self.clipboard_path) {
+            // Query the first target: read from the shared file for cross-pane sharing
+            let (name, buffer, text) = if targets.first() == Some(&b'p') {
+                let text = super::clipboard::read_private(self.primary_path);
+                ('p', &mut *self.primary, text)
+            } else {
+                let text = std::fs::read_to_string(self.clipboard_path);
+                ('c', &mut *self.clipboard, text)
+            };
+            if let Ok(text) = text {
                 if !text.is_empty() {
-                    *self.clipboard = text;
+                    *buffer = text;
                 }
             }
             use std::fmt::Write;
-            let encoded = base64_encode(self.clipboard.as_bytes());
+            let encoded = base64_encode(buffer.as_bytes());
             let mut response = String::new();
-            write!(&mut response, "\x1b]52;c;{}\x1b\\", encoded).ok();
+            write!(&mut response, "\x1b]52;{};{}\x1b\\", name, encoded).ok();
             self.pty_response.extend_from_slice(response.as_bytes());
         } else {
-            // Set: decode base64 and store in clipboard
+            // Set: decode base64 and store in every requested selection
             if let Some(decoded) = base64_decode(data) {
                 if let Ok(text) = String::from_utf8(decoded) {
-                    *self.clipboard = text.clone();
-                    let _ = std::fs::write(self.clipboard_path, &text);
-                    trace!("OSC 52: clipboard set ({} chars)", text.len());
+                    if targets.contains(&b'p') {
+                        *self.primary = text.clone();
+                        let _ = super::clipboard::write_private(self.primary_path, &text);
+                        trace!("OSC 52: primary set ({} chars)", text.len());
+                    }
+                    if targets.is_empty() || targets.iter().any(|&t| t != b'p') {
+                        *self.clipboard = text.clone();
+                        let _ = std::fs::write(self.clipboard_path, &text);
+                        trace!("OSC 52: clipboard set ({} chars)", text.len());
+                        self.clipboard_writes.push(text.clone());
+                    }
                 }
             }
         }
@@ -1302,6 +1359,7 @@ impl<'a> Performer<'a> {
                 // Command started (user pressed enter)
                 trace!("Shell integration: command execution started");
                 self.grid.shell.command_row = Some(self.grid.cursor_row);
+                self.grid.shell.command_started = Some(std::time::Instant::now());
             }
             _ if marker.starts_with("D") => {
                 // Command finished: D or D;exit_code
@@ -1320,6 +1378,14 @@ impl<'a> Performer<'a> {
                 );
                 self.grid.shell.last_exit_code = exit_code;
                 self.grid.shell.command_row = None;
+                self.grid.shell.commands_finished += 1;
+                self.finished_commands.push_back(FinishedCommand {
+                    exit_code,
+                    duration: self.grid.shell.command_started.take().map(|t| t.elapsed()),
+                });
+                if self.finished_commands.len() > super::MAX_FINISHED_COMMANDS {
+                    self.finished_commands.pop_front();
+                }
             }
             _ => {
                 trace!("Unhandled OSC 133: marker={}", marker);
@@ -1478,13 +1544,9 @@ impl<'a> Performer<'a> {
             title.push_str(&String::from_utf8_lossy(p));
         }
         info!("OSC 9: notification '{}'", title);
-        self.push_notification(Notification {
-            id: None,
-            title,
-            body: String::new(),
-            urgency: 1,
-            timestamp: std::time::Instant::now(),
-        });
+        let mut notif = Notification::new(None, 1);
+        notif.title = title;
+        self.push_notification(notif);
     }
 
     /// OSC 99 (Kitty notification protocol) handler
@@ -1495,68 +1557,91 @@ impl<'a> Performer<'a> {
             return;
         }
 
-        // Parse metadata from params[1]
-        let meta_str = String::from_utf8_lossy(params[1]);
-        let payload = if params.len() > 2 {
-            String::from_utf8_lossy(params[2]).to_string()
+        let meta = Osc99Metadata::parse(&String::from_utf8_lossy(params[1]));
+        // Rejoin the payload (it may contain ';')
+        let mut raw = params.get(2).map(|p| p.to_vec()).unwrap_or_default();
+        for p in params.iter().skip(3) {
+            raw.push(b';');
+            raw.extend_from_slice(p);
+        }
+        // Icon data is always base64, text only with e=1
+        let payload = if meta.base64 || meta.payload_type == "icon" {
+            match base64_decode(&raw) {
+                Some(decoded) => decoded,
+                None => {
+                    warn!("OSC 99: invalid base64 payload");
+                    return;
+                }
+            }
         } else {
-            String::new()
+            raw
         };
 
-        // Parse key=value pairs from metadata
-        let mut id: Option<String> = None;
-        let mut done = true; // d=1 is default (complete)
-        let mut payload_type = String::from("title"); // p=title is default
-        let mut urgency: u8 = 1;
-
-        for kv in meta_str.split(':') {
-            if let Some((key, value)) = kv.split_once('=') {
-                match key {
-                    "i" => id = Some(value.to_string()),
-                    "d" => done = value != "0",
-                    "p" => payload_type = value.to_string(),
-                    "u" => urgency = value.parse().unwrap_or(1).min(2),
-                    "e" => {
-                        // e=1 means close notification with this id
-                        if value == "1" {
-                            if let Some(ref close_id) = id {
-                                self.pending_notifications.remove(close_id);
-                            }
-                            return;
-                        }
+        match meta.payload_type.as_str() {
+            "?" => {
+                // Report capabilities per Kitty notification spec:
+                //   metadata: i=<id>:p=?  (echo back query marker)
+                //   payload:  key=values pairs of what is supported
+                let response = format!(
+                    "\x1b]99;i={}:p=?;{}\x1b\\",
+                    meta.id.as_deref().unwrap_or(""),
+                    OSC99_CAPABILITIES
+                );
+                self.pty_response.extend_from_slice(response.as_bytes());
+                debug!("OSC 99: query response sent");
+                return;
+            }
+            "close" => {
+                // The application closes its notification (toast / desktop)
+                if let Some(id) = meta.id {
+                    self.pending_notifications.remove(&id);
+                    if self.notification_closes.len() < MAX_PENDING_NOTIFICATIONS {
+                        debug!("OSC 99: close requested (id={})", id);
+                        self.notification_closes.push(id);
                     }
-                    _ => {}
                 }
+                return;
+            }
+            "title" | "body" | "icon" => {}
+            other => {
+                debug!("OSC 99: unsupported payload type '{}'", other);
+                return;
             }
         }
 
-        // Handle query (p=?)
-        if payload_type == "?" {
-            // Report capabilities per Kitty notification spec:
-            //   metadata: i=<id>:p=?  (echo back query marker)
-            //   payload:  p=title,body:a=focus:o=always:u=0,1,2
-            let resp_id = id.as_deref().unwrap_or("");
-            let response = format!(
-                "\x1b]99;i={}:p=?;p=title,body:a=focus:o=always:u=0,1,2\x1b\\",
-                resp_id
-            );
-            self.pty_response.extend_from_slice(response.as_bytes());
-            debug!("OSC 99: query response sent");
-            return;
+        // Chunks are collected under the id (or a unique key without one)
+        let key = match (&meta.id, meta.done) {
+            (Some(id), _) => id.clone(),
+            (None, false) => format!("_auto_{}", self.notification_seq),
+            (None, true) => String::new(),
+        };
+        let mut notif = match self.pending_notifications.remove(&key) {
+            Some(pending) => pending,
+            None => Notification::new(meta.id.clone(), 1),
+        };
+        meta.apply(&mut notif);
+        match meta.payload_type.as_str() {
+            "title" => notif.title.push_str(&String::from_utf8_lossy(&payload)),
+            "body" => notif.body.push_str(&String::from_utf8_lossy(&payload)),
+            _ => {
+                let mut data = notif
+                    .icon_data
+                    .take()
+                    .map(Arc::unwrap_or_clone)
+                    .unwrap_or_default();
+                if data.len() + payload.len() <= MAX_NOTIFICATION_ICON_SIZE {
+                    data.extend_from_slice(&payload);
+                } else {
+                    warn!("OSC 99: icon data too large, dropped");
+                }
+                notif.icon_data = Some(Arc::new(data));
+            }
         }
 
-        if !done {
-            // Incomplete notification: store/update in pending
-            // Use a unique auto-generated key when id is not provided to avoid collisions
-            let notif_id = id
-                .clone()
-                .unwrap_or_else(|| format!("_auto_{}", self.notification_seq));
-
+        if !meta.done {
+            // Incomplete notification: keep collecting chunks
             // Cap pending notifications to prevent unbounded accumulation
-            const MAX_PENDING: usize = 32;
-            if !self.pending_notifications.contains_key(&notif_id)
-                && self.pending_notifications.len() >= MAX_PENDING
-            {
+            if self.pending_notifications.len() >= MAX_PENDING_NOTIFICATIONS {
                 // Evict oldest pending notification by timestamp
                 if let Some(oldest_key) = self
                     .pending_notifications
@@ -1567,66 +1652,41 @@ impl<'a> Performer<'a> {
                     self.pending_notifications.remove(&oldest_key);
                 }
             }
+            self.pending_notifications.insert(key, notif);
+            debug!("OSC 99: pending notification updated (id={:?})", meta.id);
+            return;
+        }
 
-            let entry = self
-                .pending_notifications
-                .entry(notif_id)
-                .or_insert_with(|| Notification {
-                    id: id.clone(),
-                    title: String::new(),
-                    body: String::new(),
-                    urgency,
-                    timestamp: std::time::Instant::now(),
-                });
-            match payload_type.as_str() {
-                "title" => entry.title = payload,
-                "body" => entry.body = payload,
-                _ => {}
-            }
-            debug!("OSC 99: pending notification updated (id={:?})", id);
-        } else {
-            // Complete notification
-            let notif_id = id.clone().unwrap_or_default();
-            // Note: for complete (d=1, default) notifications without id,
-            // we use empty string since there's nothing to look up in pending
-
-            // Check if there's a pending notification to complete
-            let mut notif = if let Some(mut pending) = self.pending_notifications.remove(&notif_id)
-            {
-                // Update with final payload
-                match payload_type.as_str() {
-                    "title" => pending.title = payload,
-                    "body" => pending.body = payload,
-                    _ => {}
-                }
-                pending.urgency = urgency;
-                pending
-            } else {
-                // New complete notification
-                let mut n = Notification {
-                    id: id.clone(),
-                    title: String::new(),
-                    body: String::new(),
-                    urgency,
-                    timestamp: std::time::Instant::now(),
-                };
-                match payload_type.as_str() {
-                    "title" => n.title = payload,
-                    "body" => n.body = payload,
-                    _ => {}
+        // Icon data with g= is cached for later notifications with the same g=
+        if let Some(cache_id) = &notif.icon_cache_id {
+            match &notif.icon_data {
+                Some(data) => {
+                    if self.notification_icons.len() >= MAX_CACHED_NOTIFICATION_ICONS
+                        && !self.notification_icons.contains_key(cache_id)
+                    {
+                        self.notification_icons.clear();
+                    }
+                    self.notification_icons
+                        .insert(cache_id.clone(), data.clone());
                 }
-                n
-            };
-
-            // Default title if empty
-            if notif.title.is_empty() && !notif.body.is_empty() {
-                notif.title = notif.body.clone();
-                notif.body = String::new();
+                None => notif.icon_data = self.notification_icons.get(cache_id).cloned(),
             }
+        }
 
-            info!("OSC 99: notification '{}' (id={:?})", notif.title, notif.id);
-            self.push_notification(notif);
+        // Default title if empty
+        if notif.title.is_empty() && !notif.body.is_empty() {
+            notif.title = std::mem::take(&mut notif.body);
         }
+        if notif.title.is_empty() {
+            debug!(
+                "OSC 99: notification without text ignored (id={:?})",
+                notif.id
+            );
+            return;
+        }
+
+        info!("OSC 99: notification '{}' (id={:?})", notif.title, notif.id);
+        self.push_notification(notif);
     }
 
     /// Push a notification to the history list (capped at MAX_NOTIFICATIONS)
@@ -1801,6 +1861,129 @@ fn hex_decode(input: &[u8]) -> Vec<u8> {
     output
 }
 
+// ========== OSC 99 metadata ==========
+
+/// Metadata of one OSC 99 chunk. Keys other than i, d, p and e apply to
+/// the notification only when given.
+#[derive(Debug, PartialEq)]
+struct Osc99Metadata {
+    /// i=
+    id: Option<String>,
+    /// d= (default 1: the notification is complete)
+    done: bool,
+    /// p= (default "title")
+    payload_type: String,
+    /// e=1: the payload is base64
+    base64: bool,
+    /// u=
+    urgency: Option<u8>,
+    /// a=: (focus, report)
+    actions: Option<(bool, bool)>,
+    /// c=
+    report_close: Option<bool>,
+    /// o=
+    occasion: Option<NotifyOccasion>,
+    /// w=
+    expire_ms: Option<i32>,
+    /// g=
+    icon_cache_id: Option<String>,
+    /// n= (base64, may be repeated)
+    icon_names: Vec<String>,
+}
+
+impl Osc99Metadata {
+    fn parse(meta: &str) -> Self {
+        let mut parsed = Self {
+            id: None,
+            done: true,
+            payload_type: "title".to_string(),
+            base64: false,
+            urgency: None,
+            actions: None,
+            report_close: None,
+            occasion: None,
+            expire_ms: None,
+            icon_cache_id: None,
+            icon_names: Vec::new(),
+        };
+        for kv in meta.split(':') {
+            let Some((key, value)) = kv.split_once('=') else {
+                continue;
+            };
+            match key {
+                "i" => parsed.id = Some(value.to_string()),
+                "d" => parsed.done = value != "0",
+                "p" => parsed.payload_type = value.to_string(),
+                "e" => parsed.base64 = value == "1",
+                "u" => parsed.urgency = Some(value.parse().unwrap_or(1).min(2)),
+                "a" => {
+                    // Comma-separated actions, "-" prefix removes one
+                    let (mut focus, mut report) = (true, false);
+                    for action in value.split(',') {
+                        match action {
+                            "focus" => focus = true,
+                            "-focus" => focus = false,
+                            "report" => report = true,
+                            "-report" => report = false,
+                            _ => {}
+                        }
+                    }
+                    parsed.actions = Some((focus, report));
+                }
+                "c" => parsed.report_close = Some(value == "1"),
+                "o" => {
+                    parsed.occasion = match value {
+                        "always" => Some(NotifyOccasion::Always),
+                        "unfocused" => Some(NotifyOccasion::Unfocused),
+                        "invisible" => Some(NotifyOccasion::Invisible),
+                        _ => None,
+                    }
+                }
+                "w" => parsed.expire_ms = value.parse().ok().map(|ms: i32| ms.max(-1)),
+                "g" => parsed.icon_cache_id = Some(value.to_string()),
+                "n" => {
+                    if let Some(name) = base64_decode(value.as_bytes())
+                        .and_then(|name| String::from_utf8(name).ok())
+                        .filter(|name| !name.is_empty())
+                    {
+                        parsed.icon_names.push(name);
+                    }
+                }
+                _ => {}
+            }
+        }
+        parsed
+    }
+
+    /// Apply the keys that were given to a notification
+    fn apply(&self, notif: &mut Notification) {
+        if let Some(urgency) = self.urgency {
+            notif.urgency = urgency;
+        }
+        if let Some((focus, report)) = self.actions {
+            notif.focus_on_activate = focus;
+            notif.report_activation = report;
+        }
+        if let Some(close) = self.report_close {
+            notif.report_close = close;
+        }
+        if let Some(occasion) = self.occasion {
+            notif.occasion = occasion;
+        }
+        if let Some(expire_ms) = self.expire_ms {
+            notif.expire_ms = expire_ms;
+        }
+        if let Some(cache_id) = &self.icon_cache_id {
+            notif.icon_cache_id = Some(cache_id.clone());
+        }
+        for name in &self.icon_names {
+            if !notif.icon_names.contains(name) {
+                notif.icon_names.push(name.clone());
+            }
+        }
+    }
+}
+
 // ========== Base64 encode/decode ==========
 
 const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
@@ -1854,3 +2037,114 @@ fn base64_decode(input: &[u8]) -> Option<Vec<u8>> {
     }
     Some(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Notification state a Performer writes into
+    #[derive(Default)]
+    struct NotifyState {
+        notifications: VecDeque<Notification>,
+        seq: u64,
+        pending: HashMap<String, Notification>,
+        closes: Vec<String>,
+        icons: HashMap<String, Arc<Vec<u8>>>,
+        response: Vec<u8>,
+        commands: VecDeque<FinishedCommand>,
+    }
+
+    fn feed(state: &mut NotifyState, input: &str) {
+        let mut grid = Grid::new(80, 24);
+        let (mut clipboard, mut primary) = (String::new(), String::new());
+        let mut dcs_handler = None;
+        let mut images = ImageRegistry::new();
+        let mut dirty_image_ids = Vec::new();
+        let mut current_dir = None;
+        let mut clipboard_writes = Vec::new();
+        let mut active_progress = None;
+        let mut performer = Performer::new(
+            &mut grid,
+            &mut clipboard,
+            &mut primary,
+            &mut dcs_handler,
+            &mut images,
+            &mut dirty_image_ids,
+            8,
+            16,
+            &mut current_dir,
+            "",
+            "",
+            &mut clipboard_writes,
+            &mut state.response,
+            &mut state.notifications,
+            &mut state.seq,
+            &mut active_progress,
+            &mut state.pending,
+            &mut state.closes,
+            &mut state.icons,
+            &true,
+            &mut state.commands,
+        );
+        let mut parser = vte::Parser::new();
+        for byte in input.bytes() {
+            parser.advance(&mut performer, byte);
+        }
+    }
+
+    #[test]
+    fn test_osc_99() {
+        let mut state = NotifyState::default();
+        feed(&mut state, "\x1b]99;i=q:p=?;\x1b\\");
+        assert_eq!(
+            String::from_utf8_lossy(&state.response),
+            format!("\x1b]99;i=q:p=?;{}\x1b\\", OSC99_CAPABILITIES)
+        );
+
+        // Chunks are joined; e=1 payloads are base64
+        feed(
+            &mut state,
+            "\x1b]99;i=1:d=0:u=2:o=unfocused:w=0:a=report:c=1;Hel\x1b\\\
+             \x1b]99;i=1:d=0;lo\x1b\\\
+             \x1b]99;i=1:p=body:e=1;Qm9keQ==\x1b\\",
+        );
+        let n = state.notifications.back().unwrap();
+        assert_eq!((n.title.as_str(), n.body.as_str()), ("Hello", "Body"));
+        assert_eq!(
+            (n.urgency, n.occasion, n.expire_ms),
+            (2, NotifyOccasion::Unfocused, 0)
+        );
+        assert!(n.focus_on_activate && n.report_activation && n.report_close);
+        assert!(state.pending.is_empty());
+
+        // Icon data is cached by g= for later notifications
+        feed(
+            &mut state,
+            "\x1b]99;i=2:d=0:g=logo:n=ZGlhbG9nLWluZm9ybWF0aW9u;Build\x1b\\\
+             \x1b]99;i=2:p=icon;AQID\x1b\\\
+             \x1b]99;i=3:g=logo;Again\x1b\\",
+        );
+        let icon = |i: usize| state.notifications[i].icon_data.as_deref().cloned();
+        assert_eq!(icon(1), Some(vec![1, 2, 3]));
+        assert_eq!(icon(2), Some(vec![1, 2, 3]));
+        assert_eq!(state.notifications[1].icon_names, ["dialog-information"]);
+        assert_eq!(state.seq, 3);
+
+        feed(&mut state, "\x1b]99;i=1:p=close;\x1b\\");
+        assert_eq!(state.closes, ["1"]);
+        assert_eq!(state.seq, 3);
+    }
+    #[test]
+    fn test_osc_133_records_each_command() {
+        let mut state = NotifyState::default();
+        // Two commands finishing in one read are both kept
+        feed(
+            &mut state,
+            "\x1b]133;C\x07\x1b]133;D;2\x07\x1b]133;C\x07\x1b]133;D;0\x07\x1b]133;D\x07",
+        );
+        let codes: Vec<_> = state.commands.iter().map(|c| c.exit_code).collect();
+        assert_eq!(codes, [Some(2), Some(0), None]);
+        assert!(state.commands[0].duration.is_some());
+        assert!(state.commands[2].duration.is_none());
+    }
+}
@@ -0,0 +1,157 @@
+//! Paste safety
+//!
+//! Pasted text is sanitized before it reaches the PTY: ESC and C1 controls
+//! are stripped, so a pasted `ESC [201~` cannot end bracketed paste early and
+//! run the rest as typed input. Pastes that would execute on arrival
+//! (newlines or control characters without bracketed paste) or that are
+//! unusually large wait for confirmation.
+
+use std::borrow::Cow;
+
+/// Paste safety settings (config: clipboard.paste_*)
+#[derive(Debug, Clone)]
+pub struct PasteSafety {
+    /// Strip ESC and C1 control characters
+    pub sanitize: bool,
+    /// Confirm multi-line / control character pastes without bracketed paste
+    pub confirm: bool,
+    /// Confirm pastes larger than this many bytes (0 = never)
+    pub warn_size: usize,
+}
+
+impl Default for PasteSafety {
+    fn default() -> Self {
+        Self {
+            sanitize: true,
+            confirm: true,
+            warn_size: 64 * 1024,
+        }
+    }
+}
+
+/// Why a paste needs confirmation
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasteWarning {
+    pub lines: usize,
+    pub bytes: usize,
+    /// Contains newlines and the application has no bracketed paste
+    pub multi_line: bool,
+    /// Contains control characters other than tab / newline
+    pub control: bool,
+    /// Larger than the warning threshold
+    pub large: bool,
+}
+
+/// A paste waiting for confirmation
+#[derive(Debug, Clone)]
+pub struct PendingPaste {
+    pub text: String,
+    pub warning: PasteWarning,
+}
+
+/// ESC and C1 controls (U+0080-U+009F, which includes the 8-bit CSI)
+fn is_escape(c: char) -> bool {
+    c == '\x1b' || ('\u{80}'..='\u{9f}').contains(&c)
+}
+
+/// Remove ESC and C1 control characters
+pub fn sanitize(text: &str) -> Cow<'_, str> {
+    if text.chars().any(is_escape) {
+        Cow::Owned(text.chars().filter(|&c| !is_escape(c)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+impl PasteSafety {
+    /// Text to paste after sanitizing
+    pub fn prepare<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.sanitize {
+            sanitize(text)
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Whether `text` needs confirmation before pasting
+    pub fn check(&self, text: &str, bracketed: bool) -> Option<PasteWarning> {
+        let newline = text.contains(['\n', '\r']);
+        let control = text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'));
+        let multi_line = self.confirm && !bracketed && newline;
+        let control = self.confirm && !bracketed && control;
+        let large = self.warn_size > 0 && text.len() > self.warn_size;
+        (multi_line || control || large).then(|| PasteWarning {
+            lines: text.lines().count().max(1),
+            bytes: text.len(),
+            multi_line,
+            control,
+            large,
+        })
+    }
+}
+
+impl PasteWarning {
+    /// One-line description for the confirmation overlay
+    pub fn summary(&self) -> String {
+        let size = if self.bytes >= 1024 * 1024 {
+            format!("{:.1} MB", self.bytes as f64 / (1024.0 * 1024.0))
+        } else if self.bytes >= 1024 {
+            format!("{:.1} KB", self.bytes as f64 / 1024.0)
+        } else {
+            format!("{} bytes", self.bytes)
+        };
+        let mut reasons = Vec::new();
+        if self.multi_line {
+            reasons.push("runs each line (no bracketed paste)");
+        }
+        if self.control {
+            reasons.push("contains control characters");
+        }
+        if self.large {
+            reasons.push("large");
+        }
+        let lines = if self.lines == 1 { "line" } else { "lines" };
+        format!("Paste {} {} ({})? {}", self.lines, lines, size, reasons.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert!(matches!(sanitize("plain\ttext\n"), Cow::Borrowed(_)));
+        assert_eq!(sanitize("a\x1b[201~rm -rf ~\n"), "a[201~rm -rf ~\n");
+        assert_eq!(sanitize("x\u{9b}31mY\u{85}z"), "x31mYz");
+        assert_eq!(sanitize("日本語"), "日本語");
+    }
+
+    #[test]
+    fn test_check() {
+        let safety = PasteSafety::default();
+        assert_eq!(safety.check("ls -la", false), None);
+        assert_eq!(safety.check("echo a\necho b\n", true), None);
+
+        let warning = safety.check("echo a\necho b\n", false).unwrap();
+        assert!(warning.multi_line && !warning.control && !warning.large);
+        assert_eq!(warning.lines, 2);
+
+        let warning = safety.check("a\x03b", false).unwrap();
+        assert!(warning.control);
+
+        // Size warning applies even with bracketed paste
+        let big = "x".repeat(safety.warn_size + 1);
+        assert!(safety.check(&big, true).unwrap().large);
+
+        let off = PasteSafety {
+            sanitize: false,
+            confirm: false,
+            warn_size: 0,
+        };
+        assert_eq!(off.check("a\nb", false), None);
+        assert_eq!(off.prepare("\x1b"), "\x1b");
+    }
+}
@@ -51,6 +51,19 @@ impl Pty {
         term_env: &str,
         extra_env: &[(&str, &str)],
         uid: u32,
+    ) -> Result<Self> {
+        Self::spawn_command_as_user(cols, rows, term_env, extra_env, uid, None)
+    }
+
+    /// Like `spawn_as_user`, but runs `command` through the user's login
+    /// shell (`$SHELL -c command`) instead of an interactive shell.
+    pub fn spawn_command_as_user(
+        cols: u16,
+        rows: u16,
+        term_env: &str,
+        extra_env: &[(&str, &str)],
+        uid: u32,
+        command: Option<&str>,
     ) -> Result<Self> {
         let winsize = Winsize {
             ws_row: rows,
@@ -60,26 +73,8 @@ impl Pty {
         };
 
         // Look up user info before fork
-        let pwd = unsafe { libc::getpwuid(uid) };
-        if pwd.is_null() {
-            return Err(anyhow!("getpwuid({}) failed", uid));
-        }
-        let (shell, home, user_name, gid) = unsafe {
-            let shell = std::ffi::CStr::from_ptr((*pwd).pw_shell)
-                .to_str()
-                .unwrap_or("/bin/sh")
-                .to_string();
-            let home = std::ffi::CStr::from_ptr((*pwd).pw_dir)
-                .to_str()
-                .unwrap_or("/")
-                .to_string();
-            let name = std::ffi::CStr::from_ptr((*pwd).pw_name)
-                .to_str()
-                .unwrap_or("")
-                .to_string();
-            let gid = (*pwd).pw_gid;
-            (shell, home, name, gid)
-        };
+        let user = UserAccount::lookup(uid)?;
+        let shell = user.shell.clone();
 
         let ForkptyResult {
             master,
@@ -88,30 +83,23 @@ impl Pty {
 
         match fork_result {
             ForkResult::Child => {
-                // Drop privileges: set groups, gid, uid
-                unsafe {
-                    let user_cstr = std::ffi::CString::new(user_name.as_str())
-                        .unwrap_or_else(|_| std::ffi::CString::new("").unwrap());
-                    libc::initgroups(user_cstr.as_ptr(), gid);
-                    libc::setgid(gid);
-                    libc::setuid(uid);
+                // Drop privileges: set groups, gid, uid (never run the shell
+                // as root when that fails)
+                if user.become_user().is_err() {
+                    unsafe { libc::_exit(1) };
                 }
 
                 // Set environment
                 std::env::set_var("TERM", term_env);
                 std::env::set_var("COLORTERM", "truecolor");
                 std::env::set_var("TERM_PROGRAM", "bcon");
-                std::env::set_var("HOME", &home);
-                std::env::set_var("USER", &user_name);
-                std::env::set_var("LOGNAME", &user_name);
-                std::env::set_var("SHELL", &shell);
 
                 for (key, value) in extra_env {
                     std::env::set_var(key, value);
                 }
 
                 // cd to home directory
-                let _ = std::env::set_current_dir(&home);
+                let _ = std::env::set_current_dir(&user.home);
 
                 // Exec user's shell as login shell
                 let shell_cstr = match std::ffi::CString::new(shell.as_str()) {
@@ -126,8 +114,15 @@ impl Pty {
                     Ok(s) => s,
                     Err(_) => std::process::exit(1),
                 };
+                let mut argv = vec![argv0];
+                if let Some(command) = command {
+                    match std::ffi::CString::new(command) {
+                        Ok(c) => argv.extend([std::ffi::CString::new("-c").unwrap(), c]),
+                        Err(_) => std::process::exit(1),
+                    }
+                }
 
-                match nix::unistd::execvp(&shell_cstr, &[&argv0]) {
+                match nix::unistd::execvp(&shell_cstr, &argv) {
                     Ok(infallible) => match infallible {},
                     Err(e) => {
                         eprintln!("bcon: failed to spawn shell: {}", e);
@@ -394,6 +389,19 @@ impl Pty {
         }
     }
 
+    /// Check if the line discipline echoes input
+    ///
+    /// Password prompts (sudo, ssh, passwd) turn ECHO off.
+    /// Returns true if the termios cannot be read.
+    pub fn echo_enabled(&self) -> bool {
+        match nix::sys::termios::tcgetattr(&self.master) {
+            Ok(attrs) => attrs
+                .local_flags
+                .contains(nix::sys::termios::LocalFlags::ECHO),
+            Err(_) => true,
+        }
+    }
+
     /// Get foreground process name
     ///
     /// Gets the PTY's foreground process group
@@ -637,6 +645,223 @@ impl Pty {
     }
 }
 
+/// Login user account (from the passwd database)
+#[derive(Debug, Clone)]
+pub struct UserAccount {
+    pub uid: u32,
+    pub gid: u32,
+    pub name: String,
+    pub home: String,
+    pub shell: String,
+    /// `name` for initgroups, built before fork (the child must not allocate)
+    c_name: std::ffi::CString,
+}
+
+impl UserAccount {
+    /// Look up a user by UID
+    pub fn lookup(uid: u32) -> Result<Self> {
+        let pwd = unsafe { libc::getpwuid(uid) };
+        if pwd.is_null() {
+            return Err(anyhow!("getpwuid({}) failed", uid));
+        }
+        unsafe {
+            let shell = std::ffi::CStr::from_ptr((*pwd).pw_shell)
+                .to_str()
+                .unwrap_or("/bin/sh")
+                .to_string();
+            let home = std::ffi::CStr::from_ptr((*pwd).pw_dir)
+                .to_str()
+                .unwrap_or("/")
+                .to_string();
+            let c_name = std::ffi::CStr::from_ptr((*pwd).pw_name).to_owned();
+            let name = c_name.to_str().unwrap_or("").to_string();
+            Ok(Self {
+                uid,
+                gid: (*pwd).pw_gid,
+                name,
+                c_name,
+                home,
+                shell: if shell.is_empty() { "/bin/sh".to_string() } else { shell },
+            })
+        }
+    }
+
+    /// Set groups, gid and uid. Only call in a forked child before exec;
+    /// the child must not run anything if this fails.
+    pub fn drop_privileges(&self) -> io::Result<()> {
+        unsafe {
+            if libc::initgroups(self.c_name.as_ptr(), self.gid) != 0
+                || libc::setgid(self.gid) != 0
+                || libc::setuid(self.uid) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Set groups, gid and uid of the calling thread only. The kernel keeps
+    /// credentials per thread, while the libc calls in `drop_privileges`
+    /// change every thread of the process. The thread, and the threads it
+    /// starts, can never become root again.
+    pub fn drop_thread_privileges(&self) -> io::Result<()> {
+        let groups = [self.gid];
+        unsafe {
+            if libc::syscall(libc::SYS_setgroups, groups.len(), groups.as_ptr()) != 0
+                || libc::syscall(libc::SYS_setresgid, self.gid, self.gid, self.gid) != 0
+                || libc::syscall(libc::SYS_setresuid, self.uid, self.uid, self.uid) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop privileges and set HOME/USER/LOGNAME/SHELL.
+    /// Only call in a forked child before exec.
+    pub fn become_user(&self) -> io::Result<()> {
+        self.drop_privileges()?;
+        std::env::set_var("HOME", &self.home);
+        std::env::set_var("USER", &self.name);
+        std::env::set_var("LOGNAME", &self.name);
+        std::env::set_var("SHELL", &self.shell);
+        Ok(())
+    }
+}
+
+/// Build `sh -c command` running as the given user, in a new session with
+/// no controlling terminal and all stdio discarded.
+///
+/// Uses the same privilege drop as `Pty::spawn_as_user`. When bcon itself is
+/// not root, the command runs as the current user.
+fn user_command(uid: u32, command: &str) -> Result<std::process::Command> {
+    use std::os::unix::process::CommandExt;
+
+    let user = UserAccount::lookup(uid)?;
+    let is_root = unsafe { libc::getuid() } == 0;
+    if !is_root && uid != unsafe { libc::getuid() } {
+        return Err(anyhow!("cannot run as uid {} without root", uid));
+    }
+
+    let mut cmd = std::process::Command::new("/bin/sh");
+    cmd.args(["-c", command])
+        .current_dir(&user.home)
+        .env("HOME", &user.home)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name)
+        .env("SHELL", &user.shell)
+        .env("XDG_RUNTIME_DIR", format!("/run/user/{}", uid))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    unsafe {
+        cmd.pre_exec(move || {
+            libc::setsid();
+            if is_root {
+                user.drop_privileges()?;
+            }
+            Ok(())
+        });
+    }
+    Ok(cmd)
+}
+
+/// Run a shell command in the background as the given user, detached from
+/// bcon (new session, no controlling terminal, output discarded), with
+/// `env` added to its environment.
+pub fn spawn_detached_as_user(uid: u32, command: &str, env: &[(&str, String)]) -> Result<()> {
+    let mut cmd = user_command(uid, command)?;
+    cmd.envs(env.iter().map(|(k, v)| (k, v)));
+    let mut child = cmd.spawn()?;
+    info!("Spawned '{}' as uid={} (pid={})", command, uid, child.id());
+
+    // Reap in the background so the handler doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Run a shell command as the given user with `input` on its stdin and wait
+/// for it to exit. Killed (and reported as an error) after `timeout`.
+pub fn pipe_to_user_command(
+    uid: u32,
+    command: &str,
+    input: &[u8],
+    timeout: std::time::Duration,
+) -> Result<()> {
+    let mut child = user_command(uid, command)?
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+
+    // Feed stdin from a thread: a command that never reads must not block us
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_vec();
+        std::thread::spawn(move || {
+            let _ = io::Write::write_all(&mut stdin, &input);
+        });
+    }
+
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(anyhow!("'{}' exited with {}", command, status))
+            };
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("'{}' timed out", command));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+}
+
+/// Run a shell command as the given user and return its stdout.
+/// Killed (and reported as an error) after `timeout`.
+pub fn read_user_command(
+    uid: u32,
+    command: &str,
+    timeout: std::time::Duration,
+) -> Result<Vec<u8>> {
+    let mut child = user_command(uid, command)?
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        std::thread::spawn(move || {
+            let mut output = Vec::new();
+            let result = io::Read::read_to_end(&mut stdout, &mut output).map(|_| output);
+            let _ = tx.send(result);
+        });
+    }
+
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(output)) => {
+            let status = child.wait()?;
+            if status.success() {
+                Ok(output)
+            } else {
+                Err(anyhow!("'{}' exited with {}", command, status))
+            }
+        }
+        Ok(Err(e)) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            Err(e.into())
+        }
+        Err(_) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            Err(anyhow!("'{}' timed out", command))
+        }
+    }
+}
+
 impl Drop for Pty {
     fn drop(&mut self) {
         // Close master fd first — this causes EIO on the slave side,
@@ -824,3 +1049,26 @@ fn get_domainname() -> Option<String> {
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty() && s != "(none)")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_user_commands() {
+        let uid = unsafe { libc::getuid() };
+        let timeout = Duration::from_secs(5);
+
+        let output = read_user_command(uid, "printf 'a\\nb'", timeout).unwrap();
+        assert_eq!(output, b"a\nb");
+        pipe_to_user_command(uid, "test \"$(cat)\" = abc", b"abc", timeout).unwrap();
+        assert!(pipe_to_user_command(uid, "test \"$(cat)\" = abc", b"xyz", timeout).is_err());
+
+        // Commands are killed at the time limit
+        let start = std::time::Instant::now();
+        assert!(read_user_command(uid, "sleep 10", Duration::from_millis(100)).is_err());
+        assert!(pipe_to_user_command(uid, "sleep 10", b"", Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
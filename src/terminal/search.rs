@@ -0,0 +1,593 @@
+//! Scrollback search matching options
+//!
+//! The search bar can switch between literal and regex patterns and between
+//! case-sensitive, case-insensitive and smart-case matching. Both modes are
+//! compiled to a `Regex` (literal queries are escaped).
+//!
+//! Matching runs over logical lines: physical rows joined across soft wraps,
+//! so a URL or path broken by the right margin is still found.
+//!
+//! Scrollback is searched off the render thread. The grid keeps a
+//! `ScrollbackIndex` (the text of every scrollback row), built on the first
+//! search and then kept up to date as rows are pushed and evicted; a
+//! `ScanJob` worker scans it in chunks, streams matches back and then keeps
+//! following new rows until it is dropped. Only the screen itself is
+//! searched on the main thread.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+use regex::{Regex, RegexBuilder};
+
+use super::grid::Cell;
+
+/// Rows scanned per read lock by the worker
+const SCAN_CHUNK_ROWS: usize = 1024;
+
+/// How often a caught-up worker checks for new scrollback rows
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Spare row buffers kept for reuse after eviction
+const MAX_SPARE_ROWS: usize = 32;
+
+/// Case sensitivity of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMode {
+    /// Insensitive unless the query contains an uppercase letter
+    #[default]
+    Smart,
+    Insensitive,
+    Sensitive,
+}
+
+impl CaseMode {
+    /// Next mode when toggled from the search bar
+    pub fn next(self) -> Self {
+        match self {
+            CaseMode::Smart => CaseMode::Insensitive,
+            CaseMode::Insensitive => CaseMode::Sensitive,
+            CaseMode::Sensitive => CaseMode::Smart,
+        }
+    }
+
+    /// Short label for the search bar
+    pub fn label(self) -> &'static str {
+        match self {
+            CaseMode::Smart => "smart",
+            CaseMode::Insensitive => "nocase",
+            CaseMode::Sensitive => "case",
+        }
+    }
+}
+
+/// Search options toggled from the search bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression
+    pub regex: bool,
+    pub case: CaseMode,
+}
+
+/// Whether the query has an uppercase letter outside regex escapes (`\D`, `\W`, ...)
+fn has_uppercase(query: &str, regex: bool) -> bool {
+    let mut escaped = false;
+    for ch in query.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if regex && ch == '\\' {
+            escaped = true;
+            continue;
+        }
+        if ch.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Compile a query. Errors are returned as a short message for the search bar.
+pub fn build_matcher(query: &str, options: SearchOptions) -> Result<Regex, String> {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let insensitive = match options.case {
+        CaseMode::Smart => !has_uppercase(query, options.regex),
+        CaseMode::Insensitive => true,
+        CaseMode::Sensitive => false,
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(insensitive)
+        .build()
+        .map_err(|e| match e {
+            regex::Error::Syntax(msg) => msg
+                .lines()
+                .rev()
+                .find(|l| l.starts_with("error:"))
+                .map(|l| l.trim_start_matches("error:").trim().to_string())
+                .unwrap_or_else(|| "invalid pattern".to_string()),
+            _ => "pattern too large".to_string(),
+        })
+}
+
+/// A match, possibly spanning several rows of a soft-wrapped line.
+/// Rows are line numbers (see `ScrollbackIndex::first_line`); `end_col` is
+/// exclusive and belongs to `end_row`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub start_row: usize,
+    pub start_col: usize,
+    pub end_row: usize,
+    pub end_col: usize,
+}
+
+/// Searchable text of one row
+#[derive(Debug, Default)]
+pub struct IndexedRow {
+    text: String,
+    /// (col, width) of the cell each byte came from.
+    /// Empty while every byte is a single width-1 cell at its own column.
+    cols: Vec<(u16, u8)>,
+    /// Row soft-wraps into the next one
+    wrapped: bool,
+}
+
+impl IndexedRow {
+    /// Rebuild from cells (continuation cells of wide characters are skipped)
+    pub fn fill(&mut self, cells: &[Cell], wrapped: bool) {
+        self.text.clear();
+        self.cols.clear();
+        self.wrapped = wrapped;
+        let mut mapped = false;
+        for (col, cell) in cells.iter().enumerate() {
+            if cell.width == 0 {
+                continue;
+            }
+            let g = if cell.grapheme.is_empty() {
+                " "
+            } else {
+                cell.grapheme.as_str()
+            };
+            let width = cell.width.max(1);
+            if !mapped && (g.len() != 1 || width != 1 || self.text.len() != col) {
+                // First cell that breaks byte == column: switch to an explicit map
+                mapped = true;
+                self.cols
+                    .extend((0..self.text.len()).map(|i| (i as u16, 1)));
+            }
+            if mapped {
+                for _ in 0..g.len() {
+                    self.cols.push((col as u16, width));
+                }
+            }
+            self.text.push_str(g);
+        }
+    }
+
+    /// (col, width) of the cell at a byte offset
+    fn position(&self, byte: usize) -> (usize, usize) {
+        match self.cols.get(byte) {
+            Some(&(col, width)) => (col as usize, width as usize),
+            None => (byte, 1),
+        }
+    }
+}
+
+/// Text of one logical line, built row by row
+#[derive(Default)]
+pub struct LogicalLine {
+    text: String,
+    /// (line, col, width) of the cell each byte of `text` came from
+    positions: Vec<(usize, usize, usize)>,
+}
+
+impl LogicalLine {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.positions.clear();
+    }
+
+    /// Append a physical row
+    pub fn push_row(&mut self, line: usize, row: &IndexedRow) {
+        for byte in 0..row.text.len() {
+            let (col, width) = row.position(byte);
+            self.positions.push((line, col, width));
+        }
+        self.text.push_str(&row.text);
+    }
+
+    /// Find all non-empty matches of `re` and map them back to cell ranges
+    pub fn find(&self, re: &Regex, out: &mut Vec<SearchMatch>) {
+        for m in re.find_iter(&self.text) {
+            // Patterns like `a*` match the empty string everywhere
+            if m.start() == m.end() {
+                continue;
+            }
+            let (start_row, start_col, _) = self.positions[m.start()];
+            let (end_row, last_col, width) = self.positions[m.end() - 1];
+            out.push(SearchMatch {
+                start_row,
+                start_col,
+                end_row,
+                end_col: last_col + width,
+            });
+        }
+    }
+}
+
+#[derive(Default)]
+struct IndexInner {
+    /// Rows are kept (from the first search on; until then the index costs
+    /// nothing)
+    built: bool,
+    rows: VecDeque<IndexedRow>,
+    /// Line number of `rows[0]`
+    first_line: usize,
+    /// Bumped when the scrollback is rebuilt (resize reflow)
+    generation: u64,
+    spare: Vec<IndexedRow>,
+}
+
+/// Text of the scrollback, kept in step with `Grid::scrollback` and shared
+/// with search workers.
+///
+/// Rows are addressed by the grid's absolute line numbers (see
+/// `Terminal::first_line`): scrollback row `i` is line `first_line() + i`, and
+/// numbers stay the same as older rows are evicted. Screen row `r` continues
+/// the numbering at `first_line() + scrollback_len + r`.
+#[derive(Clone, Default)]
+pub struct ScrollbackIndex {
+    inner: Arc<RwLock<IndexInner>>,
+}
+
+impl ScrollbackIndex {
+    fn read(&self) -> RwLockReadGuard<'_, IndexInner> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, IndexInner> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A row was pushed onto the scrollback
+    pub fn push(&self, cells: &[Cell], wrapped: bool) {
+        let mut inner = self.write();
+        if !inner.built {
+            return;
+        }
+        let mut row = inner.spare.pop().unwrap_or_default();
+        row.fill(cells, wrapped);
+        inner.rows.push_back(row);
+    }
+
+    /// The oldest scrollback row was evicted
+    pub fn pop_front(&self) {
+        let mut inner = self.write();
+        if let Some(row) = inner.rows.pop_front() {
+            inner.first_line += 1;
+            if inner.spare.len() < MAX_SPARE_ROWS {
+                inner.spare.push(row);
+            }
+        }
+    }
+
+    /// The scrollback was rebuilt, its oldest row now being `first_line`;
+    /// running searches must start over
+    pub fn rebuild<'a>(
+        &self,
+        first_line: usize,
+        rows: impl Iterator<Item = (&'a [Cell], bool)>,
+    ) {
+        let mut inner = self.write();
+        inner.generation += 1;
+        if inner.built {
+            fill_rows(&mut inner, first_line, rows);
+        }
+    }
+
+    /// Build the index from the scrollback, unless already built: the
+    /// first search pays for it, not every terminal
+    pub fn build<'a>(
+        &self,
+        first_line: usize,
+        rows: impl Iterator<Item = (&'a [Cell], bool)>,
+    ) {
+        let mut inner = self.write();
+        if !inner.built {
+            inner.built = true;
+            fill_rows(&mut inner, first_line, rows);
+        }
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.read().built
+    }
+
+    /// Line number of the oldest scrollback row
+    pub fn first_line(&self) -> usize {
+        self.read().first_line
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.read().generation
+    }
+}
+
+fn fill_rows<'a>(
+    inner: &mut IndexInner,
+    first_line: usize,
+    rows: impl Iterator<Item = (&'a [Cell], bool)>,
+) {
+    inner.rows.clear();
+    inner.first_line = first_line;
+    for (cells, wrapped) in rows {
+        let mut row = IndexedRow::default();
+        row.fill(cells, wrapped);
+        inner.rows.push_back(row);
+    }
+}
+
+/// Results streamed from a search worker
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// Scrollback matches, in buffer order
+    Matches(Vec<SearchMatch>),
+    /// The initial scan reached the end of the scrollback
+    CaughtUp,
+    /// The scrollback was rebuilt under the worker
+    Invalidated,
+}
+
+/// Background scrollback scan. Dropping it cancels the worker.
+pub struct ScanJob {
+    cancel: Arc<AtomicBool>,
+    rx: mpsc::Receiver<ScanEvent>,
+}
+
+impl ScanJob {
+    /// Scan the whole scrollback, then follow new rows
+    pub fn spawn(index: ScrollbackIndex, re: Regex) -> Option<Self> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let worker_cancel = cancel.clone();
+        match std::thread::Builder::new()
+            .name("bcon-search".into())
+            .spawn(move || scan_worker(index, re, worker_cancel, tx))
+        {
+            Ok(_) => Some(Self { cancel, rx }),
+            Err(e) => {
+                log::warn!("Failed to start search thread: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Get all pending events
+    pub fn poll(&self) -> Vec<ScanEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+}
+
+impl Drop for ScanJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Search worker main function
+fn scan_worker(
+    index: ScrollbackIndex,
+    re: Regex,
+    cancel: Arc<AtomicBool>,
+    tx: mpsc::Sender<ScanEvent>,
+) {
+    let (generation, mut next) = {
+        let inner = index.read();
+        (inner.generation, inner.first_line)
+    };
+    let mut line = LogicalLine::default();
+    let mut caught_up = false;
+
+    while !cancel.load(Ordering::Relaxed) {
+        let mut found = Vec::new();
+        let at_end = {
+            let inner = index.read();
+            if inner.generation != generation {
+                let _ = tx.send(ScanEvent::Invalidated);
+                return;
+            }
+            // Rows evicted before we got to them are gone
+            if next < inner.first_line {
+                line.clear();
+                next = inner.first_line;
+            }
+            let end = inner.first_line + inner.rows.len();
+            let stop = (next + SCAN_CHUNK_ROWS).min(end);
+            for n in next..stop {
+                let row = &inner.rows[n - inner.first_line];
+                line.push_row(n, row);
+                // The last line of the scrollback may continue on screen;
+                // it is only searched here once it is terminated
+                if !row.wrapped {
+                    line.find(&re, &mut found);
+                    line.clear();
+                }
+            }
+            next = stop;
+            stop == end
+        };
+
+        if !found.is_empty() && tx.send(ScanEvent::Matches(found)).is_err() {
+            return;
+        }
+        if at_end {
+            if !caught_up {
+                caught_up = true;
+                if tx.send(ScanEvent::CaughtUp).is_err() {
+                    return;
+                }
+            }
+            std::thread::sleep(FOLLOW_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::wait_for;
+
+    fn opts(regex: bool, case: CaseMode) -> SearchOptions {
+        SearchOptions { regex, case }
+    }
+
+    #[test]
+    fn test_literal_and_case() {
+        let m = build_matcher("a.b", opts(false, CaseMode::Smart)).unwrap();
+        assert!(m.is_match("xA.By"));
+        assert!(!m.is_match("axb"));
+
+        // Uppercase in the query makes smart case sensitive
+        let m = build_matcher("Error", opts(false, CaseMode::Smart)).unwrap();
+        assert!(!m.is_match("error"));
+        let m = build_matcher("Error", opts(false, CaseMode::Insensitive)).unwrap();
+        assert!(m.is_match("error"));
+        let m = build_matcher("error", opts(false, CaseMode::Sensitive)).unwrap();
+        assert!(!m.is_match("ERROR"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let m = build_matcher(r"error\[E\d+\]", opts(true, CaseMode::Smart)).unwrap();
+        assert!(m.is_match("error[E0308]: mismatched types"));
+        // Escapes like \D don't count as uppercase for smart case
+        let m = build_matcher(r"\Dfoo", opts(true, CaseMode::Smart)).unwrap();
+        assert!(m.is_match("xFOO"));
+        let err = build_matcher("error[", opts(true, CaseMode::Smart)).unwrap_err();
+        assert!(!err.is_empty());
+    }
+
+    fn cells(text: &str) -> Vec<Cell> {
+        text.chars()
+            .map(|ch| Cell {
+                grapheme: ch.to_string().into(),
+                ..Cell::default()
+            })
+            .collect()
+    }
+
+    fn row(text: &str, wrapped: bool) -> IndexedRow {
+        let mut row = IndexedRow::default();
+        row.fill(&cells(text), wrapped);
+        row
+    }
+
+    #[test]
+    fn test_match_across_wrap() {
+        let mut line = LogicalLine::default();
+        line.push_row(10, &row("see https://exa", true));
+        line.push_row(11, &row("mple.com/x here", false));
+        let re = build_matcher(r"https://\S+", opts(true, CaseMode::Smart)).unwrap();
+        let mut out = Vec::new();
+        line.find(&re, &mut out);
+        assert_eq!(
+            out,
+            vec![SearchMatch {
+                start_row: 10,
+                start_col: 4,
+                end_row: 11,
+                end_col: 10,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wide_char_columns() {
+        let mut wide = cells("日本 x");
+        // "日" and "本" take two columns each
+        wide[0].width = 2;
+        wide[1].width = 2;
+        wide.insert(
+            1,
+            Cell {
+                width: 0,
+                ..Cell::default()
+            },
+        );
+        wide.insert(
+            3,
+            Cell {
+                width: 0,
+                ..Cell::default()
+            },
+        );
+        let mut line = LogicalLine::default();
+        let mut indexed = IndexedRow::default();
+        indexed.fill(&wide, false);
+        line.push_row(0, &indexed);
+        let re = build_matcher("本 x", opts(false, CaseMode::Smart)).unwrap();
+        let mut out = Vec::new();
+        line.find(&re, &mut out);
+        assert_eq!((out[0].start_col, out[0].end_col), (2, 6));
+    }
+
+    #[test]
+    fn test_scan_job_follows_new_rows() {
+        let index = ScrollbackIndex::default();
+        // Unbuilt, the index keeps nothing
+        index.push(&cells("dropped"), false);
+        index.build(0, std::iter::empty());
+        index.push(&cells("error one"), false);
+        index.push(&cells("fine"), false);
+        index.push(&cells("wrapped err"), true);
+        let re = build_matcher("error", opts(false, CaseMode::Smart)).unwrap();
+        let job = ScanJob::spawn(index.clone(), re).unwrap();
+
+        let matches = || {
+            wait_for(|| {
+                let mut got = Vec::new();
+                for event in job.poll() {
+                    if let ScanEvent::Matches(m) = event {
+                        got.extend(m);
+                    }
+                }
+                Some(got).filter(|m| !m.is_empty())
+            })
+        };
+        // The unterminated last line is not reported yet
+        let first = matches();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].start_row, 0);
+
+        // Terminating it and evicting the oldest row keeps line numbers stable
+        index.push(&cells("or here"), false);
+        index.pop_front();
+        assert_eq!(index.first_line(), 1);
+        let more = matches();
+        assert_eq!(more.len(), 1);
+        assert_eq!((more[0].start_row, more[0].start_col), (2, 8));
+        assert_eq!((more[0].end_row, more[0].end_col), (3, 2));
+    }
+
+    #[test]
+    fn test_case_cycle() {
+        let mut c = CaseMode::Smart;
+        for _ in 0..3 {
+            c = c.next();
+        }
+        assert_eq!(c, CaseMode::Smart);
+    }
+}
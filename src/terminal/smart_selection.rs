@@ -0,0 +1,232 @@
+//! Double and triple click selection
+//!
+//! A double click first tries the smart selection rules in priority order
+//! (user rules, then URL, email, path and quoted string); the first match
+//! under the pointer is selected. Otherwise the word around the pointer is
+//! selected, made of letters, digits and `word_chars`. Both work on the
+//! logical line, so a URL soft-wrapped over two rows is selected whole, and
+//! a triple click selects the whole logical line.
+
+use regex::Regex;
+
+use super::{Selection, Terminal};
+
+/// Built-in rules, in priority order: (name, regex, trim trailing punctuation)
+const BUILTIN_RULES: &[(&str, &str, bool)] = &[
+    ("url", r#"(?:https?|ftp|file)://[^\s<>"'`]+"#, true),
+    ("email", r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+", false),
+    (
+        "path",
+        r"(?:~|\.{1,2})?(?:[\w.+@-]+)?(?:/[\w.+@-]+)+/?",
+        true,
+    ),
+    ("double_quoted", r#""([^"]+)""#, false),
+    ("single_quoted", r"'([^']+)'", false),
+];
+
+struct Rule {
+    name: String,
+    re: Regex,
+    trim: bool,
+}
+
+/// Compiled word characters and smart selection rules
+#[derive(Default)]
+pub struct SelectionRules {
+    word_chars: String,
+    rules: Vec<Rule>,
+}
+
+impl SelectionRules {
+    /// Compile user rules (highest priority) followed by the built-ins.
+    /// Invalid user rules are logged and skipped.
+    pub fn new(word_chars: &str, user: &[(String, String)], builtin: bool) -> Self {
+        let mut rules = Vec::new();
+        for (name, re) in user {
+            match Regex::new(re) {
+                Ok(re) => rules.push(Rule {
+                    name: name.clone(),
+                    re,
+                    trim: false,
+                }),
+                Err(e) => log::warn!("Invalid selection rule '{}': {}", name, e),
+            }
+        }
+        if builtin {
+            for (name, re, trim) in BUILTIN_RULES {
+                rules.push(Rule {
+                    name: name.to_string(),
+                    re: Regex::new(re).expect("builtin selection rule"),
+                    trim: *trim,
+                });
+            }
+        }
+        Self {
+            word_chars: word_chars.to_string(),
+            rules,
+        }
+    }
+
+    fn is_word_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || self.word_chars.contains(c)
+    }
+
+    /// Byte range to select in `text` for a double click at byte `click`
+    pub fn select_at(&self, text: &str, click: usize) -> Option<(usize, usize)> {
+        for rule in &self.rules {
+            for caps in rule.re.captures_iter(text) {
+                let m = caps.get(1).or_else(|| caps.get(0))?;
+                let end = if rule.trim {
+                    m.start() + super::hints::trim_match(m.as_str()).len()
+                } else {
+                    m.end()
+                };
+                if m.start() <= click && click < end {
+                    log::debug!("Smart selection: {}", rule.name);
+                    return Some((m.start(), end));
+                }
+            }
+        }
+
+        let ch = text[click..].chars().next()?;
+        if !self.is_word_char(ch) {
+            // Other non-blank characters select themselves
+            return (!ch.is_whitespace()).then(|| (click, click + ch.len_utf8()));
+        }
+        let start = text[..click]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| self.is_word_char(c))
+            .last()
+            .map_or(click, |(i, _)| i);
+        let end = text[click..]
+            .char_indices()
+            .find(|&(_, c)| !self.is_word_char(c))
+            .map_or(text.len(), |(i, _)| click + i);
+        Some((start, end))
+    }
+}
+
+impl Terminal {
+    /// First and last absolute line of the logical line containing `line`
+    fn logical_line_bounds(&self, line: u64) -> (u64, u64) {
+        let bottom = self.grid.scrollback_total() + self.grid.rows() as u64 - 1;
+        let mut first = line;
+        while first > self.first_line() && self.line_wrapped(first - 1) {
+            first -= 1;
+        }
+        let mut last = line;
+        while last < bottom && self.line_wrapped(last) {
+            last += 1;
+        }
+        (first, last)
+    }
+
+    /// Double click: smart selection, falling back to the word under the pointer
+    pub fn select_word(&mut self, row: usize, col: usize, rules: &SelectionRules) {
+        let line = self.display_line(row);
+        let (first, last) = self.logical_line_bounds(line);
+        let cols = self.grid.cols();
+
+        // Logical line text, with the (line, col, width) of every byte
+        let mut text = String::new();
+        let mut cells: Vec<(u64, usize, usize)> = Vec::new();
+        let mut click = None;
+        for l in first..=last {
+            for c in 0..cols {
+                let cell = self.line_cell(l, c);
+                if cell.width == 0 {
+                    continue; // Wide character continuation cell
+                }
+                if l == line && c <= col {
+                    click = Some(text.len());
+                }
+                let g = if cell.grapheme.is_empty() {
+                    " "
+                } else {
+                    cell.grapheme.as_str()
+                };
+                cells.extend(std::iter::repeat_n((l, c, cell.width as usize), g.len()));
+                text.push_str(g);
+            }
+        }
+
+        let range = click.and_then(|click| rules.select_at(&text, click));
+        let Some((start, end)) = range else {
+            self.selection = None;
+            return;
+        };
+        let (anchor_row, anchor_col, _) = cells[start];
+        let (end_row, end_col, width) = cells[end - 1];
+        // Both anchor_col and end_col are inclusive cell positions
+        self.selection = Some(Selection {
+            anchor_row,
+            anchor_col,
+            end_row,
+            end_col: end_col + width.max(1) - 1,
+            block: false,
+        });
+        self.selection_to_primary();
+    }
+
+    /// Triple click: the whole logical line (soft-wrapped rows included)
+    pub fn select_line(&mut self, row: usize) {
+        let cols = self.grid.cols();
+        let (first, last) = self.logical_line_bounds(self.display_line(row));
+        self.selection = Some(Selection {
+            anchor_row: first,
+            anchor_col: 0,
+            end_row: last,
+            end_col: cols - 1,
+            block: false,
+        });
+        self.selection_to_primary();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(rules: &SelectionRules, text: &str, at: &str) -> Option<String> {
+        let click = text.find(at)?;
+        rules
+            .select_at(text, click)
+            .map(|(s, e)| text[s..e].to_string())
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let rules = SelectionRules::new("@-./_~?&=%+#", &[], true);
+        let text = "see https://e.com/a?b=c, mail a.b@x.org or ~/src/x.rs (\"two words\")";
+        assert_eq!(
+            select(&rules, text, "e.com").as_deref(),
+            Some("https://e.com/a?b=c")
+        );
+        assert_eq!(select(&rules, text, "b@x").as_deref(), Some("a.b@x.org"));
+        assert_eq!(select(&rules, text, "src").as_deref(), Some("~/src/x.rs"));
+        assert_eq!(select(&rules, text, "words").as_deref(), Some("two words"));
+        assert_eq!(select(&rules, text, "mail").as_deref(), Some("mail"));
+        assert_eq!(select(&rules, text, " or").as_deref(), None);
+        assert_eq!(select(&rules, text, ",").as_deref(), Some(","));
+    }
+
+    #[test]
+    fn test_word_chars_and_user_rules() {
+        let rules = SelectionRules::new("_", &[], false);
+        assert_eq!(
+            select(&rules, "foo_bar-baz", "bar").as_deref(),
+            Some("foo_bar")
+        );
+        assert_eq!(
+            select(&rules, "日本語 text", "本").as_deref(),
+            Some("日本語")
+        );
+
+        let user = [("ticket".to_string(), r"#(\d+)".to_string())];
+        let rules = SelectionRules::new("", &user, true);
+        assert_eq!(select(&rules, "fixes #123.", "12").as_deref(), Some("123"));
+        // Clicking outside the capture group falls through
+        assert_eq!(select(&rules, "fixes #123.", "#").as_deref(), Some("#"));
+    }
+}
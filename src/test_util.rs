@@ -0,0 +1,31 @@
+//! Helpers shared by unit tests
+
+use std::time::{Duration, Instant};
+
+/// How long `wait_for` polls before failing the test
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Path in the temp directory for `name`, unique to this test process
+pub fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("bcon_{}_{}", name, std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Poll `f` until it returns a value, failing the test after TIMEOUT
+pub fn wait_for<T>(mut f: impl FnMut() -> Option<T>) -> T {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        if let Some(value) = f() {
+            return value;
+        }
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Poll until `cond` holds, failing the test after TIMEOUT
+pub fn wait_until(mut cond: impl FnMut() -> bool) {
+    wait_for(|| cond().then_some(()));
+}
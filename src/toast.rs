@@ -0,0 +1,236 @@
+//! Toast notifications
+//!
+//! OSC 9 / OSC 99 notifications from every pane pop up as toasts in the
+//! bottom-right corner. OSC 99 `o=` decides whether one is shown at all and
+//! `w=` how long it stays. The newest toast can be activated from the
+//! keyboard (`notification_activate`), which focuses its pane and tab like a
+//! click on a desktop notification. The application is told when its toast
+//! is activated (`a=report`) or closed (`c=1`), with the same OSC 99 reports
+//! as [`desktop_notify`].
+//!
+//! [`desktop_notify`]: crate::terminal::desktop_notify
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::pane::tab::TabManager;
+use crate::pane::PaneId;
+use crate::terminal::Notification;
+
+/// How long a toast stays without OSC 99 `w=`
+const DEFAULT_DURATION: Duration = Duration::from_secs(5);
+
+/// Maximum toasts kept (only the newest few are drawn)
+const MAX_TOASTS: usize = 50;
+
+/// A notification on screen
+pub struct Toast {
+    /// Pane that sent it
+    pub pane: PaneId,
+    pub notification: Notification,
+    /// None: stays until activated or dismissed (OSC 99 w=0)
+    show_until: Option<Instant>,
+}
+
+/// Toasts on screen, oldest first
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    /// Last notification_seq seen per pane
+    seen: HashMap<PaneId, u64>,
+    /// OSC 99 reports to write to panes
+    reports: Vec<(PaneId, String)>,
+    /// Report closed toasts (c=1). Off while desktop notifications are
+    /// enabled: the notification daemon reports those.
+    pub close_reports: bool,
+}
+
+impl Toasts {
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Toasts, oldest first
+    pub fn iter(&self) -> std::slice::Iter<'_, Toast> {
+        self.toasts.iter()
+    }
+
+    /// Pick up new notifications from every pane. `vt_active`: bcon's VT is
+    /// on screen. Returns whether the toasts changed.
+    pub fn collect(&mut self, tab_mgr: &TabManager, vt_active: bool, muted: bool) -> bool {
+        let now = Instant::now();
+        let before = self.toasts.len();
+        self.seen.retain(|id, _| tab_mgr.tab_of(*id).is_some());
+        self.toasts.retain(|t| tab_mgr.tab_of(t.pane).is_some());
+        let mut changed = self.toasts.len() != before;
+        for tab in &tab_mgr.tabs {
+            for (id, pane) in &tab.panes {
+                let term = &pane.terminal;
+                let last = self.seen.insert(*id, term.notification_seq).unwrap_or(0);
+                if muted || term.notification_seq <= last {
+                    continue;
+                }
+                let (focused, visible) = pane_focus(tab_mgr, *id, vt_active);
+                // New notifications are at the tail of the history
+                let new = (term.notification_seq - last) as usize;
+                let skip = term.notifications.len().saturating_sub(new);
+                for n in term.notifications.iter().skip(skip) {
+                    if n.should_show(focused, visible) {
+                        self.push(*id, n.clone(), now);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    /// Show a notification from `pane`, replacing its toast with the same id
+    fn push(&mut self, pane: PaneId, notification: Notification, now: Instant) {
+        if notification.id.is_some() {
+            self.toasts
+                .retain(|t| t.pane != pane || t.notification.id != notification.id);
+        }
+        let show_until = match notification.expire_ms {
+            0 => None,
+            ms if ms > 0 => Some(now + Duration::from_millis(ms as u64)),
+            _ => Some(now + DEFAULT_DURATION),
+        };
+        self.toasts.push(Toast {
+            pane,
+            notification,
+            show_until,
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            let oldest = self.toasts.remove(0);
+            self.report_close(&oldest);
+        }
+    }
+
+    /// The application closed its notification (OSC 99 p=close)
+    pub fn close(&mut self, pane: PaneId, id: &str) -> bool {
+        let (closed, kept) = std::mem::take(&mut self.toasts)
+            .into_iter()
+            .partition(|t| t.pane == pane && t.notification.id.as_deref() == Some(id));
+        self.toasts = kept;
+        let closed: Vec<Toast> = closed;
+        for toast in &closed {
+            self.report_close(toast);
+        }
+        !closed.is_empty()
+    }
+
+    /// Remove expired toasts. Returns whether any expired.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let (expired, kept) = std::mem::take(&mut self.toasts)
+            .into_iter()
+            .partition(|t| t.show_until.is_some_and(|until| now >= until));
+        self.toasts = kept;
+        let expired: Vec<Toast> = expired;
+        for toast in &expired {
+            self.report_close(toast);
+        }
+        !expired.is_empty()
+    }
+
+    /// Dismiss every toast
+    pub fn clear(&mut self) {
+        for toast in std::mem::take(&mut self.toasts) {
+            self.report_close(&toast);
+        }
+    }
+
+    /// Activate the newest toast: returns its pane, if it wants focus
+    pub fn activate(&mut self) -> Option<PaneId> {
+        let toast = self.toasts.pop()?;
+        if let Some(report) = toast.notification.activation_report() {
+            self.reports.push((toast.pane, report));
+        }
+        self.report_close(&toast);
+        toast.notification.focus_on_activate.then_some(toast.pane)
+    }
+
+    /// OSC 99 reports to write to panes since the last call
+    pub fn take_reports(&mut self) -> Vec<(PaneId, String)> {
+        std::mem::take(&mut self.reports)
+    }
+
+    fn report_close(&mut self, toast: &Toast) {
+        if !self.close_reports {
+            return;
+        }
+        if let Some(report) = toast.notification.close_report() {
+            self.reports.push((toast.pane, report));
+        }
+    }
+}
+
+/// Whether a pane has the keyboard focus, and whether it is on screen
+/// (OSC 99 `o=unfocused` / `o=invisible`). `vt_active`: bcon's VT is shown.
+pub fn pane_focus(tab_mgr: &TabManager, id: PaneId, vt_active: bool) -> (bool, bool) {
+    let tab = tab_mgr.active_tab();
+    let visible = vt_active
+        && tab.panes.contains_key(&id)
+        && tab.zoomed_pane.is_none_or(|zoomed| zoomed == id);
+    (visible && tab.active_pane == id, visible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(id: &str, title: &str, expire_ms: i32) -> Notification {
+        let mut n = Notification::new(Some(id.to_string()), 1);
+        n.title = title.to_string();
+        n.expire_ms = expire_ms;
+        n.report_activation = true;
+        n.report_close = true;
+        n
+    }
+
+    #[test]
+    fn test_toasts() {
+        let now = Instant::now();
+        let mut toasts = Toasts {
+            close_reports: true,
+            ..Default::default()
+        };
+        toasts.push(PaneId(1), notification("build", "Building", -1), now);
+        toasts.push(PaneId(2), notification("build", "Other pane", 0), now);
+        // Same id from the same pane replaces the toast
+        toasts.push(PaneId(1), notification("build", "Done", 1000), now);
+        let titles: Vec<_> = toasts
+            .iter()
+            .map(|t| t.notification.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Other pane", "Done"]);
+
+        // w=1000 expires, w=0 stays
+        assert!(!toasts.expire(now + Duration::from_millis(500)));
+        assert!(toasts.expire(now + Duration::from_secs(60)));
+        assert_eq!(toasts.iter().count(), 1);
+        assert_eq!(
+            toasts.take_reports(),
+            vec![(PaneId(1), "\x1b]99;i=build:p=close;\x1b\\".to_string())]
+        );
+
+        assert_eq!(toasts.activate(), Some(PaneId(2)));
+        assert_eq!(
+            toasts.take_reports(),
+            vec![
+                (PaneId(2), "\x1b]99;i=build;\x1b\\".to_string()),
+                (PaneId(2), "\x1b]99;i=build:p=close;\x1b\\".to_string()),
+            ]
+        );
+        assert_eq!(toasts.activate(), None);
+
+        // OSC 99 p=close from the application; no close reports while the
+        // desktop notification daemon reports them
+        toasts.push(PaneId(3), notification("x", "X", -1), now);
+        toasts.close_reports = false;
+        assert!(!toasts.close(PaneId(1), "x"));
+        assert!(toasts.close(PaneId(3), "x"));
+        assert!(toasts.is_empty());
+        assert!(toasts.take_reports().is_empty());
+    }
+}